@@ -2,6 +2,12 @@ use anyhow::Result;
 use std::io::Read;
 use std::path::{Path, PathBuf};
 
+use crate::system_registry::SystemRegistry;
+
+#[cfg(feature = "archive-support")]
+use anyhow::Context;
+#[cfg(feature = "archive-support")]
+use std::io::Seek;
 #[cfg(feature = "archive-support")]
 use zip::ZipArchive;
 
@@ -15,6 +21,8 @@ pub struct ArchiveEntry {
     pub size: u64,
     pub is_rom: bool,
     pub extension: String,
+    /// CRC32 recorded in the ZIP central directory or 7z header, if any
+    pub crc32: Option<u32>,
 }
 
 /// Archive reader trait
@@ -51,9 +59,10 @@ impl<R: Read + Seek> ArchiveReader for ZipReader<R> {
             
             let name = file.name().to_string();
             let size = file.size();
+            let crc32 = Some(file.crc32());
             
             // Skip directories
-            if name.ends_with('/') {
+            if file.is_dir() {
                 continue;
             }
             
@@ -70,6 +79,7 @@ impl<R: Read + Seek> ArchiveReader for ZipReader<R> {
                 size,
                 is_rom,
                 extension,
+                crc32,
             });
         }
         
@@ -104,11 +114,8 @@ pub struct SevenZipReader {
 #[cfg(feature = "archive-support")]
 impl SevenZipReader {
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let file = std::fs::File::open(path.as_ref())
-            .with_context(|| format!("Failed to open 7z file: {}", path.as_ref().display()))?;
-        
-        let reader = SevenZReader::new(file, 0, Password::empty()).map_err(|e| {
-            anyhow::anyhow!("Failed to create 7z reader: {}", e)
+        let reader = SevenZReader::open(path.as_ref(), Password::empty()).map_err(|e| {
+            anyhow::anyhow!("Failed to open 7z file {}: {}", path.as_ref().display(), e)
         })?;
         
         Ok(Self { reader })
@@ -122,13 +129,14 @@ impl ArchiveReader for SevenZipReader {
         
         let archive = self.reader.archive();
         
-        for (_i, entry) in archive.files.iter().enumerate() {
+        for entry in &archive.files {
             if entry.is_directory() {
                 continue;
             }
             
             let name = entry.name().to_string();
             let size = entry.size();
+            let crc32 = entry.has_crc.then_some(entry.crc as u32);
             
             let path = Path::new(&name);
             let extension = path.extension()
@@ -143,6 +151,7 @@ impl ArchiveReader for SevenZipReader {
                 size,
                 is_rom,
                 extension,
+                crc32,
             });
         }
         
@@ -150,22 +159,21 @@ impl ArchiveReader for SevenZipReader {
     }
     
     fn extract_entry(&mut self, entry_name: &str) -> Result<Vec<u8>> {
-        let mut data = Vec::new();
+        let mut data = None;
         
         self.reader.for_each_entries(|entry, reader| {
             if entry.name() == entry_name {
-                if std::io::copy(reader, &mut data).is_ok() {
-                    return Ok(true); // Stop iteration
-                }
+                let mut buffer = Vec::with_capacity(entry.size() as usize);
+                reader.read_to_end(&mut buffer)?;
+                data = Some(buffer);
+                return Ok(false); // Stop iteration
             }
-            Ok(false) // Continue iteration
+            // Solid blocks must be decoded in order, so drain skipped entries
+            std::io::copy(reader, &mut std::io::sink())?;
+            Ok(true) // Continue iteration
         }).map_err(|e| anyhow::anyhow!("Failed to extract 7z entry: {}", e))?;
         
-        if data.is_empty() {
-            anyhow::bail!("Entry '{}' not found in 7z archive", entry_name);
-        }
-        
-        Ok(data)
+        data.ok_or_else(|| anyhow::anyhow!("Entry '{}' not found in 7z archive", entry_name))
     }
     
     fn get_entry_reader(&mut self, entry_name: &str) -> Result<Box<dyn Read + '_>> {
@@ -175,33 +183,10 @@ impl ArchiveReader for SevenZipReader {
     }
 }
 
-/// Check if extension is a ROM extension
+/// Check if extension is a ROM extension claimed in configs/systems.toml
 fn is_rom_extension(extension: &str) -> bool {
-    matches!(extension,
-        // Nintendo
-        "nes" | "fds" | "unf" | "unif" |
-        "smc" | "sfc" | "swc" | "fig" |
-        "z64" | "n64" | "v64" |
-        "gcm" | "iso" | "gcz" | "rvz" |
-        "gb" | "gbc" | "gba" |
-        "nds" | "dsi" | "ids" |
-        "3ds" | "cci" | "cxi" |
-        
-        // Sega
-        "sms" | "sg" |
-        "md" | "smd" | "gen" | "bin" |
-        "32x" | "gg" |
-        "cdi" | "gdi" | "chd" |
-        
-        // Sony
-        "pbp" | "cue" | "img" | "mdf" | "nrg" | "cso" |
-        
-        // Atari
-        "a26" | "a78" |
-        
-        // Others
-        "pce" | "sgx" | "ws" | "wsc" | "neo"
-    )
+    !matches!(extension, "zip" | "7z" | "rar")
+        && SystemRegistry::global().is_known_extension(extension)
 }
 
 /// Archive format detection
@@ -288,11 +273,7 @@ pub struct VirtualRomFile {
 
 impl VirtualRomFile {
     pub fn new(archive_path: PathBuf, entry: ArchiveEntry) -> Self {
-        let virtual_path = archive_path
-            .with_file_name(format!("{}#{}", 
-                archive_path.file_name().unwrap().to_string_lossy(),
-                entry.name
-            ));
+        let virtual_path = Self::entry_path(&archive_path, &entry.name);
         
         Self {
             archive_path,
@@ -303,6 +284,14 @@ impl VirtualRomFile {
         }
     }
     
+    /// RetroArch's `archive.zip#inner.rom` path for an entry
+    pub fn entry_path(archive_path: &Path, entry_name: &str) -> PathBuf {
+        let mut path = archive_path.as_os_str().to_os_string();
+        path.push("#");
+        path.push(entry_name);
+        PathBuf::from(path)
+    }
+    
     /// Extract ROM data from archive
     #[cfg(feature = "archive-support")]
     pub fn extract_data(&self) -> Result<Vec<u8>> {
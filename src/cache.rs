@@ -1,40 +1,194 @@
 use anyhow::{Result, Context};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use parking_lot::Mutex;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use crate::cache_log::{self, LogRecord};
+use crate::crc32::{CrcVariant, CrcVariants, Digests};
+use crate::error::CacheError;
+
+/// Changes written between automatic saves, so an interrupted scan keeps its work
+const CHECKPOINT_INTERVAL: usize = 500;
+/// Log size below which superseded records are never compacted away
+const COMPACTION_MIN_RECORDS: usize = 1000;
+/// A cache hit rewrites the entry's access time at most once per day
+const ACCESS_REFRESH_SECS: u64 = 24 * 60 * 60;
+
+/// Cache shared between the scanner's worker threads
+pub type SharedCrcCache = Arc<Mutex<CrcCache>>;
+
+/// Bytes read from each end of a file for its content fingerprint
+const FINGERPRINT_CHUNK: u64 = 64 * 1024;
+
+/// Size and modification time a cache entry is validated against, plus an
+/// optional content fingerprint used to recognize the file after a move
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FileStamp {
+    pub size: u64,
+    pub modified: u64,
+    pub fingerprint: Option<u64>,
+}
+
+impl FileStamp {
+    pub fn from_metadata(metadata: &fs::Metadata) -> Self {
+        let modified = metadata.modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+
+        Self {
+            size: metadata.len(),
+            modified,
+            fingerprint: None,
+        }
+    }
+
+    pub fn with_fingerprint(mut self, fingerprint: Option<u64>) -> Self {
+        self.fingerprint = fingerprint;
+        self
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let metadata = fs::metadata(path)
+            .with_context(|| format!("Failed to get metadata for: {}", path.display()))?;
+        Ok(Self::from_metadata(&metadata))
+    }
+}
+
+/// Cheap identity of a file's content: CRC32 of its first and last 64 KiB.
+/// Together with the size it recognizes a file that was renamed, moved or
+/// found under a different mount point without hashing all of it.
+pub fn content_fingerprint(path: &Path) -> Result<u64> {
+    let mut file = File::open(path)
+        .with_context(|| format!("Failed to open file: {}", path.display()))?;
+    let size = file.metadata()?.len();
+
+    let mut chunk = Vec::with_capacity(FINGERPRINT_CHUNK.min(size) as usize);
+    (&mut file).take(FINGERPRINT_CHUNK).read_to_end(&mut chunk)?;
+    let head = crc32fast::hash(&chunk);
+
+    let tail = if size > FINGERPRINT_CHUNK {
+        // The tail never overlaps the head
+        chunk.clear();
+        file.seek(SeekFrom::Start((size - FINGERPRINT_CHUNK).max(FINGERPRINT_CHUNK)))?;
+        file.read_to_end(&mut chunk)?;
+        crc32fast::hash(&chunk)
+    } else {
+        head
+    };
+
+    Ok(((head as u64) << 32) | tail as u64)
+}
+
+/// Fingerprint of an archive entry, derived from the archive's fingerprint
+pub fn entry_fingerprint(archive_fingerprint: u64, entry_name: &str) -> u64 {
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(&archive_fingerprint.to_le_bytes());
+    hasher.update(entry_name.as_bytes());
+    (archive_fingerprint & 0xFFFF_FFFF_0000_0000) | hasher.finalize() as u64
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Whether the file behind a cache key still exists. Archive entries are keyed
+/// as `archive.zip#entry`, so for those the archive itself is checked.
+fn source_exists(key: &Path) -> bool {
+    key.exists()
+        || key.to_string_lossy()
+            .rsplit_once('#')
+            .is_some_and(|(archive, _)| Path::new(archive).exists())
+}
+
 /// Cache entry for CRC32 calculations
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CacheEntry {
     pub crc32: u32,
+    /// CRC32 with the copier header stripped by a header skipper
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headerless_crc32: Option<u32>,
+    /// Variant that matched a DAT entry the last time this file was validated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched_variant: Option<CrcVariant>,
+    /// Size/MD5/SHA1 of the raw and headerless streams
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_digests: Option<Digests>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headerless_digests: Option<Digests>,
+    /// See [`content_fingerprint`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<u64>,
+    /// Hashes were carried over from another path with the same fingerprint
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub relinked: bool,
     pub file_size: u64,
     pub modified_time: u64,
     pub last_accessed: u64,
 }
 
-/// Persistent CRC32 cache to avoid recalculating hashes
-#[derive(Debug, Default, Serialize, Deserialize)]
+impl CacheEntry {
+    fn variants(&self) -> CrcVariants {
+        CrcVariants {
+            raw: self.crc32,
+            headerless: self.headerless_crc32,
+            skipper: None,
+            raw_digests: self.raw_digests.clone(),
+            headerless_digests: self.headerless_digests.clone(),
+        }
+    }
+}
+
+/// Layout of the JSON cache written by earlier versions, kept for migration
+#[derive(Deserialize)]
+struct LegacyJsonCache {
+    entries: HashMap<PathBuf, CacheEntry>,
+}
+
+/// Persistent CRC32 cache to avoid recalculating hashes.
+///
+/// Changes are appended to a binary log (see [`crate::cache_log`]) and the log
+/// is rewritten as a snapshot once superseded records outnumber live ones.
+/// The owning process holds an exclusive lock on the file; other processes
+/// open it read-only.
+#[derive(Debug, Default)]
 pub struct CrcCache {
     entries: HashMap<PathBuf, CacheEntry>,
-    #[serde(skip)]
+    /// Content fingerprint -> key of the entry last stored with it
+    by_fingerprint: HashMap<u64, PathBuf>,
     cache_file: Option<PathBuf>,
-    #[serde(skip)]
-    dirty: bool,
+    /// Held while this instance may write to `cache_file`
+    lock: Option<File>,
+    /// Changes not yet appended to the log
+    pending: Vec<LogRecord>,
+    /// Length of the intact part of the log on disk
+    log_len: u64,
+    /// Records in the log on disk, including superseded ones
+    log_records: usize,
+    /// Rewrite the whole log on the next save instead of appending
+    needs_compaction: bool,
+    hits: u64,
+    misses: u64,
+    relinked: u64,
 }
 
 impl CrcCache {
     /// Create a new cache instance
     pub fn new() -> Self {
-        Self {
-            entries: HashMap::new(),
-            cache_file: None,
-            dirty: false,
-        }
+        Self::default()
     }
 
-    /// Create a new cache with default location
+    /// Create a new cache with default location. A JSON cache left by an
+    /// earlier version is imported and renamed to `crc32_cache.json.migrated`.
     pub fn with_default_location() -> Result<Self> {
         let cache_dir = dirs::cache_dir()
             .or_else(|| dirs::home_dir().map(|h| h.join(".cache")))
@@ -43,134 +197,299 @@ impl CrcCache {
         let cache_dir = cache_dir.join("retroarch-indexer");
         std::fs::create_dir_all(&cache_dir)?;
         
-        let cache_file = cache_dir.join("crc32_cache.json");
-        
-        if cache_file.exists() {
-            Self::load_from_file(&cache_file)
-        } else {
-            let mut cache = Self::default();
-            cache.cache_file = Some(cache_file);
-            Ok(cache)
+        let cache_file = cache_dir.join("crc32_cache.bin");
+        let legacy_file = cache_dir.join("crc32_cache.json");
+
+        let mut cache = Self::load_from_file(&cache_file)?;
+        if legacy_file.exists() && cache.is_writable() {
+            cache.import_legacy_json(&legacy_file)?;
+            cache.auto_save()?;
+            fs::rename(&legacy_file, legacy_file.with_extension("json.migrated"))
+                .with_context(|| format!("Failed to rename legacy cache: {}", legacy_file.display()))?;
+            log::info!("Migrated legacy JSON cache {} to {}", legacy_file.display(), cache_file.display());
         }
+
+        Ok(cache)
     }
 
-    /// Load cache from file
+    /// Load cache from file. Files in the old JSON format are imported and
+    /// rewritten in the log format on the next save.
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         let path = path.as_ref();
-        
+
+        let lock = cache_log::try_lock(path)?;
+        if lock.is_none() {
+            log::warn!("{}; opening it read-only", CacheError::Locked(path.to_path_buf()));
+        }
+
+        let mut cache = Self {
+            cache_file: Some(path.to_path_buf()),
+            lock,
+            ..Self::default()
+        };
+
         if !path.exists() {
-            return Ok(Self::new());
+            // Start empty but save to this path later
+            return Ok(cache);
         }
 
-        let content = fs::read_to_string(path)
-            .with_context(|| format!("Failed to read cache file: {}", path.display()))?;
-        
-        let mut cache: Self = serde_json::from_str(&content)
-            .with_context(|| "Failed to parse cache file")?;
-        
-        cache.cache_file = Some(path.to_path_buf());
+        if cache_log::is_legacy_json(path) {
+            cache.import_legacy_json(path)?;
+            return Ok(cache);
+        }
+
+        let replay = cache_log::replay(path)?;
+        cache.entries = replay.entries;
+        cache.log_records = replay.records;
+        cache.log_len = replay.valid_len;
+        cache.needs_compaction = replay.version != cache_log::SCHEMA_VERSION;
+        cache.rebuild_fingerprint_index();
         
         Ok(cache)
     }
 
-    /// Save cache to file
+    /// Merge entries from a JSON cache; entries already present take precedence
+    fn import_legacy_json(&mut self, path: &Path) -> Result<()> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read cache file: {}", path.display()))?;
+        let legacy: LegacyJsonCache = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse legacy cache file: {}", path.display()))?;
+
+        log::info!("Importing {} entries from legacy JSON cache {}", legacy.entries.len(), path.display());
+        for (key, entry) in legacy.entries {
+            self.entries.entry(key).or_insert(entry);
+        }
+        self.needs_compaction = true;
+        self.rebuild_fingerprint_index();
+        Ok(())
+    }
+
+    fn rebuild_fingerprint_index(&mut self) {
+        self.by_fingerprint = self.entries.iter()
+            .filter_map(|(key, entry)| Some((entry.fingerprint?, key.clone())))
+            .collect();
+    }
+
+    /// Shareable handle for the scanner's worker threads
+    pub fn into_shared(self) -> SharedCrcCache {
+        Arc::new(Mutex::new(self))
+    }
+
+    /// Whether changes can be saved, i.e. this instance owns the cache file
+    pub fn is_writable(&self) -> bool {
+        self.cache_file.is_some() && self.lock.is_some()
+    }
+
+    /// Write a full snapshot to `path` and keep using it as the cache file.
+    /// The file is replaced atomically, so a crash while saving leaves the
+    /// previous version intact.
     pub fn save_to_file<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
         let path = path.as_ref();
-        
-        // Ensure parent directory exists
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)
-                .with_context(|| format!("Failed to create cache directory: {}", parent.display()))?;
+
+        if self.cache_file.as_deref() != Some(path) || self.lock.is_none() {
+            let lock = cache_log::try_lock(path)?
+                .ok_or_else(|| CacheError::Locked(path.to_path_buf()))?;
+            self.lock = Some(lock);
+            self.cache_file = Some(path.to_path_buf());
         }
 
-        let content = serde_json::to_string_pretty(self)
-            .with_context(|| "Failed to serialize cache")?;
-        
-        fs::write(path, content)
-            .with_context(|| format!("Failed to write cache file: {}", path.display()))?;
-        
-        self.cache_file = Some(path.to_path_buf());
-        self.dirty = false;
-        
+        self.compact(path)
+    }
+
+    fn compact(&mut self, path: &Path) -> Result<()> {
+        self.log_len = cache_log::write_snapshot(path, &self.entries)?;
+        self.log_records = self.entries.len();
+        self.pending.clear();
+        self.needs_compaction = false;
         Ok(())
     }
 
-    /// Auto-save if cache file is set and cache is dirty
+    /// Write pending changes if this instance owns a cache file. Changes are
+    /// appended to the log unless it is due for compaction.
     pub fn auto_save(&mut self) -> Result<()> {
-        if self.dirty && self.cache_file.is_some() {
-            let cache_file = self.cache_file.clone().unwrap();
-            self.save_to_file(cache_file)?;
+        if !self.is_writable() || (self.pending.is_empty() && !self.needs_compaction) {
+            return Ok(());
+        }
+        let cache_file = self.cache_file.clone().unwrap();
+
+        let log_records = self.log_records + self.pending.len();
+        let oversized = log_records > COMPACTION_MIN_RECORDS && log_records > 2 * self.entries.len();
+
+        if self.log_len == 0 || self.needs_compaction || oversized {
+            self.compact(&cache_file)
+        } else {
+            self.log_len = cache_log::append(&cache_file, self.log_len, &self.pending)?;
+            self.log_records = log_records;
+            self.pending.clear();
+            Ok(())
+        }
+    }
+
+    /// Save if enough changes piled up since the last save
+    pub fn checkpoint(&mut self) -> Result<()> {
+        if self.pending.len() >= CHECKPOINT_INTERVAL {
+            self.auto_save()?;
         }
         Ok(())
     }
 
     /// Get CRC32 from cache if entry is valid
     pub fn get_crc32(&mut self, file_path: &Path) -> Result<Option<u32>> {
-        let metadata = fs::metadata(file_path)
-            .with_context(|| format!("Failed to get metadata for: {}", file_path.display()))?;
-        
-        let file_size = metadata.len();
-        let modified_time = metadata.modified()
-            .with_context(|| "Failed to get file modification time")?
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
-
-        if let Some(entry) = self.entries.get_mut(file_path) {
-            // Check if entry is still valid
-            if entry.file_size == file_size && entry.modified_time == modified_time {
-                // Update last accessed time
-                entry.last_accessed = SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_secs();
-                self.dirty = true;
-                
-                return Ok(Some(entry.crc32));
-            } else {
-                // Entry is stale, remove it
-                self.entries.remove(file_path);
-                self.dirty = true;
-            }
+        Ok(self.get_crc_variants(file_path)?.map(|variants| variants.raw))
+    }
+
+    /// Get raw and headerless CRC32 from cache if entry is valid
+    pub fn get_crc_variants(&mut self, file_path: &Path) -> Result<Option<CrcVariants>> {
+        let stamp = FileStamp::read(file_path)?;
+        Ok(self.lookup(file_path, stamp))
+    }
+
+    /// Cached hashes for `key` if they were stored for a file with this stamp.
+    /// Stale entries are dropped.
+    pub fn lookup(&mut self, key: &Path, stamp: FileStamp) -> Option<CrcVariants> {
+        let Some(entry) = self.entries.get_mut(key) else {
+            self.misses += 1;
+            return None;
+        };
+
+        if entry.file_size != stamp.size || entry.modified_time != stamp.modified {
+            self.entries.remove(key);
+            self.pending.push(LogRecord::Remove(key.to_path_buf()));
+            self.misses += 1;
+            return None;
+        }
+
+        // Only log access times coarse enough for `clean_old_entries`
+        let now = now_secs();
+        if now.saturating_sub(entry.last_accessed) >= ACCESS_REFRESH_SECS {
+            entry.last_accessed = now;
+            self.pending.push(LogRecord::Put(key.to_path_buf(), Box::new(entry.clone())));
+        }
+        self.hits += 1;
+
+        Some(entry.variants())
+    }
+
+    /// Hashes of an entry stored under another key with the same content
+    /// fingerprint, size and modification time whose file is gone, i.e. this
+    /// file after a rename or move, which keeps all three.
+    pub fn moved_entry(&self, key: &Path, stamp: FileStamp) -> Option<CrcVariants> {
+        self.moved_from(key, stamp).map(|(_, entry)| entry.variants())
+    }
+
+    fn moved_from(&self, key: &Path, stamp: FileStamp) -> Option<(PathBuf, &CacheEntry)> {
+        let fingerprint = stamp.fingerprint?;
+        let old_key = self.by_fingerprint.get(&fingerprint)?;
+        let old_entry = self.entries.get(old_key)
+            .filter(|entry| {
+                old_key != key
+                    && entry.fingerprint == Some(fingerprint)
+                    && entry.file_size == stamp.size
+                    && entry.modified_time == stamp.modified
+            })?;
+
+        // A file that still exists is a different file, not the moved one
+        if source_exists(old_key) {
+            return None;
         }
+        Some((old_key.clone(), old_entry))
+    }
+
+    /// Re-key the entry found by `moved_entry` to `key` and drop the old key
+    pub fn relink(&mut self, key: &Path, stamp: FileStamp) -> Option<CrcVariants> {
+        let (old_key, old_entry) = self.moved_from(key, stamp)?;
+        let entry = CacheEntry {
+            modified_time: stamp.modified,
+            last_accessed: now_secs(),
+            relinked: true,
+            ..old_entry.clone()
+        };
+        let variants = entry.variants();
+
+        self.entries.remove(&old_key);
+        self.pending.push(LogRecord::Remove(old_key.clone()));
+        log::debug!("Relinked cache entry {} -> {}", old_key.display(), key.display());
+
+        self.pending.push(LogRecord::Put(key.to_path_buf(), Box::new(entry.clone())));
+        self.entries.insert(key.to_path_buf(), entry);
+        if let Some(fingerprint) = stamp.fingerprint {
+            self.by_fingerprint.insert(fingerprint, key.to_path_buf());
+        }
+
+        // The lookup that preceded this counted a miss
+        self.misses = self.misses.saturating_sub(1);
+        self.hits += 1;
+        self.relinked += 1;
 
-        Ok(None)
+        Some(variants)
     }
 
     /// Store CRC32 in cache
     pub fn set_crc32(&mut self, file_path: &Path, crc32: u32) -> Result<()> {
-        let metadata = fs::metadata(file_path)
-            .with_context(|| format!("Failed to get metadata for: {}", file_path.display()))?;
-        
-        let file_size = metadata.len();
-        let modified_time = metadata.modified()
-            .with_context(|| "Failed to get file modification time")?
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
-        
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
+        self.set_crc_variants(file_path, &CrcVariants::raw_only(crc32))
+    }
+
+    /// Every cached entry with its key
+    pub fn iter(&self) -> impl Iterator<Item = (&Path, &CacheEntry)> {
+        self.entries.iter().map(|(key, entry)| (key.as_path(), entry))
+    }
+
+    /// Variant that matched a DAT entry for this file, if recorded
+    pub fn matched_variant(&self, file_path: &Path) -> Option<CrcVariant> {
+        self.entries.get(file_path).and_then(|entry| entry.matched_variant)
+    }
+
+    /// Record which CRC variant matched a DAT entry
+    pub fn record_matched_variant(&mut self, file_path: &Path, variant: CrcVariant) {
+        if let Some(entry) = self.entries.get_mut(file_path)
+            && entry.matched_variant != Some(variant)
+        {
+            entry.matched_variant = Some(variant);
+            self.pending.push(LogRecord::Put(file_path.to_path_buf(), Box::new(entry.clone())));
+        }
+    }
+
+    /// Store raw and headerless CRC32 in cache
+    pub fn set_crc_variants(&mut self, file_path: &Path, variants: &CrcVariants) -> Result<()> {
+        let stamp = FileStamp::read(file_path)?;
+        self.insert(file_path, stamp, variants);
+        Ok(())
+    }
+
+    /// Store hashes under `key` for a file with this stamp. Archive entries use
+    /// their `archive.zip#entry` path as key and the archive's stamp.
+    pub fn insert(&mut self, key: &Path, stamp: FileStamp, variants: &CrcVariants) {
+        // Keep the recorded match while the file content is unchanged
+        let matched_variant = self.entries.get(key)
+            .filter(|entry| entry.file_size == stamp.size && entry.modified_time == stamp.modified)
+            .and_then(|entry| entry.matched_variant);
 
         let entry = CacheEntry {
-            crc32,
-            file_size,
-            modified_time,
-            last_accessed: now,
+            crc32: variants.raw,
+            headerless_crc32: variants.headerless,
+            matched_variant,
+            raw_digests: variants.raw_digests.clone(),
+            headerless_digests: variants.headerless_digests.clone(),
+            fingerprint: stamp.fingerprint,
+            relinked: false,
+            file_size: stamp.size,
+            modified_time: stamp.modified,
+            last_accessed: now_secs(),
         };
 
-        self.entries.insert(file_path.to_path_buf(), entry);
-        self.dirty = true;
-        
-        Ok(())
+        if let Some(fingerprint) = stamp.fingerprint {
+            self.by_fingerprint.insert(fingerprint, key.to_path_buf());
+        }
+
+        self.pending.push(LogRecord::Put(key.to_path_buf(), Box::new(entry.clone())));
+        self.entries.insert(key.to_path_buf(), entry);
     }
 
     /// Clear all cache entries
     pub fn clear(&mut self) -> Result<()> {
         self.entries.clear();
-        self.dirty = true;
+        self.by_fingerprint.clear();
+        self.needs_compaction = true;
         self.auto_save()?;
         Ok(())
     }
@@ -204,10 +523,12 @@ impl CrcCache {
 
         Ok(DetailedCacheStats {
             total_entries: self.entries.len(),
+            relinked_entries: self.entries.values().filter(|entry| entry.relinked).count(),
+            relinked: self.relinked,
             cache_size,
-            cache_hits: 0, // Would need to track this in real implementation
-            cache_misses: 0, // Would need to track this in real implementation
-            total_requests: 0, // Would need to track this in real implementation
+            cache_hits: self.hits,
+            cache_misses: self.misses,
+            total_requests: self.hits + self.misses,
             last_updated,
         })
     }
@@ -215,10 +536,7 @@ impl CrcCache {
     /// Clean old entries and return count of removed entries
     pub fn clean_old_entries(&mut self, max_age_days: u64) -> Result<usize> {
         let max_age_secs = max_age_days * 24 * 60 * 60;
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
+        let now = now_secs();
         
         let initial_count = self.entries.len();
         
@@ -229,7 +547,8 @@ impl CrcCache {
         let removed_count = initial_count - self.entries.len();
         if removed_count > 0 {
             log::info!("Cleaned {} old cache entries", removed_count);
-            self.dirty = true;
+            self.rebuild_fingerprint_index();
+            self.needs_compaction = true;
             self.auto_save()?;
         }
         
@@ -246,6 +565,10 @@ impl CrcCache {
 #[derive(Debug, Default)]
 pub struct DetailedCacheStats {
     pub total_entries: usize,
+    /// Entries whose hashes were carried over from a renamed or moved file
+    pub relinked_entries: usize,
+    /// Entries re-linked by this instance
+    pub relinked: u64,
     pub cache_size: u64,
     pub cache_hits: u64,
     pub cache_misses: u64,
@@ -262,7 +585,7 @@ mod tests {
     #[test]
     fn test_cache_operations() {
         let temp_dir = tempdir().unwrap();
-        let cache_file = temp_dir.path().join("test_cache.json");
+        let cache_file = temp_dir.path().join("test_cache.bin");
         
         // Create a test file
         let test_file = temp_dir.path().join("test.rom");
@@ -286,4 +609,147 @@ mod tests {
         // Should still have the CRC32
         assert_eq!(reloaded_cache.get_crc32(&test_file).unwrap(), Some(0x12345678));
     }
+
+    #[test]
+    fn test_headerless_variant_recording() {
+        let temp_dir = tempdir().unwrap();
+        let cache_file = temp_dir.path().join("test_cache.bin");
+        let test_file = temp_dir.path().join("game.nes");
+        std::fs::write(&test_file, b"NES\x1a test data").unwrap();
+
+        let mut cache = CrcCache::new();
+        let variants = CrcVariants {
+            raw: 0x11111111,
+            headerless: Some(0x22222222),
+            ..Default::default()
+        };
+        cache.set_crc_variants(&test_file, &variants).unwrap();
+        cache.record_matched_variant(&test_file, CrcVariant::Headerless);
+        cache.save_to_file(&cache_file).unwrap();
+
+        let mut reloaded_cache = CrcCache::load_from_file(&cache_file).unwrap();
+        let cached = reloaded_cache.get_crc_variants(&test_file).unwrap().unwrap();
+        assert_eq!(cached.headerless, Some(0x22222222));
+        assert_eq!(reloaded_cache.matched_variant(&test_file), Some(CrcVariant::Headerless));
+    }
+
+    #[test]
+    fn test_log_recovers_from_torn_write() {
+        let temp_dir = tempdir().unwrap();
+        let cache_file = temp_dir.path().join("test_cache.bin");
+        let stamp = FileStamp { size: 4, modified: 1, fingerprint: None };
+
+        {
+            let mut cache = CrcCache::load_from_file(&cache_file).unwrap();
+            cache.insert(Path::new("a.nes"), stamp, &CrcVariants::raw_only(1));
+            cache.auto_save().unwrap();
+            cache.insert(Path::new("b.nes"), stamp, &CrcVariants::raw_only(2));
+            cache.auto_save().unwrap();
+        }
+
+        // Simulate a crash halfway through appending a record
+        let mut file = std::fs::OpenOptions::new().append(true).open(&cache_file).unwrap();
+        std::io::Write::write_all(&mut file, &[40, 0, 0, 0, 1, 2, 3]).unwrap();
+        drop(file);
+
+        {
+            let mut cache = CrcCache::load_from_file(&cache_file).unwrap();
+            assert_eq!(cache.lookup(Path::new("b.nes"), stamp).map(|v| v.raw), Some(2));
+            cache.insert(Path::new("c.nes"), stamp, &CrcVariants::raw_only(3));
+            cache.auto_save().unwrap();
+        }
+
+        let mut cache = CrcCache::load_from_file(&cache_file).unwrap();
+        assert_eq!(cache.get_stats().unwrap().total_entries, 3);
+        assert_eq!(cache.lookup(Path::new("a.nes"), stamp).map(|v| v.raw), Some(1));
+        assert_eq!(cache.lookup(Path::new("c.nes"), stamp).map(|v| v.raw), Some(3));
+    }
+
+    #[test]
+    fn test_legacy_json_cache_is_imported() {
+        let temp_dir = tempdir().unwrap();
+        let cache_file = temp_dir.path().join("crc32_cache.json");
+        let test_file = temp_dir.path().join("game.sfc");
+        std::fs::write(&test_file, b"snes data").unwrap();
+        let stamp = FileStamp::read(&test_file).unwrap();
+
+        let legacy = serde_json::json!({
+            "entries": {
+                test_file.to_string_lossy(): {
+                    "crc32": 0xABCD,
+                    "file_size": stamp.size,
+                    "modified_time": stamp.modified,
+                    "last_accessed": 0,
+                }
+            }
+        });
+        std::fs::write(&cache_file, serde_json::to_string_pretty(&legacy).unwrap()).unwrap();
+
+        let mut cache = CrcCache::load_from_file(&cache_file).unwrap();
+        assert_eq!(cache.get_crc32(&test_file).unwrap(), Some(0xABCD));
+        cache.auto_save().unwrap();
+        drop(cache);
+
+        assert!(!cache_log::is_legacy_json(&cache_file));
+        let mut reloaded = CrcCache::load_from_file(&cache_file).unwrap();
+        assert_eq!(reloaded.get_crc32(&test_file).unwrap(), Some(0xABCD));
+    }
+
+    #[test]
+    fn test_second_instance_is_read_only() {
+        let temp_dir = tempdir().unwrap();
+        let cache_file = temp_dir.path().join("test_cache.bin");
+        let stamp = FileStamp { size: 1, modified: 1, fingerprint: None };
+
+        let mut owner = CrcCache::load_from_file(&cache_file).unwrap();
+        owner.insert(Path::new("a.gb"), stamp, &CrcVariants::raw_only(1));
+        owner.auto_save().unwrap();
+
+        let mut other = CrcCache::load_from_file(&cache_file).unwrap();
+        assert!(!other.is_writable());
+        assert_eq!(other.lookup(Path::new("a.gb"), stamp).map(|v| v.raw), Some(1));
+
+        other.insert(Path::new("b.gb"), stamp, &CrcVariants::raw_only(2));
+        other.auto_save().unwrap();
+        let err = other.save_to_file(&cache_file).unwrap_err();
+        assert!(matches!(err.downcast_ref(), Some(CacheError::Locked(_))));
+
+        drop(owner);
+        let reloaded = CrcCache::load_from_file(&cache_file).unwrap();
+        assert_eq!(reloaded.get_stats().unwrap().total_entries, 1);
+    }
+
+    #[test]
+    fn test_moved_file_is_relinked_by_fingerprint() {
+        let temp_dir = tempdir().unwrap();
+        let old_path = temp_dir.path().join("old/game.gba");
+        let new_path = temp_dir.path().join("new/game.gba");
+        std::fs::create_dir_all(old_path.parent().unwrap()).unwrap();
+        std::fs::create_dir_all(new_path.parent().unwrap()).unwrap();
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&old_path, &data).unwrap();
+
+        let mut cache = CrcCache::new();
+        let stamp = FileStamp::read(&old_path).unwrap()
+            .with_fingerprint(Some(content_fingerprint(&old_path).unwrap()));
+        cache.insert(&old_path, stamp, &CrcVariants::raw_only(0xCAFEBABE));
+
+        std::fs::rename(&old_path, &new_path).unwrap();
+        let stamp = FileStamp::read(&new_path).unwrap();
+        assert!(cache.lookup(&new_path, stamp).is_none());
+        let stamp = stamp.with_fingerprint(Some(content_fingerprint(&new_path).unwrap()));
+        assert_eq!(cache.relink(&new_path, stamp).map(|v| v.raw), Some(0xCAFEBABE));
+
+        let stats = cache.get_stats().unwrap();
+        assert_eq!((stats.total_entries, stats.relinked_entries, stats.relinked), (1, 1, 1));
+        assert_eq!((stats.cache_hits, stats.cache_misses), (1, 0));
+
+        // A different file of the same size is not mistaken for the moved one
+        let mut other = data.clone();
+        other[150_000] ^= 0xFF;
+        std::fs::write(&old_path, &other).unwrap();
+        let stamp = FileStamp::read(&old_path).unwrap()
+            .with_fingerprint(Some(content_fingerprint(&old_path).unwrap()));
+        assert!(cache.relink(&old_path, stamp).is_none());
+    }
 }
@@ -0,0 +1,283 @@
+use rayon::prelude::*;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::cache::{CacheEntry, CrcCache, FileStamp};
+use crate::crc32::{calculate_crc32_variants_with, CrcVariants, Digests, HashAlgorithm, HashAlgorithms, HashOptions};
+use crate::header_skipper::SkipperSet;
+
+/// Re-hashes cached files whose size and modification time are unchanged and
+/// reports the ones whose content no longer matches, i.e. silent corruption
+pub struct CacheAuditor<'a> {
+    cache: &'a CrcCache,
+    sample: Option<usize>,
+    seed: u64,
+    skippers: SkipperSet,
+}
+
+/// Outcome of an audit, serialized as the machine-readable report
+#[derive(Debug, Default, Serialize)]
+pub struct AuditReport {
+    /// Cache entries considered (all of them, or the sample)
+    pub checked: usize,
+    /// Re-hashed with the same result
+    pub verified: usize,
+    /// Size or modification time changed, so the entry is just stale
+    pub modified: usize,
+    pub missing: usize,
+    /// Entries stored inside archives, which are not re-hashed
+    pub skipped: usize,
+    pub mismatches: Vec<AuditMismatch>,
+    pub errors: Vec<AuditError>,
+}
+
+/// File whose content changed without a metadata change
+#[derive(Debug, Serialize)]
+pub struct AuditMismatch {
+    pub path: PathBuf,
+    /// Algorithms whose hash differs from the cached one
+    pub algorithms: Vec<HashAlgorithm>,
+    pub expected_crc32: String,
+    pub actual_crc32: String,
+}
+
+/// File that could not be read back
+#[derive(Debug, Serialize)]
+pub struct AuditError {
+    pub path: PathBuf,
+    pub error: String,
+}
+
+enum Outcome {
+    Verified,
+    Modified,
+    Missing,
+    Skipped,
+    Mismatch(AuditMismatch),
+    Error(AuditError),
+}
+
+impl AuditReport {
+    /// Whether the audit found corruption or unreadable files
+    pub fn has_failures(&self) -> bool {
+        !self.mismatches.is_empty() || !self.errors.is_empty()
+    }
+}
+
+impl<'a> CacheAuditor<'a> {
+    pub fn new(cache: &'a CrcCache) -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos() as u64)
+            .unwrap_or_default();
+
+        Self {
+            cache,
+            sample: None,
+            seed,
+            skippers: SkipperSet::builtin(),
+        }
+    }
+
+    /// Check only `size` randomly chosen entries
+    pub fn with_sample(mut self, size: usize) -> Self {
+        self.sample = Some(size);
+        self
+    }
+
+    /// Seed for the sample selection, to repeat a previous audit
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn with_header_skippers(mut self, skippers: SkipperSet) -> Self {
+        self.skippers = skippers;
+        self
+    }
+
+    pub fn run(&self) -> AuditReport {
+        let mut selected: Vec<(&Path, &CacheEntry)> = self.cache.iter().collect();
+        if let Some(size) = self.sample {
+            // Ranking by a seeded hash of the path gives a uniform random sample
+            let seed = self.seed.to_le_bytes();
+            selected.sort_by_cached_key(|(key, _)| {
+                let mut hasher = crc32fast::Hasher::new();
+                hasher.update(&seed);
+                hasher.update(key.to_string_lossy().as_bytes());
+                hasher.finalize()
+            });
+            selected.truncate(size);
+        }
+
+        let outcomes: Vec<Outcome> = selected
+            .par_iter()
+            .map(|(key, entry)| self.audit_entry(key, entry))
+            .collect();
+
+        let mut report = AuditReport {
+            checked: outcomes.len(),
+            ..Default::default()
+        };
+        for outcome in outcomes {
+            match outcome {
+                Outcome::Verified => report.verified += 1,
+                Outcome::Modified => report.modified += 1,
+                Outcome::Missing => report.missing += 1,
+                Outcome::Skipped => report.skipped += 1,
+                Outcome::Mismatch(mismatch) => report.mismatches.push(mismatch),
+                Outcome::Error(error) => report.errors.push(error),
+            }
+        }
+        report.mismatches.sort_by(|a, b| a.path.cmp(&b.path));
+        report.errors.sort_by(|a, b| a.path.cmp(&b.path));
+
+        report
+    }
+
+    fn audit_entry(&self, key: &Path, entry: &CacheEntry) -> Outcome {
+        if !key.exists() {
+            let in_archive = key.to_string_lossy()
+                .rsplit_once('#')
+                .is_some_and(|(archive, _)| Path::new(archive).exists());
+            return if in_archive { Outcome::Skipped } else { Outcome::Missing };
+        }
+
+        let stamp = match FileStamp::read(key) {
+            Ok(stamp) => stamp,
+            Err(e) => return Outcome::Error(AuditError { path: key.to_path_buf(), error: format!("{:#}", e) }),
+        };
+        if stamp.size != entry.file_size || stamp.modified != entry.modified_time {
+            return Outcome::Modified;
+        }
+
+        let options = HashOptions::new(cached_algorithms(entry));
+        match calculate_crc32_variants_with(key, &self.skippers, &options, None) {
+            Ok(actual) => {
+                let algorithms = mismatched_algorithms(entry, &actual);
+                if algorithms.is_empty() {
+                    Outcome::Verified
+                } else {
+                    log::warn!("Conteúdo de {} mudou sem alteração de tamanho ou data", key.display());
+                    Outcome::Mismatch(AuditMismatch {
+                        path: key.to_path_buf(),
+                        algorithms,
+                        expected_crc32: format!("{:08X}", entry.crc32),
+                        actual_crc32: format!("{:08X}", actual.raw),
+                    })
+                }
+            }
+            Err(e) => Outcome::Error(AuditError { path: key.to_path_buf(), error: format!("{:#}", e) }),
+        }
+    }
+}
+
+/// CRC32 plus the digests stored for the entry that this build can compute
+fn cached_algorithms(entry: &CacheEntry) -> HashAlgorithms {
+    let mut algorithms = HashAlgorithms::crc32_only();
+    for digests in [&entry.raw_digests, &entry.headerless_digests].into_iter().flatten() {
+        for (algorithm, value) in digest_values(digests) {
+            if value.is_some() && algorithm.is_available() {
+                algorithms = algorithms.with(algorithm);
+            }
+        }
+    }
+    algorithms
+}
+
+fn digest_values(digests: &Digests) -> [(HashAlgorithm, Option<&str>); 3] {
+    [
+        (HashAlgorithm::Md5, digests.md5.as_deref()),
+        (HashAlgorithm::Sha1, digests.sha1.as_deref()),
+        (HashAlgorithm::Sha256, digests.sha256.as_deref()),
+    ]
+}
+
+/// Algorithms whose cached and recomputed hashes differ
+fn mismatched_algorithms(entry: &CacheEntry, actual: &CrcVariants) -> Vec<HashAlgorithm> {
+    let mut mismatched = Vec::new();
+    if entry.crc32 != actual.raw
+        || entry.headerless_crc32.is_some_and(|crc| actual.headerless != Some(crc))
+    {
+        mismatched.push(HashAlgorithm::Crc32);
+    }
+
+    let pairs = [
+        (&entry.raw_digests, &actual.raw_digests),
+        (&entry.headerless_digests, &actual.headerless_digests),
+    ];
+    for (expected, actual) in pairs {
+        let (Some(expected), Some(actual)) = (expected, actual) else {
+            continue;
+        };
+        for ((algorithm, expected), (_, actual)) in digest_values(expected).into_iter().zip(digest_values(actual)) {
+            if let (Some(expected), Some(actual)) = (expected, actual)
+                && !expected.eq_ignore_ascii_case(actual)
+                && !mismatched.contains(&algorithm)
+            {
+                mismatched.push(algorithm);
+            }
+        }
+    }
+
+    mismatched
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::FileStamp;
+    use tempfile::tempdir;
+
+    fn cache_with(files: &[&Path]) -> CrcCache {
+        let mut cache = CrcCache::new();
+        for file in files {
+            let stamp = FileStamp::read(file).unwrap();
+            let variants = calculate_crc32_variants_with(file, &SkipperSet::builtin(), &HashOptions::default(), None).unwrap();
+            cache.insert(file, stamp, &variants);
+        }
+        cache
+    }
+
+    #[test]
+    fn test_detects_silent_corruption() {
+        let dir = tempdir().unwrap();
+        let good = dir.path().join("good.gba");
+        let rotten = dir.path().join("rotten.gba");
+        let edited = dir.path().join("edited.gba");
+        let gone = dir.path().join("gone.gba");
+        for path in [&good, &rotten, &edited, &gone] {
+            std::fs::write(path, b"original rom contents").unwrap();
+        }
+        let cache = cache_with(&[&good, &rotten, &edited, &gone]);
+
+        // Flip a byte but keep size and mtime, as a failing disk would
+        let modified = std::fs::metadata(&rotten).unwrap().modified().unwrap();
+        std::fs::write(&rotten, b"original rom c0ntents").unwrap();
+        std::fs::File::options().write(true).open(&rotten).unwrap().set_modified(modified).unwrap();
+        std::fs::write(&edited, b"a longer, legitimately edited rom").unwrap();
+        std::fs::remove_file(&gone).unwrap();
+
+        let report = CacheAuditor::new(&cache).run();
+        assert_eq!((report.checked, report.verified, report.modified, report.missing), (4, 1, 1, 1));
+        assert!(report.has_failures());
+        assert_eq!(report.mismatches.len(), 1);
+        assert_eq!(report.mismatches[0].path, rotten);
+        assert!(report.mismatches[0].algorithms.contains(&HashAlgorithm::Crc32));
+    }
+
+    #[test]
+    fn test_sample_limits_checked_entries() {
+        let dir = tempdir().unwrap();
+        let files: Vec<PathBuf> = (0..10).map(|i| dir.path().join(format!("{}.gb", i))).collect();
+        for file in &files {
+            std::fs::write(file, file.to_string_lossy().as_bytes()).unwrap();
+        }
+        let cache = cache_with(&files.iter().map(PathBuf::as_path).collect::<Vec<_>>());
+
+        let report = CacheAuditor::new(&cache).with_sample(3).with_seed(42).run();
+        assert_eq!((report.checked, report.verified), (3, 3));
+        assert!(!report.has_failures());
+    }
+}
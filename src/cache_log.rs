@@ -0,0 +1,475 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::cache::CacheEntry;
+use crate::crc32::{CrcVariant, Digests};
+use crate::error::CacheError;
+
+/// First bytes of every cache log
+pub const MAGIC: &[u8; 8] = b"RAFICRC\0";
+/// Bumped whenever the record encoding changes. Version 1 lacks the content
+/// fingerprint and re-link flag of cache entries.
+pub const SCHEMA_VERSION: u32 = 2;
+const HEADER_LEN: usize = MAGIC.len() + 4;
+/// Larger length prefixes can only come from a corrupt or torn record
+const MAX_RECORD_LEN: usize = 64 * 1024;
+
+const RECORD_PUT: u8 = 1;
+const RECORD_REMOVE: u8 = 2;
+
+/// One change appended to the log. Replaying the records in order rebuilds the cache.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogRecord {
+    Put(PathBuf, Box<CacheEntry>),
+    Remove(PathBuf),
+}
+
+/// State rebuilt from a log file
+#[derive(Debug, Default)]
+pub struct Replay {
+    pub entries: HashMap<PathBuf, CacheEntry>,
+    /// Records read, including the ones later superseded
+    pub records: usize,
+    /// Length of the valid prefix; anything after it is a torn write
+    pub valid_len: u64,
+    /// Schema version of the file; older logs must be rewritten before appending
+    pub version: u32,
+}
+
+/// Take the exclusive lock guarding the log at `path`. Returns `None` when
+/// another process holds it; the lock is released when the file is dropped.
+pub fn try_lock(path: &Path) -> Result<Option<File>> {
+    let lock_path = path.with_extension("lock");
+    if let Some(parent) = lock_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create cache directory: {}", parent.display()))?;
+    }
+
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .with_context(|| format!("Failed to open cache lock: {}", lock_path.display()))?;
+
+    match file.try_lock() {
+        Ok(()) => Ok(Some(file)),
+        Err(TryLockError::WouldBlock) => Ok(None),
+        Err(TryLockError::Error(err)) => Err(err)
+            .with_context(|| format!("Failed to lock cache: {}", lock_path.display())),
+    }
+}
+
+/// Whether the file is the JSON cache written by earlier versions
+pub fn is_legacy_json(path: &Path) -> bool {
+    let mut head = [0u8; 64];
+    let Ok(read) = File::open(path).and_then(|mut file| file.read(&mut head)) else {
+        return false;
+    };
+    head[..read].iter().find(|byte| !byte.is_ascii_whitespace()) == Some(&b'{')
+}
+
+/// Read every intact record of the log at `path`. Reading stops at the first
+/// truncated or corrupt record, so a crash mid-append loses only that record.
+pub fn replay(path: &Path) -> Result<Replay> {
+    let data = fs::read(path)
+        .with_context(|| format!("Failed to read cache file: {}", path.display()))?;
+
+    if data.len() < HEADER_LEN || &data[..MAGIC.len()] != MAGIC {
+        return Err(CacheError::InvalidHeader(path.to_path_buf()).into());
+    }
+    let version = u32::from_le_bytes(data[MAGIC.len()..HEADER_LEN].try_into().unwrap());
+    if version == 0 || version > SCHEMA_VERSION {
+        return Err(CacheError::UnsupportedVersion { path: path.to_path_buf(), version }.into());
+    }
+
+    let mut replay = Replay {
+        valid_len: HEADER_LEN as u64,
+        version,
+        ..Default::default()
+    };
+    let mut position = HEADER_LEN;
+
+    while let Some((record, next)) = read_frame(&data, position, version) {
+        match record {
+            LogRecord::Put(key, entry) => {
+                replay.entries.insert(key, *entry);
+            }
+            LogRecord::Remove(key) => {
+                replay.entries.remove(&key);
+            }
+        }
+        replay.records += 1;
+        replay.valid_len = next as u64;
+        position = next;
+    }
+
+    if position < data.len() {
+        log::warn!(
+            "Cache file {} has {} trailing bytes from an interrupted write; they will be discarded",
+            path.display(),
+            data.len() - position
+        );
+    }
+
+    Ok(replay)
+}
+
+/// Append `records` to an existing log, cutting off any torn tail first
+pub fn append(path: &Path, valid_len: u64, records: &[LogRecord]) -> Result<u64> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .open(path)
+        .with_context(|| format!("Failed to open cache file: {}", path.display()))?;
+
+    let current_len = file.metadata()?.len();
+    if current_len != valid_len {
+        file.set_len(valid_len)
+            .with_context(|| format!("Failed to truncate cache file: {}", path.display()))?;
+    }
+
+    let mut buffer = Vec::new();
+    for record in records {
+        encode_frame(record, &mut buffer);
+    }
+
+    file.seek(SeekFrom::Start(valid_len))?;
+    file.write_all(&buffer)
+        .with_context(|| format!("Failed to write cache file: {}", path.display()))?;
+    file.sync_data()
+        .with_context(|| format!("Failed to sync cache file: {}", path.display()))?;
+
+    Ok(valid_len + buffer.len() as u64)
+}
+
+/// Replace the log with one `Put` per live entry. The snapshot is written to a
+/// temporary file, synced and renamed over the log, so a crash leaves either
+/// the old or the new file, never a mix.
+pub fn write_snapshot(path: &Path, entries: &HashMap<PathBuf, CacheEntry>) -> Result<u64> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create cache directory: {}", parent.display()))?;
+    }
+
+    let mut buffer = Vec::with_capacity(HEADER_LEN + entries.len() * 64);
+    buffer.extend_from_slice(MAGIC);
+    buffer.extend_from_slice(&SCHEMA_VERSION.to_le_bytes());
+    for (key, entry) in entries {
+        encode_frame(&LogRecord::Put(key.clone(), Box::new(entry.clone())), &mut buffer);
+    }
+
+    let temp_path = path.with_extension("tmp");
+    {
+        let mut temp = File::create(&temp_path)
+            .with_context(|| format!("Failed to write cache file: {}", temp_path.display()))?;
+        temp.write_all(&buffer)
+            .with_context(|| format!("Failed to write cache file: {}", temp_path.display()))?;
+        temp.sync_all()
+            .with_context(|| format!("Failed to sync cache file: {}", temp_path.display()))?;
+    }
+    fs::rename(&temp_path, path)
+        .with_context(|| format!("Failed to replace cache file: {}", path.display()))?;
+
+    Ok(buffer.len() as u64)
+}
+
+/// Frame layout: payload length (u32 LE), CRC32 of the payload (u32 LE), payload
+fn encode_frame(record: &LogRecord, out: &mut Vec<u8>) {
+    let mut payload = Encoder::default();
+    match record {
+        LogRecord::Put(key, entry) => {
+            payload.u8(RECORD_PUT);
+            payload.path(key);
+            payload.entry(entry);
+        }
+        LogRecord::Remove(key) => {
+            payload.u8(RECORD_REMOVE);
+            payload.path(key);
+        }
+    }
+
+    let payload = payload.0;
+    out.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    out.extend_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+    out.extend_from_slice(&payload);
+}
+
+fn read_frame(data: &[u8], position: usize, version: u32) -> Option<(LogRecord, usize)> {
+    let header = data.get(position..position + 8)?;
+    let len = u32::from_le_bytes(header[..4].try_into().ok()?) as usize;
+    let crc = u32::from_le_bytes(header[4..].try_into().ok()?);
+    if len > MAX_RECORD_LEN {
+        return None;
+    }
+
+    let payload = data.get(position + 8..position + 8 + len)?;
+    if crc32fast::hash(payload) != crc {
+        return None;
+    }
+
+    let mut decoder = Decoder { data: payload, position: 0, version };
+    let record = match decoder.u8()? {
+        RECORD_PUT => LogRecord::Put(decoder.path()?, Box::new(decoder.entry()?)),
+        RECORD_REMOVE => LogRecord::Remove(decoder.path()?),
+        _ => return None,
+    };
+
+    Some((record, position + 8 + len))
+}
+
+#[derive(Default)]
+struct Encoder(Vec<u8>);
+
+impl Encoder {
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn bytes(&mut self, value: &[u8]) {
+        self.u32(value.len() as u32);
+        self.0.extend_from_slice(value);
+    }
+
+    fn path(&mut self, path: &Path) {
+        self.bytes(path_to_bytes(path).as_ref());
+    }
+
+    fn opt_u32(&mut self, value: Option<u32>) {
+        match value {
+            Some(value) => {
+                self.u8(1);
+                self.u32(value);
+            }
+            None => self.u8(0),
+        }
+    }
+
+    fn opt_str(&mut self, value: Option<&str>) {
+        match value {
+            Some(value) => {
+                self.u8(1);
+                self.bytes(value.as_bytes());
+            }
+            None => self.u8(0),
+        }
+    }
+
+    fn digests(&mut self, digests: Option<&Digests>) {
+        let Some(digests) = digests else {
+            return self.u8(0);
+        };
+        self.u8(1);
+        self.u64(digests.size);
+        self.opt_str(digests.md5.as_deref());
+        self.opt_str(digests.sha1.as_deref());
+        self.opt_str(digests.sha256.as_deref());
+    }
+
+    fn entry(&mut self, entry: &CacheEntry) {
+        self.u32(entry.crc32);
+        self.opt_u32(entry.headerless_crc32);
+        self.u8(match entry.matched_variant {
+            None => 0,
+            Some(CrcVariant::Raw) => 1,
+            Some(CrcVariant::Headerless) => 2,
+        });
+        self.u64(entry.file_size);
+        self.u64(entry.modified_time);
+        self.u64(entry.last_accessed);
+        self.digests(entry.raw_digests.as_ref());
+        self.digests(entry.headerless_digests.as_ref());
+        match entry.fingerprint {
+            Some(fingerprint) => {
+                self.u8(1);
+                self.u64(fingerprint);
+            }
+            None => self.u8(0),
+        }
+        self.u8(entry.relinked as u8);
+    }
+}
+
+struct Decoder<'a> {
+    data: &'a [u8],
+    position: usize,
+    version: u32,
+}
+
+impl Decoder<'_> {
+    fn take(&mut self, len: usize) -> Option<&[u8]> {
+        let slice = self.data.get(self.position..self.position + len)?;
+        self.position += len;
+        Some(slice)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|bytes| bytes[0])
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4).map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.take(8).map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn bytes(&mut self) -> Option<&[u8]> {
+        let len = self.u32()? as usize;
+        self.take(len)
+    }
+
+    fn path(&mut self) -> Option<PathBuf> {
+        self.bytes().map(path_from_bytes)
+    }
+
+    fn opt_u32(&mut self) -> Option<Option<u32>> {
+        match self.u8()? {
+            0 => Some(None),
+            _ => self.u32().map(Some),
+        }
+    }
+
+    fn opt_u64(&mut self) -> Option<Option<u64>> {
+        match self.u8()? {
+            0 => Some(None),
+            _ => self.u64().map(Some),
+        }
+    }
+
+    fn opt_str(&mut self) -> Option<Option<String>> {
+        match self.u8()? {
+            0 => Some(None),
+            _ => String::from_utf8(self.bytes()?.to_vec()).ok().map(Some),
+        }
+    }
+
+    fn digests(&mut self) -> Option<Option<Digests>> {
+        if self.u8()? == 0 {
+            return Some(None);
+        }
+        Some(Some(Digests {
+            size: self.u64()?,
+            md5: self.opt_str()?,
+            sha1: self.opt_str()?,
+            sha256: self.opt_str()?,
+        }))
+    }
+
+    fn entry(&mut self) -> Option<CacheEntry> {
+        let legacy = self.version < 2;
+        Some(CacheEntry {
+            crc32: self.u32()?,
+            headerless_crc32: self.opt_u32()?,
+            matched_variant: match self.u8()? {
+                1 => Some(CrcVariant::Raw),
+                2 => Some(CrcVariant::Headerless),
+                _ => None,
+            },
+            file_size: self.u64()?,
+            modified_time: self.u64()?,
+            last_accessed: self.u64()?,
+            raw_digests: self.digests()?,
+            headerless_digests: self.digests()?,
+            fingerprint: if legacy { None } else { self.opt_u64()? },
+            relinked: !legacy && self.u8()? != 0,
+        })
+    }
+}
+
+/// Paths are stored as raw OS bytes on Unix so non-UTF-8 names survive
+#[cfg(unix)]
+fn path_to_bytes(path: &Path) -> std::borrow::Cow<'_, [u8]> {
+    use std::os::unix::ffi::OsStrExt;
+    std::borrow::Cow::Borrowed(path.as_os_str().as_bytes())
+}
+
+#[cfg(not(unix))]
+fn path_to_bytes(path: &Path) -> std::borrow::Cow<'_, [u8]> {
+    std::borrow::Cow::Owned(path.to_string_lossy().into_owned().into_bytes())
+}
+
+#[cfg(unix)]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+    PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn sample_entry() -> CacheEntry {
+        CacheEntry {
+            crc32: 0xDEADBEEF,
+            headerless_crc32: Some(0x12345678),
+            matched_variant: Some(CrcVariant::Headerless),
+            raw_digests: Some(Digests {
+                size: 1024,
+                md5: Some("d41d8cd98f00b204e9800998ecf8427e".to_string()),
+                sha1: None,
+                sha256: None,
+            }),
+            headerless_digests: None,
+            fingerprint: Some(0x0123_4567_89AB_CDEF),
+            relinked: true,
+            file_size: 1040,
+            modified_time: 1_700_000_000,
+            last_accessed: 1_700_000_100,
+        }
+    }
+
+    #[test]
+    fn test_snapshot_and_append_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("cache.bin");
+
+        let mut entries = HashMap::new();
+        entries.insert(PathBuf::from("roms/game.nes"), sample_entry());
+        entries.insert(PathBuf::from("roms/pack.zip#inner.sfc"), sample_entry());
+        let len = write_snapshot(&path, &entries).unwrap();
+
+        let len = append(&path, len, &[LogRecord::Remove(PathBuf::from("roms/game.nes"))]).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().len(), len);
+
+        let replayed = replay(&path).unwrap();
+        assert_eq!(replayed.records, 3);
+        assert_eq!(replayed.valid_len, len);
+        assert_eq!(replayed.entries.len(), 1);
+        assert_eq!(replayed.entries[Path::new("roms/pack.zip#inner.sfc")], sample_entry());
+    }
+
+    #[test]
+    fn test_rejects_unknown_schema_version() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("cache.bin");
+
+        let mut data = MAGIC.to_vec();
+        data.extend_from_slice(&(SCHEMA_VERSION + 1).to_le_bytes());
+        fs::write(&path, data).unwrap();
+
+        let err = replay(&path).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref(),
+            Some(CacheError::UnsupportedVersion { version, .. }) if *version == SCHEMA_VERSION + 1
+        ));
+        assert!(!is_legacy_json(&path));
+    }
+}
@@ -1,6 +1,10 @@
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
+use crate::crc32::HashAlgorithm;
 use crate::platform::Platform;
+use crate::playlist::PlaylistFormat;
+#[cfg(feature = "watch-mode")]
+use crate::watch::WatchBackend;
 
 #[derive(Parser, Clone)]
 #[command(
@@ -108,6 +112,44 @@ pub struct Args {
     #[arg(long)]
     pub no_crc: bool,
 
+    /// Agrupa jogos multi-disco em arquivos .m3u
+    #[arg(long)]
+    pub generate_m3u: bool,
+
+    /// Diretório para os arquivos .m3u gerados (padrão: junto aos discos)
+    #[arg(long, value_name = "PATH")]
+    pub m3u_dir: Option<PathBuf>,
+
+    /// Grava o caminho do core de cada sistema na plataforma de destino em
+    /// vez de "DETECT" (respeita [cores] da configuração)
+    #[arg(long)]
+    pub core_paths: bool,
+
+    /// Diretório info/ do RetroArch com os arquivos .info dos cores
+    #[arg(long, value_name = "PATH")]
+    pub core_info_dir: Option<PathBuf>,
+
+    /// retroarch.cfg da instalação local, ou da origem na conversão
+    /// (procurado nos locais comuns ao indexar)
+    #[arg(long, value_name = "PATH")]
+    pub retroarch_cfg: Option<PathBuf>,
+
+    /// retroarch.cfg copiado do dispositivo de destino
+    #[arg(long, value_name = "PATH")]
+    pub target_retroarch_cfg: Option<PathBuf>,
+
+    /// Diretório com header skippers XML do clrmamepro (além dos embutidos)
+    #[arg(long, value_name = "PATH")]
+    pub header_skippers: Option<PathBuf>,
+
+    /// Hashes calculados no scan além do CRC32 (ex.: --hash md5,sha1)
+    #[arg(long = "hash", value_enum, value_delimiter = ',', value_name = "ALGORITMOS")]
+    pub hash_algorithms: Option<Vec<HashAlgorithm>>,
+
+    /// Formato das playlists geradas (legacy: RetroArch anterior ao 1.7.6)
+    #[arg(long, value_enum)]
+    pub playlist_format: Option<PlaylistFormat>,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
@@ -134,6 +176,10 @@ pub enum Commands {
         /// Valida se os caminhos convertidos existem
         #[arg(long)]
         validate_paths: bool,
+
+        /// Formato da playlist gerada (padrão: o mesmo da entrada)
+        #[arg(long, value_enum)]
+        format: Option<PlaylistFormat>,
     },
 
     /// Converte todas as playlists de um diretório
@@ -157,6 +203,10 @@ pub enum Commands {
         /// Valida se os caminhos convertidos existem
         #[arg(long)]
         validate_paths: bool,
+
+        /// Formato das playlists geradas (padrão: o mesmo de cada entrada)
+        #[arg(long, value_enum)]
+        format: Option<PlaylistFormat>,
     },
 
     /// Monitora diretórios de ROMs e atualiza playlists automaticamente
@@ -173,6 +223,38 @@ pub enum Commands {
         /// Incluir arquivos de arquivo no monitoramento
         #[arg(long)]
         include_archives: bool,
+
+        /// Como detectar mudanças: eventos nativos, varredura periódica
+        /// (polling) ou automático, que usa polling em montagens de rede/FUSE
+        #[arg(long, value_enum, default_value = "auto")]
+        backend: WatchBackend,
+
+        /// Intervalo entre varreduras no modo polling, em segundos
+        #[arg(long, default_value = "5")]
+        poll_interval: u64,
+
+        /// Roda como daemon, aceitando comandos de `watch-ctl` pelo socket de controle
+        #[arg(long)]
+        daemon: bool,
+
+        /// Socket de controle do daemon (padrão: diretório de runtime do usuário)
+        #[arg(long, requires = "daemon")]
+        socket: Option<PathBuf>,
+    },
+
+    /// Envia comandos a um daemon de monitoramento em execução
+    #[cfg(feature = "watch-mode")]
+    WatchCtl {
+        /// Socket de controle do daemon (padrão: diretório de runtime do usuário)
+        #[arg(long)]
+        socket: Option<PathBuf>,
+
+        /// Imprime a resposta do daemon em JSON, para scripts
+        #[arg(long)]
+        json: bool,
+
+        #[command(subcommand)]
+        action: WatchCtlAction,
     },
 
     /// Baixa arquivos DAT automaticamente
@@ -237,6 +319,17 @@ pub enum Commands {
         report: Option<PathBuf>,
     },
 
+    /// Converte ROMs de N64 (.v64/.n64) para .z64 big-endian
+    Normalize {
+        /// Mantém os arquivos originais após a conversão
+        #[arg(long)]
+        keep_original: bool,
+
+        /// Apenas simular (não gravar arquivos)
+        #[arg(long)]
+        dry_run: bool,
+    },
+
     /// Gerencia cache de CRC32
     Cache {
         #[command(subcommand)]
@@ -244,6 +337,30 @@ pub enum Commands {
     },
 }
 
+/// Comandos aceitos pelo daemon de monitoramento
+#[cfg(feature = "watch-mode")]
+#[derive(Subcommand, Clone, Debug)]
+pub enum WatchCtlAction {
+    /// Mostra diretórios monitorados, fila de eventos e última varredura
+    Status,
+    /// Pausa a aplicação de eventos; eles ficam na fila até retomar
+    Pause,
+    /// Retoma a aplicação de eventos
+    Resume,
+    /// Passa a monitorar e indexar mais um diretório (a indexação roda em segundo plano)
+    Add {
+        path: PathBuf,
+    },
+    /// Deixa de monitorar um diretório e remove suas ROMs das playlists
+    Remove {
+        path: PathBuf,
+    },
+    /// Agenda uma varredura do zero de todos os diretórios monitorados
+    Rescan,
+    /// Encerra o daemon
+    Stop,
+}
+
 /// Estratégias de deduplicação
 #[derive(clap::ValueEnum, Clone, Copy, Debug)]
 pub enum DeduplicationStrategy {
@@ -277,4 +394,19 @@ pub enum CacheAction {
         #[arg(long, default_value = "30")]
         max_age: u64,
     },
+    /// Recalcula os hashes dos arquivos em cache para detectar corrupção
+    /// silenciosa. Termina com código 1 se algum arquivo não confere.
+    Verify {
+        /// Verifica apenas uma amostra aleatória de N arquivos
+        #[arg(long)]
+        sample: Option<usize>,
+
+        /// Semente da amostra aleatória, para repetir uma verificação
+        #[arg(long, requires = "sample")]
+        seed: Option<u64>,
+
+        /// Arquivo para salvar o relatório em JSON
+        #[arg(long)]
+        report: Option<PathBuf>,
+    },
 }
@@ -2,7 +2,9 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
+use crate::crc32::HashAlgorithm;
 use crate::platform::Platform;
+use crate::playlist::PlaylistFormat;
 use crate::error::ConfigError;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -11,6 +13,8 @@ pub struct Config {
     pub paths: PathsConfig,
     pub cores: CoresConfig,
     pub dat: DatConfig,
+    #[serde(default)]
+    pub hooks: HooksConfig,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -22,6 +26,15 @@ pub struct GeneralConfig {
     pub threads: Option<usize>,
     pub calculate_crc: bool,
     pub recursive_scan: bool,
+    /// Group multi-disc games into generated .m3u files
+    #[serde(default)]
+    pub generate_m3u: bool,
+    /// Format of the generated playlists; `legacy` for RetroArch before 1.7.6
+    #[serde(default)]
+    pub playlist_format: PlaylistFormat,
+    /// Digests computed while scanning; CRC32 is always included
+    #[serde(default)]
+    pub hash_algorithms: Option<Vec<HashAlgorithm>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -30,12 +43,37 @@ pub struct PathsConfig {
     pub output_directory: PathBuf,
     pub dat_directory: Option<PathBuf>,
     pub custom_extensions: Option<Vec<String>>,
+    /// Where generated .m3u files go (defaults to the disc's own directory)
+    #[serde(default)]
+    pub m3u_directory: Option<PathBuf>,
+    /// `retroarch.cfg` of the local install, read for playlist, core and
+    /// info directories (looked up in the usual install locations when unset)
+    #[serde(default)]
+    pub retroarch_config: Option<PathBuf>,
+    /// `retroarch.cfg` copied from the target device, for its ROM and core paths
+    #[serde(default)]
+    pub target_retroarch_config: Option<PathBuf>,
+    /// clrmamepro header skipper XMLs, added to the built-in ones
+    #[serde(default)]
+    pub header_skipper_directory: Option<PathBuf>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CoresConfig {
+    /// Write each system's core path and name into playlists instead of "DETECT"
+    #[serde(default)]
+    pub write_core_paths: bool,
     pub override_system_cores: std::collections::HashMap<String, String>,
+    /// Cores directory per target platform
     pub custom_core_paths: std::collections::HashMap<Platform, PathBuf>,
+    /// RetroArch `info/` directory with the cores' `.info` files
+    /// (looked up in the usual install locations when unset)
+    #[serde(default)]
+    pub info_directory: Option<PathBuf>,
+    /// Local copy or mount of the target device's cores directory; only the
+    /// cores found there are written to playlists
+    #[serde(default)]
+    pub installed_cores_directory: Option<PathBuf>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -45,6 +83,45 @@ pub struct DatConfig {
     pub update_interval_days: u32,
 }
 
+/// Shell commands run after playlists change. Each gets the details as
+/// `RETROARCH_INDEXER_*` environment variables and as JSON on stdin.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    /// After each playlist file is written
+    pub after_playlist: Vec<String>,
+    /// After a batch of watch events has been applied
+    pub after_watch_batch: Vec<String>,
+    /// After a full index run
+    pub after_index: Vec<String>,
+    /// Hooks still running after this many seconds are killed
+    pub timeout_secs: u64,
+    pub on_failure: HookFailurePolicy,
+}
+
+/// What a failed or timed out hook does to the run that triggered it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookFailurePolicy {
+    /// Log a warning and carry on
+    #[default]
+    Warn,
+    /// Fail the index run, or stop the watcher
+    Abort,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            after_playlist: Vec::new(),
+            after_watch_batch: Vec::new(),
+            after_index: Vec::new(),
+            timeout_secs: 60,
+            on_failure: HookFailurePolicy::Warn,
+        }
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -56,14 +133,24 @@ impl Default for Config {
                 threads: None,
                 calculate_crc: true,
                 recursive_scan: true,
+                generate_m3u: false,
+                playlist_format: PlaylistFormat::Json,
+                hash_algorithms: None,
             },
             paths: PathsConfig {
                 roms_directories: Vec::new(),
                 output_directory: PathBuf::from("./playlists"),
                 dat_directory: Some(PathBuf::from("./dats")),
                 custom_extensions: None,
+                m3u_directory: None,
+                retroarch_config: None,
+                target_retroarch_config: None,
+                header_skipper_directory: None,
             },
             cores: CoresConfig {
+                write_core_paths: false,
+                info_directory: None,
+                installed_cores_directory: None,
                 override_system_cores: std::collections::HashMap::new(),
                 custom_core_paths: std::collections::HashMap::new(),
             },
@@ -75,6 +162,7 @@ impl Default for Config {
                 ],
                 update_interval_days: 30,
             },
+            hooks: HooksConfig::default(),
         }
     }
 }
@@ -132,11 +220,16 @@ impl Config {
     }
 
     pub fn get_default_config_path() -> PathBuf {
+        Self::get_default_config_dir().join("config.toml")
+    }
+
+    /// Per-user directory for config.toml and systems.toml/cores.toml overrides
+    pub fn get_default_config_dir() -> PathBuf {
         // Try to use system config directory
         if let Some(config_dir) = dirs::config_dir() {
-            config_dir.join("retroarch-indexer").join("config.toml")
+            config_dir.join("retroarch-indexer")
         } else {
-            PathBuf::from("config.toml")
+            PathBuf::from(".")
         }
     }
 
@@ -4,17 +4,21 @@ use std::path::{Path, PathBuf};
 use walkdir;
 
 use crate::playlist::Playlist;
-use crate::platform::{Platform, PlatformPathConverter};
+use crate::platform::{Platform, PlatformPathConverter, PlatformPaths};
 use crate::error::ConverterError;
 
 pub struct PlaylistConverter {
     validate_paths: bool,
+    source_paths: PlatformPaths,
+    target_paths: PlatformPaths,
 }
 
 impl PlaylistConverter {
     pub fn new() -> Self {
         Self {
             validate_paths: false,
+            source_paths: PlatformPaths::default(),
+            target_paths: PlatformPaths::default(),
         }
     }
 
@@ -23,6 +27,14 @@ impl PlaylistConverter {
         self
     }
 
+    /// ROM and core directories of the devices, used instead of the
+    /// platforms' default locations
+    pub fn with_device_paths(mut self, source: PlatformPaths, target: PlatformPaths) -> Self {
+        self.source_paths = source;
+        self.target_paths = target;
+        self
+    }
+
     pub fn load_playlist(&self, path: &Path) -> Result<Playlist> {
         info!("Carregando playlist: {}", path.display());
         Playlist::load(path)
@@ -130,7 +142,9 @@ impl PlaylistConverter {
         info!("Convertendo playlist de {} para {}", 
               source.display_name(), target.display_name());
 
-        let converter = PlatformPathConverter::new(source, target);
+        let converter = PlatformPathConverter::new(source, target)
+            .with_source_paths(self.source_paths.clone())
+            .with_target_paths(self.target_paths.clone());
         let mut new_playlist = playlist.clone();
 
         // Convert default core if present
@@ -138,6 +152,13 @@ impl PlaylistConverter {
             new_playlist.default_core_path = Some(converter.convert_core_path(core_path));
         }
 
+        // Directories RetroArch's manual scan remembers for this playlist
+        for dir in [&mut new_playlist.base_content_directory, &mut new_playlist.scan_content_dir] {
+            if let Some(path) = dir.as_mut().filter(|path| !path.is_empty()) {
+                *path = convert_directory(&converter, path);
+            }
+        }
+
         // Convert all items
         for item in &mut new_playlist.items {
             // Convert ROM path
@@ -148,6 +169,13 @@ impl PlaylistConverter {
                 item.core_path = Some(converter.convert_core_path(core_path));
             }
 
+            // Extra content of subsystem launches
+            if let Some(ref mut roms) = item.subsystem_roms {
+                for rom in roms.iter_mut() {
+                    *rom = converter.convert_rom_path(rom);
+                }
+            }
+
             // Validate converted paths if requested
             if self.validate_paths {
                 if let Err(e) = self.validate_converted_paths(item, target) {
@@ -248,6 +276,13 @@ impl Default for PlaylistConverter {
     }
 }
 
+/// `convert_rom_path` for a directory. The trailing separator makes a ROM
+/// root map to the target's ROM root instead of one of its subdirectories.
+fn convert_directory(converter: &PlatformPathConverter, dir: &str) -> String {
+    let converted = converter.convert_rom_path(&format!("{}/", dir.trim_end_matches(['/', '\\'])));
+    converted.trim_end_matches(['/', '\\']).to_string()
+}
+
 #[derive(Debug, Default)]
 pub struct ConversionReport {
     pub successful_conversions: Vec<String>,
@@ -346,4 +381,24 @@ mod tests {
         assert!(converted.items[0].path.contains("/switch/"));
         assert!(converted.items[0].core_path.as_ref().unwrap().contains("_libnx.a"));
     }
+
+    #[test]
+    fn test_playlist_content_directories_conversion() {
+        let converter = PlaylistConverter::new().with_device_paths(
+            PlatformPaths { roms: Some("/home/deck/ROMs".to_string()), cores: None },
+            PlatformPaths { roms: Some("sdmc:/roms".to_string()), cores: None },
+        );
+
+        let mut playlist = Playlist::new();
+        playlist.base_content_directory = Some("/home/deck/ROMs".to_string());
+        playlist.scan_content_dir = Some("/home/deck/ROMs/snes/".to_string());
+        let converted = converter.convert_playlist(&playlist, Platform::SteamDeck, Platform::Switch).unwrap();
+        assert_eq!(converted.base_content_directory.as_deref(), Some("sdmc:/roms"));
+        assert_eq!(converted.scan_content_dir.as_deref(), Some("sdmc:/roms/snes"));
+
+        // Playlists that were never scanned keep the empty value RetroArch writes
+        playlist.base_content_directory = Some(String::new());
+        let converted = converter.convert_playlist(&playlist, Platform::SteamDeck, Platform::Switch).unwrap();
+        assert_eq!(converted.base_content_directory.as_deref(), Some(""));
+    }
 }
@@ -0,0 +1,266 @@
+use anyhow::{Context, Result};
+use log::{debug, warn};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Contents of one libretro `<core>_libretro.info` file
+#[derive(Debug, Clone, Default)]
+pub struct LibretroCoreInfo {
+    /// File name without `_libretro.info`, e.g. "mgba"
+    pub id: String,
+    pub display_name: String,
+    pub core_name: Option<String>,
+    pub system_name: Option<String>,
+    /// Lowercase, without the dot
+    pub supported_extensions: Vec<String>,
+    /// RetroArch database names of the systems the core runs, e.g. "Nintendo - Game Boy"
+    pub databases: Vec<String>,
+    pub firmware: Vec<CoreFirmware>,
+    /// Every key of the file, for entries not mapped to a field
+    pub fields: HashMap<String, String>,
+}
+
+/// BIOS or firmware file listed by a core
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoreFirmware {
+    /// Path relative to RetroArch's system directory
+    pub path: String,
+    pub description: String,
+    pub optional: bool,
+}
+
+/// Core `.info` files of a RetroArch `info/` directory, optionally
+/// narrowed down to the cores installed on the target device
+#[derive(Debug, Default)]
+pub struct CoreInfoRepository {
+    cores: BTreeMap<String, LibretroCoreInfo>,
+    /// Ids with a core library in the installed cores directory, when known
+    installed: Option<HashSet<String>>,
+}
+
+impl LibretroCoreInfo {
+    /// Parse the `key = "value"` lines of an `.info` file
+    pub fn parse(id: &str, content: &str) -> Self {
+        let fields = parse_key_values(content);
+
+        let list = |key: &str| -> Vec<String> {
+            fields.get(key)
+                .map(|value| value.split('|').map(str::trim).filter(|item| !item.is_empty()).map(str::to_string).collect())
+                .unwrap_or_default()
+        };
+
+        let firmware_count = fields.get("firmware_count").and_then(|count| count.parse().ok()).unwrap_or(0);
+        let firmware = (0..firmware_count)
+            .filter_map(|index: usize| {
+                Some(CoreFirmware {
+                    path: fields.get(&format!("firmware{}_path", index))?.clone(),
+                    description: fields.get(&format!("firmware{}_desc", index)).cloned().unwrap_or_default(),
+                    optional: fields.get(&format!("firmware{}_opt", index)).is_some_and(|opt| opt == "true"),
+                })
+            })
+            .collect();
+
+        Self {
+            id: id.to_string(),
+            display_name: fields.get("display_name").cloned().unwrap_or_else(|| id.to_string()),
+            core_name: fields.get("corename").cloned(),
+            system_name: fields.get("systemname").cloned(),
+            supported_extensions: list("supported_extensions").into_iter().map(|ext| ext.to_lowercase()).collect(),
+            databases: list("database"),
+            firmware,
+            fields,
+        }
+    }
+
+    /// Whether the core loads files with `extension` (with or without the dot)
+    pub fn supports_extension(&self, extension: &str) -> bool {
+        let extension = extension.trim_start_matches('.').to_lowercase();
+        self.supported_extensions.contains(&extension)
+    }
+}
+
+impl CoreInfoRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load every `*_libretro.info` file in `directory`
+    pub fn load_directory(directory: &Path) -> Result<Self> {
+        let mut repository = Self::new();
+        let entries = std::fs::read_dir(directory)
+            .with_context(|| format!("Falha ao ler diretório de info dos cores: {}", directory.display()))?;
+
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let path = entry.path();
+            let Some(id) = core_id(&path, ".info") else {
+                continue;
+            };
+            match std::fs::read_to_string(&path) {
+                Ok(content) => repository.add(LibretroCoreInfo::parse(&id, &content)),
+                Err(e) => warn!("Falha ao ler {}: {}", path.display(), e),
+            }
+        }
+
+        debug!("{} cores carregados de {}", repository.cores.len(), directory.display());
+        Ok(repository)
+    }
+
+    /// First RetroArch `info/` directory found in the usual install locations
+    pub fn discover() -> Option<PathBuf> {
+        let mut candidates = Vec::new();
+        if let Some(config) = dirs::config_dir() {
+            candidates.push(config.join("retroarch/info"));
+            candidates.push(config.join("RetroArch/info"));
+        }
+        if let Some(home) = dirs::home_dir() {
+            candidates.push(home.join(".var/app/org.libretro.RetroArch/config/retroarch/info"));
+            candidates.push(home.join("Library/Application Support/RetroArch/info"));
+        }
+        candidates.extend([
+            PathBuf::from("/usr/share/libretro/info"),
+            PathBuf::from("/usr/local/share/libretro/info"),
+            PathBuf::from("C:/RetroArch/info"),
+            PathBuf::from("C:/RetroArch-Win64/info"),
+        ]);
+
+        candidates.into_iter().find(|dir| dir.is_dir())
+    }
+
+    pub fn add(&mut self, info: LibretroCoreInfo) {
+        self.cores.insert(info.id.clone(), info);
+    }
+
+    /// Restrict core choices to the libraries found in `directory`, a local
+    /// copy or mount of the target device's cores directory. Returns how
+    /// many were found.
+    pub fn mark_installed(&mut self, directory: &Path) -> Result<usize> {
+        let entries = std::fs::read_dir(directory)
+            .with_context(|| format!("Falha ao ler diretório de cores: {}", directory.display()))?;
+
+        let installed: HashSet<String> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| core_id(&entry.path(), ""))
+            .collect();
+        let count = installed.len();
+        self.installed = Some(installed);
+        Ok(count)
+    }
+
+    /// Whether the core is installed; true when no installed list was loaded
+    pub fn is_installed(&self, id: &str) -> bool {
+        self.installed.as_ref().is_none_or(|installed| installed.contains(id))
+    }
+
+    pub fn get(&self, id: &str) -> Option<&LibretroCoreInfo> {
+        self.cores.get(id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &LibretroCoreInfo> {
+        self.cores.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cores.is_empty()
+    }
+
+    /// Installed cores whose `database` lists `system`, by id
+    pub fn cores_for_system(&self, system: &str) -> Vec<&LibretroCoreInfo> {
+        self.cores.values()
+            .filter(|info| info.databases.iter().any(|database| database == system))
+            .filter(|info| self.is_installed(&info.id))
+            .collect()
+    }
+}
+
+/// `key = "value"` lines of libretro's config format, shared by `.info`
+/// files and `retroarch.cfg`. Comments and blank lines are skipped.
+pub(crate) fn parse_key_values(content: &str) -> HashMap<String, String> {
+    let mut fields = HashMap::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        let value = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(value);
+        fields.insert(key.trim().to_string(), value.to_string());
+    }
+    fields
+}
+
+/// Core id from a file named `<id>_libretro<suffix...>`, e.g.
+/// `mgba_libretro.info` or `mgba_libretro_android.so`
+fn core_id(path: &Path, required_suffix: &str) -> Option<String> {
+    let name = path.file_name()?.to_str()?;
+    if !name.ends_with(required_suffix) {
+        return None;
+    }
+    let (id, _) = name.split_once("_libretro")?;
+    (!id.is_empty()).then(|| id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const MGBA_INFO: &str = r#"# Software Information
+display_name = "Nintendo - Game Boy Advance (mGBA)"
+authors = "endrift"
+supported_extensions = "gb|gbc|gba"
+corename = "mGBA"
+database = "Nintendo - Game Boy|Nintendo - Game Boy Color|Nintendo - Game Boy Advance"
+systemname = "Game Boy Advance"
+
+firmware_count = 2
+firmware0_desc = "gba_bios.bin (Game Boy Advance BIOS)"
+firmware0_path = "gba_bios.bin"
+firmware0_opt = "true"
+firmware1_desc = "gb_bios.bin (Game Boy BIOS)"
+firmware1_path = "gb_bios.bin"
+firmware1_opt = "false"
+"#;
+
+    #[test]
+    fn test_parse_info_file() {
+        let info = LibretroCoreInfo::parse("mgba", MGBA_INFO);
+
+        assert_eq!(info.display_name, "Nintendo - Game Boy Advance (mGBA)");
+        assert_eq!(info.core_name.as_deref(), Some("mGBA"));
+        assert_eq!(info.databases.len(), 3);
+        assert!(info.supports_extension(".GBA"));
+        assert!(!info.supports_extension("nes"));
+        assert_eq!(info.firmware.len(), 2);
+        assert!(info.firmware[0].optional && !info.firmware[1].optional);
+        assert_eq!(info.fields.get("authors").map(String::as_str), Some("endrift"));
+    }
+
+    #[test]
+    fn test_installed_cores_filter_candidates() {
+        let info_dir = tempdir().unwrap();
+        let cores_dir = tempdir().unwrap();
+        std::fs::write(info_dir.path().join("mgba_libretro.info"), MGBA_INFO).unwrap();
+        std::fs::write(
+            info_dir.path().join("gambatte_libretro.info"),
+            "display_name = \"Gambatte\"\nsupported_extensions = \"gb|gbc|dmg\"\ndatabase = \"Nintendo - Game Boy|Nintendo - Game Boy Color\"\n",
+        ).unwrap();
+        std::fs::write(info_dir.path().join("readme.txt"), "not a core").unwrap();
+
+        let mut repository = CoreInfoRepository::load_directory(info_dir.path()).unwrap();
+        assert_eq!(repository.len(), 2);
+        let ids = |cores: Vec<&LibretroCoreInfo>| cores.into_iter().map(|info| info.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(repository.cores_for_system("Nintendo - Game Boy")), vec!["gambatte", "mgba"]);
+
+        std::fs::write(cores_dir.path().join("gambatte_libretro_android.so"), b"").unwrap();
+        assert_eq!(repository.mark_installed(cores_dir.path()).unwrap(), 1);
+        assert_eq!(ids(repository.cores_for_system("Nintendo - Game Boy")), vec!["gambatte"]);
+        assert!(repository.cores_for_system("Nintendo - Game Boy Advance").is_empty());
+    }
+}
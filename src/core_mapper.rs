@@ -1,5 +1,8 @@
 use std::collections::HashMap;
+use std::path::PathBuf;
+use crate::core_info::CoreInfoRepository;
 use crate::platform::Platform;
+use crate::system_registry::SystemRegistry;
 
 #[derive(Debug, Clone)]
 pub struct CoreInfo {
@@ -7,6 +10,8 @@ pub struct CoreInfo {
     pub library_name: String,
     pub supported_systems: Vec<String>,
     pub platform_cores: HashMap<Platform, String>,
+    /// Lowercase extensions the core loads; empty when unknown
+    pub supported_extensions: Vec<String>,
 }
 
 impl CoreInfo {
@@ -16,6 +21,7 @@ impl CoreInfo {
             library_name,
             supported_systems: Vec::new(),
             platform_cores: HashMap::new(),
+            supported_extensions: Vec::new(),
         }
     }
 
@@ -32,6 +38,12 @@ impl CoreInfo {
     pub fn get_core_for_platform(&self, platform: Platform) -> Option<String> {
         self.platform_cores.get(&platform).cloned()
     }
+
+    /// Whether the core loads `extension`, assuming it does when unknown
+    pub fn supports_extension(&self, extension: &str) -> bool {
+        self.supported_extensions.is_empty()
+            || self.supported_extensions.iter().any(|supported| supported.eq_ignore_ascii_case(extension))
+    }
 }
 
 #[derive(Debug, Default)]
@@ -40,158 +52,159 @@ pub struct CoreMapper {
     system_cores: HashMap<String, String>,
     // Core name -> CoreInfo mapping
     cores: HashMap<String, CoreInfo>,
+    // System name -> other cores able to run it, from the `.info` files
+    system_candidates: HashMap<String, Vec<String>>,
+    // Emit concrete core paths instead of letting RetroArch detect the core
+    core_paths: bool,
+    // System name -> core chosen by the user (`cores.override_system_cores`)
+    overrides: HashMap<String, String>,
+    // Platform -> cores directory replacing `Platform::default_cores_path`
+    cores_directories: HashMap<Platform, PathBuf>,
 }
 
 impl CoreMapper {
     pub fn new() -> Self {
+        Self::from_registry(SystemRegistry::global())
+    }
+
+    /// Build the mappings from the `[cores]` and `[systems]` definitions
+    pub fn from_registry(registry: &SystemRegistry) -> Self {
         let mut mapper = Self::default();
-        mapper.initialize_default_mappings();
+
+        for (core_name, core) in registry.cores() {
+            let mut info = CoreInfo::new(core.display_name.clone(), core_name.clone());
+            info.platform_cores = core.platforms.clone();
+            mapper.cores.insert(core_name.clone(), info);
+        }
+
+        for (_, system) in registry.systems() {
+            let Some(core_name) = &system.default_core else {
+                continue;
+            };
+            if let Some(info) = mapper.cores.get_mut(core_name) {
+                info.supported_systems.push(system.name.clone());
+            }
+            mapper.system_cores.insert(system.name.clone(), core_name.clone());
+        }
+
         mapper
     }
 
-    fn initialize_default_mappings(&mut self) {
-        // Nintendo 64
-        let mupen64plus = CoreInfo::new(
-            "Mupen64Plus-Next".to_string(),
-            "mupen64plus_next".to_string(),
-        )
-        .with_systems(vec!["Nintendo - Nintendo 64".to_string()])
-        .with_platform_core(Platform::Windows, "mupen64plus_next_libretro.dll".to_string())
-        .with_platform_core(Platform::Linux, "mupen64plus_next_libretro.so".to_string())
-        .with_platform_core(Platform::MacOS, "mupen64plus_next_libretro.dylib".to_string())
-        .with_platform_core(Platform::Android, "mupen64plus_next_libretro_android.so".to_string())
-        .with_platform_core(Platform::Switch, "mupen64plus_next_libretro_libnx.nro".to_string());
-
-        self.cores.insert("mupen64plus_next".to_string(), mupen64plus);
-        self.system_cores.insert("Nintendo - Nintendo 64".to_string(), "mupen64plus_next".to_string());
-
-        // SNES
-        let snes9x = CoreInfo::new(
-            "Snes9x".to_string(),
-            "snes9x".to_string(),
-        )
-        .with_systems(vec!["Nintendo - Super Nintendo Entertainment System".to_string()])
-        .with_platform_core(Platform::Windows, "snes9x_libretro.dll".to_string())
-        .with_platform_core(Platform::Linux, "snes9x_libretro.so".to_string())
-        .with_platform_core(Platform::MacOS, "snes9x_libretro.dylib".to_string())
-        .with_platform_core(Platform::Android, "snes9x_libretro_android.so".to_string())
-        .with_platform_core(Platform::Switch, "snes9x_libretro_libnx.nro".to_string());
-
-        self.cores.insert("snes9x".to_string(), snes9x);
-        self.system_cores.insert("Nintendo - Super Nintendo Entertainment System".to_string(), "snes9x".to_string());
-
-        // Game Boy Advance
-        let mgba = CoreInfo::new(
-            "mGBA".to_string(),
-            "mgba".to_string(),
-        )
-        .with_systems(vec![
-            "Nintendo - Game Boy Advance".to_string(),
-            "Nintendo - Game Boy Color".to_string(),
-            "Nintendo - Game Boy".to_string(),
-        ])
-        .with_platform_core(Platform::Windows, "mgba_libretro.dll".to_string())
-        .with_platform_core(Platform::Linux, "mgba_libretro.so".to_string())
-        .with_platform_core(Platform::MacOS, "mgba_libretro.dylib".to_string())
-        .with_platform_core(Platform::Android, "mgba_libretro_android.so".to_string())
-        .with_platform_core(Platform::Switch, "mgba_libretro_libnx.nro".to_string());
-
-        self.cores.insert("mgba".to_string(), mgba);
-        self.system_cores.insert("Nintendo - Game Boy Advance".to_string(), "mgba".to_string());
-        self.system_cores.insert("Nintendo - Game Boy Color".to_string(), "mgba".to_string());
-        self.system_cores.insert("Nintendo - Game Boy".to_string(), "mgba".to_string());
-
-        // Genesis/Mega Drive
-        let genesis_plus_gx = CoreInfo::new(
-            "Genesis Plus GX".to_string(),
-            "genesis_plus_gx".to_string(),
-        )
-        .with_systems(vec![
-            "Sega - Mega Drive - Genesis".to_string(),
-            "Sega - 32X".to_string(),
-            "Sega - Master System - Mark III".to_string(),
-            "Sega - Game Gear".to_string(),
-        ])
-        .with_platform_core(Platform::Windows, "genesis_plus_gx_libretro.dll".to_string())
-        .with_platform_core(Platform::Linux, "genesis_plus_gx_libretro.so".to_string())
-        .with_platform_core(Platform::MacOS, "genesis_plus_gx_libretro.dylib".to_string())
-        .with_platform_core(Platform::Android, "genesis_plus_gx_libretro_android.so".to_string())
-        .with_platform_core(Platform::Switch, "genesis_plus_gx_libretro_libnx.nro".to_string());
-
-        self.cores.insert("genesis_plus_gx".to_string(), genesis_plus_gx);
-        self.system_cores.insert("Sega - Mega Drive - Genesis".to_string(), "genesis_plus_gx".to_string());
-        self.system_cores.insert("Sega - 32X".to_string(), "genesis_plus_gx".to_string());
-        self.system_cores.insert("Sega - Master System - Mark III".to_string(), "genesis_plus_gx".to_string());
-        self.system_cores.insert("Sega - Game Gear".to_string(), "genesis_plus_gx".to_string());
-
-        // PlayStation
-        let pcsx_rearmed = CoreInfo::new(
-            "PCSX ReARMed".to_string(),
-            "pcsx_rearmed".to_string(),
-        )
-        .with_systems(vec!["Sony - PlayStation".to_string()])
-        .with_platform_core(Platform::Windows, "pcsx_rearmed_libretro.dll".to_string())
-        .with_platform_core(Platform::Linux, "pcsx_rearmed_libretro.so".to_string())
-        .with_platform_core(Platform::MacOS, "pcsx_rearmed_libretro.dylib".to_string())
-        .with_platform_core(Platform::Android, "pcsx_rearmed_libretro_android.so".to_string())
-        .with_platform_core(Platform::Switch, "pcsx_rearmed_libretro_libnx.nro".to_string());
-
-        self.cores.insert("pcsx_rearmed".to_string(), pcsx_rearmed);
-        self.system_cores.insert("Sony - PlayStation".to_string(), "pcsx_rearmed".to_string());
-
-        // NES
-        let fceumm = CoreInfo::new(
-            "FCEUmm".to_string(),
-            "fceumm".to_string(),
-        )
-        .with_systems(vec!["Nintendo - Nintendo Entertainment System".to_string()])
-        .with_platform_core(Platform::Windows, "fceumm_libretro.dll".to_string())
-        .with_platform_core(Platform::Linux, "fceumm_libretro.so".to_string())
-        .with_platform_core(Platform::MacOS, "fceumm_libretro.dylib".to_string())
-        .with_platform_core(Platform::Android, "fceumm_libretro_android.so".to_string())
-        .with_platform_core(Platform::Switch, "fceumm_libretro_libnx.nro".to_string());
-
-        self.cores.insert("fceumm".to_string(), fceumm);
-        self.system_cores.insert("Nintendo - Nintendo Entertainment System".to_string(), "fceumm".to_string());
-
-        // MAME
-        let mame_current = CoreInfo::new(
-            "MAME (Current)".to_string(),
-            "mame".to_string(),
-        )
-        .with_systems(vec!["MAME".to_string()])
-        .with_platform_core(Platform::Windows, "mame_libretro.dll".to_string())
-        .with_platform_core(Platform::Linux, "mame_libretro.so".to_string())
-        .with_platform_core(Platform::MacOS, "mame_libretro.dylib".to_string())
-        .with_platform_core(Platform::Android, "mame_libretro_android.so".to_string())
-        .with_platform_core(Platform::Switch, "mame_libretro_libnx.nro".to_string());
-
-        // MAME 2003 Plus
-        let mame2003_plus = CoreInfo::new(
-            "MAME 2003 Plus".to_string(),
-            "mame2003_plus".to_string(),
-        )
-        .with_systems(vec!["MAME".to_string(), "Arcade".to_string()])
-        .with_platform_core(Platform::Windows, "mame2003_plus_libretro.dll".to_string())
-        .with_platform_core(Platform::Linux, "mame2003_plus_libretro.so".to_string())
-        .with_platform_core(Platform::MacOS, "mame2003_plus_libretro.dylib".to_string())
-        .with_platform_core(Platform::Android, "mame2003_plus_libretro_android.so".to_string())
-        .with_platform_core(Platform::Switch, "mame2003_plus_libretro_libnx.nro".to_string());
-
-        self.cores.insert("mame".to_string(), mame_current);
-        self.cores.insert("mame2003_plus".to_string(), mame2003_plus);
-        self.system_cores.insert("MAME".to_string(), "mame2003_plus".to_string());  // Use MAME 2003 Plus as default
-        self.system_cores.insert("Arcade".to_string(), "mame2003_plus".to_string());
+    /// Replace the built-in core data with RetroArch's `.info` files. Systems
+    /// get their cores from the `database` field, and a system whose default
+    /// core isn't installed switches to an installed one, or to none.
+    pub fn with_core_info(mut self, repository: &CoreInfoRepository) -> Self {
+        for info in repository.iter() {
+            let core = self.cores.entry(info.id.clone())
+                .or_insert_with(|| CoreInfo::new(info.display_name.clone(), info.id.clone()));
+            core.display_name = info.display_name.clone();
+            core.supported_systems = info.databases.clone();
+            core.supported_extensions = info.supported_extensions.clone();
+        }
+
+        let mut systems: Vec<String> = repository.iter()
+            .flat_map(|info| info.databases.iter().cloned())
+            .chain(self.system_cores.keys().cloned())
+            .collect();
+        systems.sort();
+        systems.dedup();
+
+        for system in systems {
+            let candidates: Vec<String> = repository.cores_for_system(&system)
+                .into_iter()
+                .map(|info| info.id.clone())
+                .collect();
+            // Cores without an .info file are kept as long as they are installed
+            let keep_current = self.system_cores.get(&system).is_some_and(|current| {
+                repository.is_installed(current)
+                    && (repository.get(current).is_none() || candidates.contains(current))
+            });
+
+            if !keep_current {
+                match candidates.first() {
+                    Some(first) => {
+                        self.system_cores.insert(system.clone(), first.clone());
+                    }
+                    None => {
+                        self.system_cores.remove(&system);
+                    }
+                }
+            }
+            self.system_candidates.insert(system, candidates);
+        }
+
+        self
+    }
+
+    /// Write the core library path and display name of each system's core
+    /// into playlists, so RetroArch doesn't ask which core to use
+    pub fn with_core_paths(mut self, enabled: bool) -> Self {
+        self.core_paths = enabled;
+        self
+    }
+
+    /// Cores to use for specific systems, by core name
+    pub fn with_overrides(mut self, overrides: HashMap<String, String>) -> Self {
+        self.overrides = overrides;
+        self
     }
 
-    pub fn get_default_core(&self, system: &str, _platform: Platform) -> Option<(String, String)> {
-        // Always return "DETECT" for both core path and core name
-        // This forces RetroArch to auto-detect the appropriate core
-        if self.system_cores.contains_key(system) {
-            Some(("DETECT".to_string(), "DETECT".to_string()))
-        } else {
-            None
+    /// Cores directory per target platform
+    pub fn with_cores_directories(mut self, directories: HashMap<Platform, PathBuf>) -> Self {
+        self.cores_directories = directories;
+        self
+    }
+
+    /// Core path and core name for `system` on `platform`. Both are "DETECT",
+    /// letting RetroArch pick the core, unless core paths are enabled.
+    pub fn get_default_core(&self, system: &str, platform: Platform) -> Option<(String, String)> {
+        let core_name = self.overrides.get(system).or_else(|| self.system_cores.get(system))?;
+        self.resolve_core(core_name, platform)
+    }
+
+    /// Like `get_default_core`, but when the system's core can't load files
+    /// with `extension` another candidate core that can is used instead
+    pub fn get_core_for_file(&self, system: &str, extension: &str, platform: Platform) -> Option<(String, String)> {
+        if self.overrides.contains_key(system) {
+            return self.get_default_core(system, platform);
         }
+
+        let default = self.system_cores.get(system);
+        let supports = |core: &String| self.cores.get(core).is_none_or(|info| info.supports_extension(extension));
+        let core_name = match default {
+            Some(core) if supports(core) => core,
+            _ => self.system_candidates.get(system)
+                .and_then(|candidates| candidates.iter().find(|core| supports(core)))
+                .or(default)?,
+        };
+
+        self.resolve_core(core_name, platform)
+    }
+
+    fn resolve_core(&self, core_name: &String, platform: Platform) -> Option<(String, String)> {
+        if !self.core_paths {
+            return Some(("DETECT".to_string(), "DETECT".to_string()));
+        }
+
+        let info = self.cores.get(core_name);
+        // Overrides may name cores the registry doesn't know
+        let library = info
+            .and_then(|info| info.get_core_for_platform(platform))
+            .unwrap_or_else(|| format!("{}_libretro{}", core_name, platform.core_extension()));
+        let display_name = info
+            .map(|info| info.display_name.clone())
+            .unwrap_or_else(|| core_name.clone());
+
+        Some((self.core_path(platform, &library), display_name))
+    }
+
+    fn core_path(&self, platform: Platform, library: &str) -> String {
+        let directory = self.cores_directories.get(&platform)
+            .map(|dir| dir.to_string_lossy().to_string())
+            .unwrap_or_else(|| platform.default_cores_path().to_string());
+        let separator = if directory.contains('\\') { '\\' } else { '/' };
+
+        format!("{}{}{}", directory.trim_end_matches(['/', '\\']), separator, library)
     }
 
     pub fn get_core_info(&self, core_name: &str) -> Option<&CoreInfo> {
@@ -277,6 +290,94 @@ mod tests {
         assert_eq!(gba_core, "DETECT");
     }
 
+    #[test]
+    fn test_cores_from_registry() {
+        let mapper = CoreMapper::new();
+
+        let mupen = mapper.get_core_info("mupen64plus_next").unwrap();
+        assert_eq!(mupen.display_name, "Mupen64Plus-Next");
+        assert_eq!(mupen.get_core_for_platform(Platform::Linux).as_deref(), Some("mupen64plus_next_libretro.so"));
+        assert!(mupen.supported_systems.contains(&"Nintendo - Nintendo 64".to_string()));
+
+        assert!(mapper.get_supported_systems().contains(&"Nintendo - Virtual Boy".to_string()));
+    }
+
+    #[test]
+    fn test_concrete_core_paths() {
+        let mapper = CoreMapper::new().with_core_paths(true);
+
+        let (core_path, core_name) = mapper
+            .get_default_core("Nintendo - Game Boy Advance", Platform::SteamDeck)
+            .unwrap();
+        assert_eq!(core_path, "/home/deck/.var/app/org.libretro.RetroArch/config/retroarch/cores/mgba_libretro.so");
+        assert_eq!(core_name, "mGBA");
+
+        let (switch_core, _) = mapper
+            .get_default_core("Nintendo - Nintendo 64", Platform::Switch)
+            .unwrap();
+        assert_eq!(switch_core, "/switch/retroarch/cores/mupen64plus_next_libretro_libnx.a");
+    }
+
+    #[test]
+    fn test_core_overrides_and_custom_directories() {
+        let mapper = CoreMapper::new()
+            .with_core_paths(true)
+            .with_overrides(HashMap::from([
+                ("Nintendo - Nintendo 64".to_string(), "parallel_n64".to_string()),
+            ]))
+            .with_cores_directories(HashMap::from([
+                (Platform::Windows, PathBuf::from("D:\\RetroArch\\cores\\")),
+            ]));
+
+        let (core_path, core_name) = mapper
+            .get_default_core("Nintendo - Nintendo 64", Platform::Windows)
+            .unwrap();
+        assert_eq!(core_path, "D:\\RetroArch\\cores\\parallel_n64_libretro.dll");
+        assert_eq!(core_name, "parallel_n64");
+
+        // Systems without an override keep their default core
+        let (gba_core, _) = mapper
+            .get_default_core("Nintendo - Game Boy Advance", Platform::Windows)
+            .unwrap();
+        assert_eq!(gba_core, "D:\\RetroArch\\cores\\mgba_libretro.dll");
+    }
+
+    #[test]
+    fn test_core_info_picks_installed_cores() {
+        use crate::core_info::LibretroCoreInfo;
+
+        let mut repository = CoreInfoRepository::new();
+        repository.add(LibretroCoreInfo::parse("gambatte", "display_name = \"Nintendo - Game Boy / Color (Gambatte)\"\nsupported_extensions = \"gb|gbc|dmg\"\ndatabase = \"Nintendo - Game Boy|Nintendo - Game Boy Color\"\n"));
+        repository.add(LibretroCoreInfo::parse("mgba", "display_name = \"Nintendo - Game Boy Advance (mGBA)\"\nsupported_extensions = \"gb|gbc|gba\"\ndatabase = \"Nintendo - Game Boy|Nintendo - Game Boy Advance\"\n"));
+        let cores_dir = tempfile::tempdir().unwrap();
+        std::fs::write(cores_dir.path().join("gambatte_libretro.so"), b"").unwrap();
+        repository.mark_installed(cores_dir.path()).unwrap();
+
+        let mapper = CoreMapper::new().with_core_info(&repository).with_core_paths(true);
+
+        // mGBA is the registry default for Game Boy but isn't installed
+        let (core_path, core_name) = mapper.get_default_core("Nintendo - Game Boy", Platform::Linux).unwrap();
+        assert_eq!(core_path, "/usr/lib/libretro/gambatte_libretro.so");
+        assert_eq!(core_name, "Nintendo - Game Boy / Color (Gambatte)");
+        assert!(mapper.get_default_core("Nintendo - Game Boy Advance", Platform::Linux).is_none());
+        assert!(!mapper.get_core_for_file("Nintendo - Game Boy", "dmg", Platform::Linux).unwrap().0.contains("mgba"));
+    }
+
+    #[test]
+    fn test_core_for_file_checks_extensions() {
+        use crate::core_info::LibretroCoreInfo;
+
+        let mut repository = CoreInfoRepository::new();
+        repository.add(LibretroCoreInfo::parse("mgba", "display_name = \"mGBA\"\nsupported_extensions = \"gb|gbc|gba\"\ndatabase = \"Nintendo - Game Boy\"\n"));
+        repository.add(LibretroCoreInfo::parse("sameboy", "display_name = \"SameBoy\"\nsupported_extensions = \"gb|gbc|dmg\"\ndatabase = \"Nintendo - Game Boy\"\n"));
+
+        let mapper = CoreMapper::new().with_core_info(&repository).with_core_paths(true);
+        let (_, gb_core) = mapper.get_core_for_file("Nintendo - Game Boy", "gb", Platform::Linux).unwrap();
+        let (_, dmg_core) = mapper.get_core_for_file("Nintendo - Game Boy", "dmg", Platform::Linux).unwrap();
+        assert_eq!(gb_core, "mGBA");
+        assert_eq!(dmg_core, "SameBoy");
+    }
+
     #[test]
     fn test_unknown_system() {
         let mapper = CoreMapper::new();
@@ -1,30 +1,492 @@
-use anyhow::{Result, Context};
+use anyhow::{bail, Result, Context};
+use serde::{Deserialize, Serialize};
 use std::fs::File;
-use std::io::{BufReader, Read};
+use std::io::Read;
 use std::path::Path;
 
+use crate::header_skipper::{SkipperSet, SKIPPER_PROBE_SIZE};
+use crate::n64::{is_n64_extension, read_full, N64ByteOrder};
+
 const BUFFER_SIZE: usize = 64 * 1024; // 64KB buffer
 
-pub fn calculate_crc32(file_path: &Path) -> Result<u32> {
-    let file = File::open(file_path)
+/// Which CRC of a file matched a DAT entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CrcVariant {
+    /// CRC of the whole file (big-endian order for N64 images)
+    Raw,
+    /// CRC with the copier/dumper header removed by a header skipper
+    Headerless,
+}
+
+/// Size and whichever of MD5/SHA1/SHA256 were computed for a hashed stream (lowercase hex)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Digests {
+    pub size: u64,
+    pub md5: Option<String>,
+    pub sha1: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+impl Digests {
+    /// Hex digest for `algorithm`; CRC32 is kept outside `Digests`
+    pub fn get(&self, algorithm: HashAlgorithm) -> Option<&str> {
+        match algorithm {
+            HashAlgorithm::Crc32 => None,
+            HashAlgorithm::Md5 => self.md5.as_deref(),
+            HashAlgorithm::Sha1 => self.sha1.as_deref(),
+            HashAlgorithm::Sha256 => self.sha256.as_deref(),
+        }
+    }
+}
+
+/// One CRC of a file plus whatever else is known about the same byte stream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashCandidate {
+    pub variant: CrcVariant,
+    pub crc32: u32,
+    pub digests: Option<Digests>,
+}
+
+/// Raw CRC plus the header-stripped CRC when a header skipper applied
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CrcVariants {
+    pub raw: u32,
+    pub headerless: Option<u32>,
+    /// Name of the skipper that produced `headerless`
+    pub skipper: Option<String>,
+    /// Size and digests of the stream behind `raw`
+    pub raw_digests: Option<Digests>,
+    /// Size and digests of the stream behind `headerless`
+    pub headerless_digests: Option<Digests>,
+}
+
+impl CrcVariants {
+    /// Whether every algorithm in `algorithms` was computed for the raw stream,
+    /// so a cached result can stand in for a new read
+    pub fn covers(&self, algorithms: HashAlgorithms) -> bool {
+        algorithms.iter().all(|algorithm| {
+            algorithm == HashAlgorithm::Crc32
+                || self.raw_digests.as_ref().is_some_and(|digests| digests.get(algorithm).is_some())
+        })
+    }
+
+    pub fn raw_only(raw: u32) -> Self {
+        Self {
+            raw,
+            ..Default::default()
+        }
+    }
+
+    pub fn get(&self, variant: CrcVariant) -> Option<u32> {
+        match variant {
+            CrcVariant::Raw => Some(self.raw),
+            CrcVariant::Headerless => self.headerless,
+        }
+    }
+
+    /// CRCs to try during DAT lookup, raw first
+    pub fn candidates(&self) -> Vec<(CrcVariant, u32)> {
+        let mut candidates = vec![(CrcVariant::Raw, self.raw)];
+        if let Some(headerless) = self.headerless {
+            candidates.push((CrcVariant::Headerless, headerless));
+        }
+        candidates
+    }
+
+    /// Like `candidates`, with the size and digests of each stream attached
+    pub fn hash_candidates(&self) -> Vec<HashCandidate> {
+        self.candidates()
+            .into_iter()
+            .map(|(variant, crc32)| HashCandidate {
+                variant,
+                crc32,
+                digests: match variant {
+                    CrcVariant::Raw => self.raw_digests.clone(),
+                    CrcVariant::Headerless => self.headerless_digests.clone(),
+                },
+            })
+            .collect()
+    }
+}
+
+/// Digest algorithms the hashing engine can compute
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    Crc32,
+    Md5,
+    Sha1,
+    Sha256,
+}
+
+impl HashAlgorithm {
+    pub const ALL: [HashAlgorithm; 4] = [Self::Crc32, Self::Md5, Self::Sha1, Self::Sha256];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Crc32 => "CRC32",
+            Self::Md5 => "MD5",
+            Self::Sha1 => "SHA1",
+            Self::Sha256 => "SHA256",
+        }
+    }
+
+    /// Whether this build includes the crate behind the algorithm
+    pub fn is_available(&self) -> bool {
+        match self {
+            Self::Crc32 => true,
+            Self::Md5 | Self::Sha256 => cfg!(any(feature = "checksums", feature = "dat-download")),
+            Self::Sha1 => cfg!(feature = "checksums"),
+        }
+    }
+
+    fn bit(&self) -> u8 {
+        1 << (*self as u8)
+    }
+}
+
+/// Set of algorithms computed together in one read of the file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HashAlgorithms(u8);
+
+impl HashAlgorithms {
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub fn crc32_only() -> Self {
+        Self::empty().with(HashAlgorithm::Crc32)
+    }
+
+    /// CRC32 plus the MD5/SHA1 that DATs carry, when built with `checksums`
+    pub fn dat_default() -> Self {
+        let algorithms = Self::crc32_only();
+        if cfg!(feature = "checksums") {
+            algorithms.with(HashAlgorithm::Md5).with(HashAlgorithm::Sha1)
+        } else {
+            algorithms
+        }
+    }
+
+    pub fn with(self, algorithm: HashAlgorithm) -> Self {
+        Self(self.0 | algorithm.bit())
+    }
+
+    pub fn contains(&self, algorithm: HashAlgorithm) -> bool {
+        self.0 & algorithm.bit() != 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = HashAlgorithm> + '_ {
+        HashAlgorithm::ALL.into_iter().filter(|algorithm| self.contains(*algorithm))
+    }
+
+    /// Fail if any requested algorithm was not compiled into this build
+    pub fn ensure_available(&self) -> Result<()> {
+        let missing: Vec<&str> = self.iter()
+            .filter(|algorithm| !algorithm.is_available())
+            .map(|algorithm| algorithm.name())
+            .collect();
+        if !missing.is_empty() {
+            bail!("Algoritmos de hash indisponíveis nesta build: {} (compile com --features checksums)", missing.join(", "));
+        }
+        Ok(())
+    }
+}
+
+impl Default for HashAlgorithms {
+    fn default() -> Self {
+        Self::dat_default()
+    }
+}
+
+impl FromIterator<HashAlgorithm> for HashAlgorithms {
+    fn from_iter<I: IntoIterator<Item = HashAlgorithm>>(iter: I) -> Self {
+        iter.into_iter().fold(Self::empty(), Self::with)
+    }
+}
+
+/// Files at least this large are read in `HashOptions::large_read_size` chunks
+const LARGE_FILE_THRESHOLD: u64 = 64 * 1024 * 1024;
+const LARGE_READ_SIZE: usize = 8 * 1024 * 1024;
+
+/// Which digests the engine produces and how it reads the file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HashOptions {
+    pub algorithms: HashAlgorithms,
+    pub large_file_threshold: u64,
+    pub large_read_size: usize,
+}
+
+impl Default for HashOptions {
+    fn default() -> Self {
+        Self::new(HashAlgorithms::default())
+    }
+}
+
+impl HashOptions {
+    pub fn new(algorithms: HashAlgorithms) -> Self {
+        Self {
+            algorithms,
+            large_file_threshold: LARGE_FILE_THRESHOLD,
+            large_read_size: LARGE_READ_SIZE,
+        }
+    }
+
+    /// Read size for a file, kept word aligned for N64 normalization and
+    /// never below the header skipper probe
+    fn read_size(&self, file_size: u64) -> usize {
+        if file_size >= self.large_file_threshold {
+            self.large_read_size.max(BUFFER_SIZE) / 4 * 4
+        } else {
+            BUFFER_SIZE
+        }
+    }
+}
+
+/// Digests of one stream produced by `MultiHasher`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileHashes {
+    pub crc32: Option<u32>,
+    pub digests: Digests,
+}
+
+/// Feeds one byte stream to every selected algorithm. Algorithms missing from
+/// this build are skipped; check `HashAlgorithms::ensure_available` first.
+pub struct MultiHasher {
+    size: u64,
+    crc32: Option<crc32fast::Hasher>,
+    #[cfg(any(feature = "checksums", feature = "dat-download"))]
+    md5: Option<md5::Context>,
+    #[cfg(feature = "checksums")]
+    sha1: Option<sha1::Sha1>,
+    #[cfg(any(feature = "checksums", feature = "dat-download"))]
+    sha256: Option<sha2::Sha256>,
+}
+
+impl MultiHasher {
+    pub fn new(algorithms: HashAlgorithms) -> Self {
+        Self {
+            size: 0,
+            crc32: algorithms.contains(HashAlgorithm::Crc32).then(crc32fast::Hasher::new),
+            #[cfg(any(feature = "checksums", feature = "dat-download"))]
+            md5: algorithms.contains(HashAlgorithm::Md5).then(md5::Context::new),
+            #[cfg(feature = "checksums")]
+            sha1: algorithms.contains(HashAlgorithm::Sha1).then(sha1::Sha1::default),
+            #[cfg(any(feature = "checksums", feature = "dat-download"))]
+            sha256: algorithms.contains(HashAlgorithm::Sha256).then(sha2::Sha256::default),
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.size += data.len() as u64;
+        if let Some(crc) = &mut self.crc32 {
+            crc.update(data);
+        }
+        #[cfg(any(feature = "checksums", feature = "dat-download"))]
+        {
+            use sha2::Digest;
+            if let Some(md5) = &mut self.md5 {
+                md5.consume(data);
+            }
+            #[cfg(feature = "checksums")]
+            if let Some(sha1) = &mut self.sha1 {
+                sha1.update(data);
+            }
+            if let Some(sha256) = &mut self.sha256 {
+                sha256.update(data);
+            }
+        }
+    }
+
+    pub fn finish(self) -> FileHashes {
+        #[allow(unused_mut)]
+        let mut digests = Digests {
+            size: self.size,
+            ..Default::default()
+        };
+        #[cfg(any(feature = "checksums", feature = "dat-download"))]
+        {
+            use sha2::Digest;
+            digests.md5 = self.md5.map(|md5| format!("{:x}", md5.finalize()));
+            #[cfg(feature = "checksums")]
+            {
+                digests.sha1 = self.sha1.map(|sha1| format!("{:x}", sha1.finalize()));
+            }
+            digests.sha256 = self.sha256.map(|sha256| format!("{:x}", sha256.finalize()));
+        }
+
+        FileHashes {
+            crc32: self.crc32.map(|crc| crc.finalize()),
+            digests,
+        }
+    }
+}
+
+fn file_extension(file_path: &Path) -> String {
+    file_path.extension()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_lowercase()
+}
+
+/// Hashes of the raw stream, plus the headerless stream and its skipper name
+/// when a header skipper applied
+type StreamHashes = (FileHashes, Option<(String, FileHashes)>);
+
+/// Single read of `file_path` feeding the raw and headerless hashers
+fn hash_stream(
+    file_path: &Path,
+    skippers: &SkipperSet,
+    options: &HashOptions,
+    progress: Option<&mut dyn FnMut(u64, u64)>,
+) -> Result<StreamHashes> {
+    let mut file = File::open(file_path)
         .with_context(|| format!("Falha ao abrir arquivo: {}", file_path.display()))?;
+    let file_size = file.metadata()
+        .with_context(|| format!("Falha ao ler metadados de {}", file_path.display()))?
+        .len();
 
-    let mut reader = BufReader::with_capacity(BUFFER_SIZE, file);
-    let mut crc = crc32fast::Hasher::new();
-    let mut buffer = vec![0u8; BUFFER_SIZE];
+    hash_reader(&mut file, &file_extension(file_path), file_size, skippers, options, progress)
+        .with_context(|| format!("Falha ao ler arquivo: {}", file_path.display()))
+}
 
-    loop {
-        let bytes_read = reader.read(&mut buffer)
-            .with_context(|| format!("Falha ao ler arquivo: {}", file_path.display()))?;
-        
-        if bytes_read == 0 {
-            break;
+/// Hash `size` bytes from `reader`. N64 images are normalized to big-endian
+/// order and `extension` selects the header skippers to probe.
+fn hash_reader<R: Read + ?Sized>(
+    reader: &mut R,
+    extension: &str,
+    size: u64,
+    skippers: &SkipperSet,
+    options: &HashOptions,
+    mut progress: Option<&mut dyn FnMut(u64, u64)>,
+) -> std::io::Result<StreamHashes> {
+    // Reads go straight into the chunk buffer, so no BufReader in between
+    let mut buffer = vec![0u8; options.read_size(size)];
+
+    // Full reads keep the probe complete and N64 chunks word aligned
+    let mut chunk_len = read_full(reader, &mut buffer)?;
+
+    let byte_order = if is_n64_extension(extension) {
+        N64ByteOrder::detect(&buffer[..chunk_len])
+    } else {
+        None
+    };
+
+    let found = if skippers.handles_extension(extension) {
+        skippers.find(extension, &buffer[..chunk_len.min(SKIPPER_PROBE_SIZE)], size)
+    } else {
+        None
+    };
+    let (skipper, range) = match found {
+        Some((skipper, rule)) => (
+            Some(skipper.name.clone()),
+            Some((rule.start_offset, rule.end_offset.unwrap_or(size).min(size))),
+        ),
+        None => (None, None),
+    };
+
+    let mut raw = MultiHasher::new(options.algorithms);
+    let mut headerless = range.map(|_| MultiHasher::new(options.algorithms));
+    let mut position = 0u64;
+
+    while chunk_len > 0 {
+        let chunk = &mut buffer[..chunk_len];
+        if let Some(order) = byte_order {
+            order.normalize(chunk);
         }
-        
-        crc.update(&buffer[..bytes_read]);
+        raw.update(chunk);
+
+        if let (Some((start, end)), Some(headerless)) = (range, headerless.as_mut()) {
+            let chunk_start = position;
+            let chunk_end = position + chunk_len as u64;
+            let from = start.max(chunk_start);
+            let to = end.min(chunk_end);
+            if from < to {
+                headerless.update(&chunk[(from - chunk_start) as usize..(to - chunk_start) as usize]);
+            }
+        }
+        position += chunk_len as u64;
+
+        if let Some(progress) = progress.as_mut() {
+            progress(position, size);
+        }
+
+        chunk_len = read_full(reader, &mut buffer)?;
     }
 
-    Ok(crc.finalize())
+    Ok((raw.finish(), skipper.zip(headerless.map(MultiHasher::finish))))
+}
+
+/// Hash a file with every algorithm in `options` in a single pass, calling
+/// `progress(bytes_done, total)` after each read
+pub fn hash_file(
+    file_path: &Path,
+    options: &HashOptions,
+    progress: Option<&mut dyn FnMut(u64, u64)>,
+) -> Result<FileHashes> {
+    options.algorithms.ensure_available()?;
+    let (hashes, _) = hash_stream(file_path, &SkipperSet::new(), options, progress)?;
+    Ok(hashes)
+}
+
+/// CRC32 of a file. N64 images are hashed in big-endian order regardless of how they are stored.
+pub fn calculate_crc32(file_path: &Path) -> Result<u32> {
+    let hashes = hash_file(file_path, &HashOptions::new(HashAlgorithms::crc32_only()), None)?;
+    Ok(hashes.crc32.unwrap_or_default())
+}
+
+/// Compute the raw CRC32 and, when a header skipper matches, the headerless CRC32 in one pass.
+/// Sizes are always recorded; MD5/SHA1 are added with the `checksums` feature.
+pub fn calculate_crc32_variants(file_path: &Path, skippers: &SkipperSet) -> Result<CrcVariants> {
+    calculate_crc32_variants_with(file_path, skippers, &HashOptions::default(), None)
+}
+
+/// `calculate_crc32_variants` with a chosen digest set and progress reporting.
+/// CRC32 is always computed since DAT lookups key on it.
+pub fn calculate_crc32_variants_with(
+    file_path: &Path,
+    skippers: &SkipperSet,
+    options: &HashOptions,
+    progress: Option<&mut dyn FnMut(u64, u64)>,
+) -> Result<CrcVariants> {
+    let options = variant_options(options)?;
+    hash_stream(file_path, skippers, &options, progress).map(into_variants)
+}
+
+/// `calculate_crc32_variants_with` over an already open stream, such as an
+/// archive entry. `extension` and `size` describe the data behind `reader`.
+pub fn calculate_crc32_variants_from_reader<R: Read + ?Sized>(
+    reader: &mut R,
+    extension: &str,
+    size: u64,
+    skippers: &SkipperSet,
+    options: &HashOptions,
+) -> Result<CrcVariants> {
+    let options = variant_options(options)?;
+    let hashes = hash_reader(reader, extension, size, skippers, &options, None)
+        .context("Falha ao ler fluxo de dados")?;
+    Ok(into_variants(hashes))
+}
+
+fn variant_options(options: &HashOptions) -> Result<HashOptions> {
+    options.algorithms.ensure_available()?;
+    Ok(HashOptions {
+        algorithms: options.algorithms.with(HashAlgorithm::Crc32),
+        ..*options
+    })
+}
+
+fn into_variants((raw, headerless): StreamHashes) -> CrcVariants {
+    let (skipper, headerless) = headerless.unzip();
+    CrcVariants {
+        raw: raw.crc32.unwrap_or_default(),
+        headerless: headerless.as_ref().and_then(|hashes| hashes.crc32),
+        skipper,
+        raw_digests: Some(raw.digests),
+        headerless_digests: headerless.map(|hashes| hashes.digests),
+    }
 }
 
 pub fn calculate_crc32_from_bytes(data: &[u8]) -> u32 {
@@ -80,6 +542,118 @@ mod tests {
         assert_eq!(crc, 3964322768);
     }
 
+    #[test]
+    fn test_cached_variants_cover_requested_algorithms() {
+        let mut variants = CrcVariants {
+            raw: 1,
+            headerless: None,
+            skipper: None,
+            raw_digests: Some(Digests { size: 4, md5: Some("abc".into()), sha1: None, sha256: None }),
+            headerless_digests: None,
+        };
+        assert!(variants.covers(HashAlgorithms::crc32_only().with(HashAlgorithm::Md5)));
+        assert!(!variants.covers(HashAlgorithms::crc32_only().with(HashAlgorithm::Sha1)));
+
+        variants.raw_digests = None;
+        assert!(variants.covers(HashAlgorithms::crc32_only()));
+        assert!(!variants.covers(HashAlgorithms::crc32_only().with(HashAlgorithm::Md5)));
+    }
+
+    #[test]
+    fn test_headerless_crc_variants() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("game.nes");
+
+        let body: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        let mut data = b"NES\x1A".to_vec();
+        data.resize(16, 0);
+        data.extend_from_slice(&body);
+        std::fs::write(&path, &data).unwrap();
+
+        let variants = calculate_crc32_variants(&path, &SkipperSet::builtin()).unwrap();
+        assert_eq!(variants.raw, calculate_crc32_from_bytes(&data));
+        assert_eq!(variants.headerless, Some(calculate_crc32_from_bytes(&body)));
+        assert_eq!(variants.skipper.as_deref(), Some("No-Intro_NES.xml"));
+
+        // Without a header only the raw CRC is produced
+        std::fs::write(&path, &body).unwrap();
+        let variants = calculate_crc32_variants(&path, &SkipperSet::builtin()).unwrap();
+        assert_eq!(variants.raw, calculate_crc32_from_bytes(&body));
+        assert_eq!(variants.headerless, None);
+        assert_eq!(variants.raw_digests.map(|d| d.size), Some(body.len() as u64));
+    }
+
+    #[cfg(feature = "checksums")]
+    #[test]
+    fn test_digests_in_same_pass() {
+        let temp_file = NamedTempFile::new().unwrap();
+        temp_file.as_file().write_all(b"Hello, World!").unwrap();
+
+        let variants = calculate_crc32_variants(temp_file.path(), &SkipperSet::builtin()).unwrap();
+        let digests = variants.raw_digests.unwrap();
+        assert_eq!(variants.raw, 3964322768);
+        assert_eq!(digests.size, 13);
+        assert_eq!(digests.md5.as_deref(), Some("65a8e27d8879283831b664bd8b7f0ad4"));
+        assert_eq!(digests.sha1.as_deref(), Some("0a0a9f2a6772942557ab5355d76af442f8f65e01"));
+    }
+
+    #[test]
+    fn test_large_reads_report_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("disc.iso");
+        let data: Vec<u8> = (0..600_000u32).map(|i| (i % 241) as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+
+        let options = HashOptions {
+            large_file_threshold: 0,
+            large_read_size: 256 * 1024,
+            ..HashOptions::new(HashAlgorithms::crc32_only())
+        };
+        let mut reports = Vec::new();
+        let hashes = hash_file(&path, &options, Some(&mut |done, total| reports.push((done, total)))).unwrap();
+
+        assert_eq!(hashes.crc32, Some(calculate_crc32_from_bytes(&data)));
+        assert_eq!(hashes.digests.size, data.len() as u64);
+        assert_eq!(reports.len(), 3);
+        assert_eq!(reports.last(), Some(&(data.len() as u64, data.len() as u64)));
+    }
+
+    #[cfg(feature = "checksums")]
+    #[test]
+    fn test_multi_hasher_selected_algorithms() {
+        let algorithms: HashAlgorithms = [HashAlgorithm::Md5, HashAlgorithm::Sha256].into_iter().collect();
+        let mut hasher = MultiHasher::new(algorithms);
+        hasher.update(b"Hello, ");
+        hasher.update(b"World!");
+        let hashes = hasher.finish();
+
+        assert_eq!(hashes.crc32, None);
+        assert_eq!(hashes.digests.sha1, None);
+        assert_eq!(hashes.digests.md5.as_deref(), Some("65a8e27d8879283831b664bd8b7f0ad4"));
+        assert_eq!(
+            hashes.digests.sha256.as_deref(),
+            Some("dffd6021bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f")
+        );
+    }
+
+    #[test]
+    fn test_n64_crc_uses_big_endian_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut z64 = vec![0x80, 0x37, 0x12, 0x40];
+        z64.extend((0..100_000u32).map(|i| (i % 249) as u8));
+        let expected = calculate_crc32_from_bytes(&z64);
+
+        let v64: Vec<u8> = z64.chunks(2).flat_map(|half| [half[1], half[0]]).collect();
+        let n64: Vec<u8> = z64.chunks(4).flat_map(|word| word.iter().rev().copied().collect::<Vec<_>>()).collect();
+
+        for (name, data) in [("game.z64", &z64), ("game.v64", &v64), ("game.n64", &n64)] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, data).unwrap();
+            assert_eq!(calculate_crc32(&path).unwrap(), expected, "{}", name);
+            assert_eq!(calculate_crc32_variants(&path, &SkipperSet::builtin()).unwrap().raw, expected, "{}", name);
+        }
+    }
+
     #[test]
     fn test_empty_file() {
         let temp_file = NamedTempFile::new().unwrap();
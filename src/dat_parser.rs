@@ -1,8 +1,11 @@
 use anyhow::Result;
 use log::{debug, warn};
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
 use std::collections::HashMap;
 use std::path::Path;
 
+use crate::crc32::{CrcVariant, Digests, HashCandidate};
 use crate::error::DatError;
 use crate::mame_xml::MameXmlDatabase;
 
@@ -13,27 +16,126 @@ pub struct DatEntry {
     pub size: Option<u64>,
     pub md5: Option<String>,
     pub sha1: Option<String>,
+    /// File name of the ROM inside the game set (e.g. "Game (USA).sfc")
+    pub rom_name: Option<String>,
+    /// Dump status as reported by the DAT ("baddump", "nodump", "verified")
+    pub status: Option<String>,
+    pub clone_of: Option<String>,
+    pub rom_of: Option<String>,
+}
+
+impl DatEntry {
+    /// How strongly `digests` confirm this entry, or `None` when a known size or hash contradicts it
+    pub fn confirm(&self, digests: Option<&Digests>) -> Option<MatchedBy> {
+        let Some(digests) = digests else {
+            return Some(MatchedBy::Crc32);
+        };
+
+        let mut matched_by = MatchedBy::Crc32;
+        if let Some(size) = self.size {
+            if size != digests.size {
+                return None;
+            }
+            matched_by = MatchedBy::Size;
+        }
+        if let (Some(expected), Some(actual)) = (&self.md5, &digests.md5) {
+            if !expected.eq_ignore_ascii_case(actual) {
+                return None;
+            }
+            matched_by = MatchedBy::Md5;
+        }
+        if let (Some(expected), Some(actual)) = (&self.sha1, &digests.sha1) {
+            if !expected.eq_ignore_ascii_case(actual) {
+                return None;
+            }
+            matched_by = MatchedBy::Sha1;
+        }
+        Some(matched_by)
+    }
+}
+
+/// Strongest evidence that confirmed a DAT match, weakest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MatchedBy {
+    Crc32,
+    /// CRC32 plus the size recorded in the DAT
+    Size,
+    Md5,
+    Sha1,
+}
+
+impl MatchedBy {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            MatchedBy::Crc32 => "CRC32",
+            MatchedBy::Size => "CRC32+size",
+            MatchedBy::Md5 => "MD5",
+            MatchedBy::Sha1 => "SHA1",
+        }
+    }
+}
+
+/// DAT entry chosen for a file and how it was confirmed
+#[derive(Debug, Clone, Copy)]
+pub struct DatMatch<'a> {
+    pub entry: &'a DatEntry,
+    pub variant: CrcVariant,
+    pub matched_by: MatchedBy,
+    /// Other entries with the same CRC were confirmed just as strongly
+    pub ambiguous: bool,
+}
+
+/// Metadata from the `<header>` block of a Logiqx XML DAT
+#[derive(Debug, Clone, Default)]
+pub struct DatHeader {
+    pub name: String,
+    pub description: Option<String>,
+    pub version: Option<String>,
+    pub author: Option<String>,
+    pub homepage: Option<String>,
+    /// Header skipper definition referenced by `<clrmamepro header="..."/>`
+    pub header_skipper: Option<String>,
+}
+
+/// Game being assembled while streaming a Logiqx XML DAT
+#[derive(Debug, Default)]
+struct XmlGame {
+    name: String,
+    description: String,
+    clone_of: Option<String>,
+    rom_of: Option<String>,
+    roms: Vec<XmlRom>,
+}
+
+#[derive(Debug, Default)]
+struct XmlRom {
+    name: Option<String>,
+    size: Option<u64>,
+    crc: Option<String>,
+    md5: Option<String>,
+    sha1: Option<String>,
+    status: Option<String>,
 }
 
 #[derive(Debug, Default)]
 pub struct DatCollection {
-    // CRC32 -> ROM name mapping
-    crc_to_name: HashMap<u32, String>,
     // System -> DatEntry mapping
     pub entries: HashMap<String, Vec<DatEntry>>,
-    // All entries indexed by CRC32 for fast lookup
-    crc_to_entry: HashMap<u32, DatEntry>,
+    // All entries indexed by CRC32 for fast lookup; colliding CRCs keep every entry
+    crc_to_entry: HashMap<u32, Vec<DatEntry>>,
     // MAME XML database for MAME 2003-Plus fallback
     mame_xml_db: Option<MameXmlDatabase>,
+    // System -> header metadata for XML DATs
+    headers: HashMap<String, DatHeader>,
 }
 
 impl DatCollection {
     pub fn new() -> Self {
         let mut collection = Self {
-            crc_to_name: HashMap::new(),
             entries: HashMap::new(),
             crc_to_entry: HashMap::new(),
             mame_xml_db: None,
+            headers: HashMap::new(),
         };
         
         // Always try to load MAME XML database for MAME fallback
@@ -80,8 +182,51 @@ impl DatCollection {
         self.load_dat_file(path.as_ref())
     }
 
+    /// First entry with this CRC32, in DAT order. Clones and shared BIOS
+    /// files often carry the same CRC, so matching code goes through
+    /// `find_match`, which narrows them by size and digests.
     pub fn find_by_crc32(&self, crc32: u32) -> Option<&DatEntry> {
-        self.crc_to_entry.get(&crc32)
+        self.find_all_by_crc32(crc32).first()
+    }
+
+    /// Every entry sharing this CRC32, in DAT order
+    pub fn find_all_by_crc32(&self, crc32: u32) -> &[DatEntry] {
+        self.crc_to_entry.get(&crc32).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Try each candidate in order and narrow entries sharing its CRC by size,
+    /// then MD5/SHA1 when both the file and the DAT carry them
+    pub fn find_match(&self, candidates: &[HashCandidate]) -> Option<DatMatch<'_>> {
+        candidates.iter().find_map(|candidate| {
+            let mut best: Option<(&DatEntry, MatchedBy)> = None;
+            let mut ambiguous = false;
+
+            for entry in self.find_all_by_crc32(candidate.crc32) {
+                let Some(matched_by) = entry.confirm(candidate.digests.as_ref()) else {
+                    continue;
+                };
+                match best {
+                    Some((_, best_by)) if matched_by < best_by => {}
+                    Some((_, best_by)) if matched_by == best_by => ambiguous = true,
+                    _ => {
+                        best = Some((entry, matched_by));
+                        ambiguous = false;
+                    }
+                }
+            }
+
+            best.map(|(entry, matched_by)| {
+                if ambiguous {
+                    debug!("CRC32 {:08X} ambíguo, usando {} ({})", candidate.crc32, entry.name, matched_by.describe());
+                }
+                DatMatch {
+                    entry,
+                    variant: candidate.variant,
+                    matched_by,
+                    ambiguous,
+                }
+            })
+        })
     }
 
     pub fn load_directory(dir: &Path) -> Result<Self> {
@@ -94,14 +239,14 @@ impl DatCollection {
                 let entry = entry?;
                 let path = entry.path();
                 
-                if path.is_file() && path.extension().map_or(false, |ext| ext == "dat") {
+                if path.is_file() && path.extension().map_or(false, |ext| ext == "dat" || ext == "xml") {
                     if let Err(e) = collection.load_dat_file(&path) {
                         warn!("Falha ao carregar DAT {}: {}", path.display(), e);
                     }
                 }
             }
 
-            debug!("Carregados {} entradas DAT de {}", collection.crc_to_name.len(), dir.display());
+            debug!("Carregados {} entradas DAT de {}", collection.total_entries(), dir.display());
         }
 
         // Always try to load MAME XML database as fallback
@@ -118,15 +263,19 @@ impl DatCollection {
                 path: path.to_path_buf(),
                 source: e,
             })?;
-
-        // Detect DAT format and parse accordingly
-        if content.contains("clrmamepro") || content.contains("game (") {
-            self.parse_clrmamepro_dat(&content, path)?;
-        } else if content.contains("<?xml") {
-            self.parse_xml_dat(&content, path)?;
+        // DATs saved on Windows often start with a UTF-8 BOM
+        let content = content.trim_start_matches('\u{feff}');
+
+        // Detect DAT format and parse accordingly. XML is checked first because
+        // Logiqx headers may contain a <clrmamepro header="..."/> element.
+        let trimmed = content.trim_start();
+        if trimmed.starts_with("<?xml") || trimmed.starts_with("<datafile") || trimmed.starts_with("<!DOCTYPE") {
+            self.parse_xml_dat(content, path)?;
+        } else if content.contains("clrmamepro") || content.contains("game (") {
+            self.parse_clrmamepro_dat(content, path)?;
         } else {
             // Try simple format: CRC32=Name
-            self.parse_simple_dat(&content, path)?;
+            self.parse_simple_dat(content, path)?;
         }
 
         Ok(())
@@ -190,8 +339,7 @@ impl DatCollection {
                 let game_entries = self.parse_clrmamepro_game_block(&game_block, "")?;
                 for entry in game_entries {
                     debug!("Parsed MAME game: {} (CRC: {:08X})", entry.name, entry.crc32);
-                    self.crc_to_name.insert(entry.crc32, entry.name.clone());
-                    self.crc_to_entry.insert(entry.crc32, entry.clone());
+                    self.crc_to_entry.entry(entry.crc32).or_default().push(entry.clone());
                     entries.push(entry);
                 }
             } else {
@@ -339,6 +487,10 @@ impl DatCollection {
                     size: size_val,
                     md5: None,
                     sha1: None,
+                    rom_name: None,
+                    status: None,
+                    clone_of: None,
+                    rom_of: None,
                 });
             }
         }
@@ -346,13 +498,172 @@ impl DatCollection {
         None
     }
 
-    fn parse_xml_dat(&mut self, _content: &str, path: &Path) -> Result<()> {
-        // TODO: Implement XML DAT parsing for No-Intro/Redump XML formats
-        // This requires a proper XML parser
-        warn!("XML DAT parsing not yet implemented: {}", path.display());
+    fn parse_xml_dat(&mut self, content: &str, path: &Path) -> Result<()> {
+        // Logiqx XML format used by No-Intro and Redump:
+        // <datafile>
+        //     <header>
+        //         <name>Nintendo - Super Nintendo Entertainment System</name>
+        //         <clrmamepro header="No-Intro_SNES.xml"/>
+        //     </header>
+        //     <game name="Game (USA)" cloneof="Game (Japan)">
+        //         <description>Game (USA)</description>
+        //         <rom name="Game (USA).sfc" size="524288" crc="b19ed489" md5="..." sha1="..."/>
+        //     </game>
+        // </datafile>
+        let mut reader = Reader::from_str(content);
+        reader.config_mut().trim_text(true);
+
+        let mut buf = Vec::new();
+        let mut header = DatHeader::default();
+        let mut in_header = false;
+        let mut current_game: Option<XmlGame> = None;
+        let mut current_text = String::new();
+        let mut entries = Vec::new();
+
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(ref e)) => match e.name().as_ref() {
+                    b"header" => in_header = true,
+                    b"game" | b"machine" => current_game = Some(Self::parse_xml_game(e)),
+                    b"rom" => {
+                        if let Some(ref mut game) = current_game {
+                            game.roms.push(Self::parse_xml_rom(e));
+                        }
+                    }
+                    _ => {}
+                },
+                Ok(Event::Empty(ref e)) => match e.name().as_ref() {
+                    b"rom" => {
+                        if let Some(ref mut game) = current_game {
+                            game.roms.push(Self::parse_xml_rom(e));
+                        }
+                    }
+                    b"clrmamepro" if in_header => {
+                        header.header_skipper = Self::xml_attribute(e, b"header");
+                    }
+                    _ => {}
+                },
+                Ok(Event::Text(e)) => {
+                    current_text = e.unescape()
+                        .map(|text| text.to_string())
+                        .unwrap_or_default();
+                }
+                Ok(Event::End(ref e)) => {
+                    match e.name().as_ref() {
+                        b"header" => in_header = false,
+                        b"name" if in_header => header.name = current_text.clone(),
+                        b"description" if in_header => header.description = Some(current_text.clone()),
+                        b"version" if in_header => header.version = Some(current_text.clone()),
+                        b"author" if in_header => header.author = Some(current_text.clone()),
+                        b"homepage" if in_header => header.homepage = Some(current_text.clone()),
+                        b"description" => {
+                            if let Some(ref mut game) = current_game {
+                                game.description = current_text.clone();
+                            }
+                        }
+                        b"game" | b"machine" => {
+                            if let Some(game) = current_game.take() {
+                                entries.extend(self.xml_game_to_entries(game));
+                            }
+                        }
+                        _ => {}
+                    }
+                    current_text.clear();
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => {
+                    return Err(DatError::InvalidFormat {
+                        path: path.to_path_buf(),
+                        message: format!("XML inválido na posição {}: {}", reader.buffer_position(), e),
+                    }.into());
+                }
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        let system_name = if header.name.is_empty() {
+            self.extract_system_name_from_path(path)
+        } else {
+            header.name.clone()
+        };
+
+        for entry in &entries {
+            self.crc_to_entry.entry(entry.crc32).or_default().push(entry.clone());
+        }
+
+        debug!("Loaded {} XML DAT entries for system '{}'", entries.len(), system_name);
+        self.headers.insert(system_name.clone(), header);
+
+        if !entries.is_empty() {
+            self.entries.entry(system_name).or_default().extend(entries);
+        }
+
         Ok(())
     }
 
+    fn parse_xml_game(element: &BytesStart) -> XmlGame {
+        XmlGame {
+            name: Self::xml_attribute(element, b"name").unwrap_or_default(),
+            clone_of: Self::xml_attribute(element, b"cloneof"),
+            rom_of: Self::xml_attribute(element, b"romof"),
+            ..Default::default()
+        }
+    }
+
+    fn parse_xml_rom(element: &BytesStart) -> XmlRom {
+        XmlRom {
+            name: Self::xml_attribute(element, b"name"),
+            size: Self::xml_attribute(element, b"size").and_then(|size| size.parse().ok()),
+            crc: Self::xml_attribute(element, b"crc"),
+            md5: Self::xml_attribute(element, b"md5").map(|h| h.to_lowercase()),
+            sha1: Self::xml_attribute(element, b"sha1").map(|h| h.to_lowercase()),
+            status: Self::xml_attribute(element, b"status"),
+        }
+    }
+
+    fn xml_attribute(element: &BytesStart, key: &[u8]) -> Option<String> {
+        element.attributes()
+            .filter_map(|attr| attr.ok())
+            .find(|attr| attr.key.as_ref() == key)
+            .and_then(|attr| attr.unescape_value().ok().map(|value| value.to_string()))
+    }
+
+    fn xml_game_to_entries(&self, game: XmlGame) -> Vec<DatEntry> {
+        let display_name = if !game.description.is_empty() {
+            game.description.clone()
+        } else {
+            game.name.clone()
+        };
+
+        game.roms
+            .into_iter()
+            .filter_map(|rom| {
+                // ROMs marked "nodump" have no usable hashes
+                let crc_str = rom.crc?;
+                let crc32 = match u32::from_str_radix(crc_str.trim_start_matches("0x"), 16) {
+                    Ok(crc) => crc,
+                    Err(_) => {
+                        warn!("CRC32 inválido no jogo '{}': {}", game.name, crc_str);
+                        return None;
+                    }
+                };
+
+                Some(DatEntry {
+                    crc32,
+                    name: display_name.clone(),
+                    size: rom.size,
+                    md5: rom.md5,
+                    sha1: rom.sha1,
+                    rom_name: rom.name,
+                    status: rom.status,
+                    clone_of: game.clone_of.clone(),
+                    rom_of: game.rom_of.clone(),
+                })
+            })
+            .collect()
+    }
+
     fn parse_simple_dat(&mut self, content: &str, path: &Path) -> Result<()> {
         let system_name = self.extract_system_name_from_path(path);
         let mut entries = Vec::new();
@@ -372,17 +683,19 @@ impl DatCollection {
                 
                 match u32::from_str_radix(crc_str.trim(), 16) {
                     Ok(crc32) => {
-                        let name = name.trim().to_string();
                         let entry = DatEntry {
                             crc32,
-                            name: name.clone(),
+                            name: name.trim().to_string(),
                             size: None,
                             md5: None,
                             sha1: None,
+                            rom_name: None,
+                            status: None,
+                            clone_of: None,
+                            rom_of: None,
                         };
                         
-                        self.crc_to_name.insert(crc32, name);
-                        self.crc_to_entry.insert(crc32, entry.clone());
+                        self.crc_to_entry.entry(crc32).or_default().push(entry.clone());
                         entries.push(entry);
                     }
                     Err(_) => {
@@ -409,9 +722,11 @@ impl DatCollection {
 
     pub fn get_name_by_crc(&self, crc32: u32) -> Option<String> {
         // Primary DAT lookup - this is now the first step for MAME systems
-        // The returned name will be used to lookup description in MAME XML
-        if let Some(name) = self.crc_to_name.get(&crc32) {
-            return Some(name.clone());
+        // The returned name will be used to lookup description in MAME XML.
+        // When several entries share the CRC the parent set wins, then DAT order.
+        let candidates = self.find_all_by_crc32(crc32);
+        if let Some(entry) = candidates.iter().find(|entry| entry.clone_of.is_none()).or(candidates.first()) {
+            return Some(entry.name.clone());
         }
 
         // No luck with DAT lookup
@@ -458,12 +773,29 @@ impl DatCollection {
         None
     }
 
+    /// Get the header of an XML DAT loaded for a system
+    pub fn get_header(&self, system: &str) -> Option<&DatHeader> {
+        self.headers.get(system)
+    }
+
+    /// Header skippers named by the loaded XML DATs, without duplicates
+    pub fn header_skippers(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.headers
+            .values()
+            .filter_map(|header| header.header_skipper.clone())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
     pub fn get_system_entries(&self, system: &str) -> Option<&[DatEntry]> {
         self.entries.get(system).map(|v| v.as_slice())
     }
 
+    /// Every loaded entry, counting each one that shares a CRC32 with another
     pub fn total_entries(&self) -> usize {
-        self.crc_to_name.len()
+        self.crc_to_entry.values().map(Vec::len).sum()
     }
 
     pub fn systems_count(&self) -> usize {
@@ -565,6 +897,10 @@ impl DatCollection {
                     size: size_val,
                     md5: None,
                     sha1: None,
+                    rom_name: if rom_name.is_empty() { None } else { Some(rom_name) },
+                    status: None,
+                    clone_of: None,
+                    rom_of: None,
                 };
                 
                 return Some((entry, i));
@@ -642,6 +978,27 @@ INVALID_CRC=Invalid Game
         );
     }
 
+    #[test]
+    fn test_shared_crc_name_prefers_parent() {
+        let dat = r#"<?xml version="1.0"?>
+<datafile>
+    <header><name>Arcade</name></header>
+    <game name="puckmanb" cloneof="puckman"><rom name="pm1.6e" size="4096" crc="c1e6ab10"/></game>
+    <game name="puckman"><rom name="pm1.6e" size="4096" crc="c1e6ab10"/></game>
+    <game name="pacmanf" cloneof="puckman"><rom name="pm1.6e" size="4096" crc="c1e6ab10"/></game>
+</datafile>"#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(dat.as_bytes()).unwrap();
+
+        let mut collection = DatCollection::new();
+        collection.load_dat_file(temp_file.path()).unwrap();
+
+        // Entries sharing a CRC are all counted
+        assert_eq!(collection.total_entries(), 3);
+        assert_eq!(collection.get_name_by_crc(0xc1e6ab10).unwrap(), "puckman");
+    }
+
     #[test]
     fn test_create_simple_dat() {
         let entries = [
@@ -2,7 +2,9 @@ use anyhow::{Result, Context};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::fs;
-use log::{info, debug};
+use std::sync::LazyLock;
+use log::{info, debug, warn};
+use regex::Regex;
 
 use crate::scanner::RomFile;
 use crate::crc32::calculate_crc32;
@@ -145,6 +147,64 @@ impl RomQuality {
     }
 }
 
+/// Disc tag such as "(Disc 2)", "(Disc 2 of 3)", "(Disk B)" or "(CD2)"
+static DISC_TAG: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\s*\((?:disc|disk|cd)\s*([0-9]+|[a-z])(?:\s+of\s+[0-9]+)?\)").unwrap()
+});
+
+/// Disc number of a multi-disc game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiscTag {
+    pub number: u32,
+}
+
+impl DiscTag {
+    /// Parse the disc tag from filename; letters count from A = 1
+    pub fn from_filename(filename: &str) -> Option<Self> {
+        let captures = DISC_TAG.captures(filename)?;
+        let value = captures.get(1)?.as_str();
+
+        let number = match value.parse::<u32>() {
+            Ok(number) => number,
+            Err(_) => {
+                let letter = value.chars().next()?.to_ascii_uppercase();
+                letter as u32 - 'A' as u32 + 1
+            }
+        };
+
+        Some(Self { number })
+    }
+
+    /// Remove the disc tag, keeping every other tag
+    pub fn strip(name: &str) -> String {
+        DISC_TAG.replace_all(name, "").trim().to_string()
+    }
+}
+
+/// Title with extension and every (...) / [...] tag removed, lowercased
+pub fn normalize_title(filename: &str) -> String {
+    let stem = Path::new(filename)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| filename.to_string());
+
+    let mut title = String::new();
+    let mut depth = 0u32;
+    for c in stem.chars() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => title.push(c),
+            _ => {}
+        }
+    }
+
+    title.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
 /// Duplicate group containing ROMs with the same content
 #[derive(Debug)]
 pub struct DuplicateGroup {
@@ -435,11 +495,12 @@ impl RomDeduplicator {
         let mut crc_groups: HashMap<u32, Vec<RomFile>> = HashMap::new();
         
         for rom in roms {
-            let crc32 = match rom.crc32 {
-                Some(crc) => crc,
-                None => {
+            // Headered and headerless copies of a dump share the headerless CRC
+            let crc32 = match (rom.crc32_headerless, rom.crc32) {
+                (Some(crc), _) | (None, Some(crc)) => crc,
+                (None, None) => {
                     // Calculate CRC32 if not available
-                    calculate_crc32(&rom.path)?
+                    calculate_crc32(rom.hash_path())?
                 }
             };
             
@@ -489,21 +550,35 @@ impl RomDeduplicator {
             // Remove duplicates (all except the best one)
             for (i, rom) in group_roms.iter().enumerate() {
                 if i != best_index {
-                    let file_size = if rom.size > 0 {
-                        rom.size
-                    } else {
-                        std::fs::metadata(&rom.path).map(|m| m.len()).unwrap_or(0)
+                    // Removing the file would take the other entries of the archive with it
+                    if rom.archive_entry.is_some() {
+                        warn!("Keeping duplicate stored inside an archive: {}", rom.playlist_path().display());
+                        continue;
+                    }
+
+                    // A disc sheet frees the space of its track files too
+                    let file_size = match rom.files()
+                        .iter()
+                        .filter_map(|file| std::fs::metadata(file).ok())
+                        .map(|m| m.len())
+                        .sum::<u64>()
+                    {
+                        0 => rom.size,
+                        size => size,
                     };
                     
                     if !self.dry_run {
-                        // Backup file if requested
-                        if self.backup {
-                            self.backup_file(&rom.path)?;
+                        // A disc sheet is removed together with its track files
+                        for file in rom.files() {
+                            // Backup file if requested
+                            if self.backup {
+                                self.backup_file(file)?;
+                            }
+
+                            // Remove the duplicate
+                            std::fs::remove_file(file)
+                                .with_context(|| format!("Failed to remove duplicate: {}", file.display()))?;
                         }
-                        
-                        // Remove the duplicate
-                        std::fs::remove_file(&rom.path)
-                            .with_context(|| format!("Failed to remove duplicate: {}", rom.path.display()))?;
                     }
                     
                     report.files_removed += 1;
@@ -708,6 +783,41 @@ mod tests {
         assert_eq!(RomQuality::assess_from_filename("Game [f1].nes"), RomQuality::Good);
     }
 
+    #[test]
+    fn test_disc_tag_parsing() {
+        assert_eq!(DiscTag::from_filename("Final Fantasy VII (USA) (Disc 2).cue").unwrap().number, 2);
+        assert_eq!(DiscTag::from_filename("Game (Europe) (Disk B).chd").unwrap().number, 2);
+        assert_eq!(DiscTag::from_filename("Game (Disc 3 of 4).iso").unwrap().number, 3);
+        assert!(DiscTag::from_filename("Game (USA).cue").is_none());
+
+        assert_eq!(DiscTag::strip("Final Fantasy VII (USA) (Disc 2)"), "Final Fantasy VII (USA)");
+        assert_eq!(normalize_title("Final Fantasy VII (USA) (Disc 2).cue"), "final fantasy vii");
+    }
+
+    #[test]
+    fn test_space_freed_counts_disc_tracks() {
+        let dir = tempfile::tempdir().unwrap();
+        let roms: Vec<RomFile> = ["Game (USA)", "Game (USA) [b]"]
+            .iter()
+            .map(|name| {
+                let cue = dir.path().join(format!("{}.cue", name));
+                let bin = dir.path().join(format!("{}.bin", name));
+                std::fs::write(&cue, format!("FILE \"{}.bin\" BINARY\n  TRACK 01 MODE2/2352\n", name)).unwrap();
+                std::fs::write(&bin, vec![0u8; 4096]).unwrap();
+
+                let mut rom = RomFile::new(cue);
+                rom.tracks = vec![bin];
+                rom.crc32 = Some(0x1234_5678);
+                rom
+            })
+            .collect();
+
+        let report = RomDeduplicator::new().with_dry_run(true).deduplicate(&roms).unwrap();
+        assert_eq!(report.removed_files, vec![dir.path().join("Game (USA) [b].cue")]);
+        let sheet_size = std::fs::metadata(&report.removed_files[0]).unwrap().len();
+        assert_eq!(report.space_freed, sheet_size + 4096);
+    }
+
     #[test]
     fn test_duplicate_group() {
         let group = DuplicateGroup::new(0x12345678);
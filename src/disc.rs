@@ -0,0 +1,770 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::disc_sheet::DiscSheet;
+use crate::rom_header::SystemDetection;
+
+/// Logical sector size of ISO9660 and Sega boot areas
+const SECTOR_SIZE: u64 = 2048;
+
+/// Raw CD sectors start with this 12-byte sync pattern
+const CD_SYNC: [u8; 12] = [0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00];
+
+const GAMECUBE_MAGIC: [u8; 4] = [0xC2, 0x33, 0x9F, 0x3D];
+const WII_MAGIC: [u8; 4] = [0x5D, 0x1C, 0x9E, 0xA3];
+
+const CHD_MAGIC: &[u8; 8] = b"MComprHD";
+
+/// CHD stores every CD frame as a raw 2352-byte sector plus 96 bytes of subcode
+const CHD_FRAME_SIZE: u64 = 2448;
+
+/// chdman pads each track to a multiple of this many frames
+const CHD_TRACK_PADDING: u64 = 4;
+
+/// Extensions of disc images the inspector knows how to open.
+/// CSO/ZSO are left out: their blocks are deflate/LZ4 compressed and the
+/// extension fallback still applies to them.
+pub fn is_inspectable_extension(extension: &str) -> bool {
+    matches!(extension,
+        "iso" | "bin" | "img" | "mdf" | "nrg" |
+        "gcm" | "rvz" | "wia" |
+        "pbp" | "cue" | "gdi" | "chd" | "m3u"
+    )
+}
+
+/// Identify the system and serial/game ID of a disc image.
+/// CUE and GDI sheets are followed to their data track, M3U lists to their first disc.
+pub fn inspect_disc(path: &Path) -> Option<SystemDetection> {
+    let extension = path.extension()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_lowercase();
+
+    let image = match extension.as_str() {
+        "cue" | "gdi" => DiscSheet::load(path).ok()?.data_track()?.file.clone(),
+        "m3u" => {
+            let disc = DiscSheet::load(path).ok()?.data_track()?.file.clone();
+            // An M3U listing another M3U is not followed, so lists can't loop
+            let nested = disc.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("m3u"));
+            return if nested { None } else { inspect_disc(&disc) };
+        }
+        _ => path.to_path_buf(),
+    };
+
+    let mut file = File::open(&image).ok()?;
+    inspect_reader(&mut file)
+}
+
+/// Identify a disc from any seekable image stream
+pub fn inspect_reader<R: Read + Seek>(reader: &mut R) -> Option<SystemDetection> {
+    let mut head = [0u8; 0x80];
+    let read = read_at(reader, 0, &mut head).ok()?;
+    let head = &head[..read];
+
+    if let Some(detection) = inspect_nintendo_optical(head) {
+        return Some(detection);
+    }
+    if head.starts_with(b"\0PBP") {
+        return inspect_pbp(reader);
+    }
+    if head.starts_with(CHD_MAGIC) {
+        return inspect_chd(reader);
+    }
+
+    let mut disc = SectorReader::detect(reader).ok()?;
+
+    // Sega boot sector (IP.BIN) lives in sector 0 of the data track
+    let boot = disc.read_sector(0).ok()?;
+    if let Some(detection) = inspect_sega_boot(&boot) {
+        return Some(detection);
+    }
+
+    inspect_iso9660(&mut disc)
+}
+
+/// Reads 2048-byte logical sectors from cooked (.iso) or raw 2352-byte (.bin) images
+struct SectorReader<'a, R: Read + Seek> {
+    reader: &'a mut R,
+    stride: u64,
+    data_offset: u64,
+}
+
+impl<'a, R: Read + Seek> SectorReader<'a, R> {
+    fn detect(reader: &'a mut R) -> io::Result<Self> {
+        let mut header = [0u8; 16];
+        let read = read_at(reader, 0, &mut header)?;
+
+        let (stride, data_offset) = if read == 16 && header[..12] == CD_SYNC {
+            // Mode 2 (CD-XA) carries an 8-byte subheader before user data
+            match header[15] {
+                2 => (2352, 24),
+                _ => (2352, 16),
+            }
+        } else {
+            (SECTOR_SIZE, 0)
+        };
+
+        Ok(Self { reader, stride, data_offset })
+    }
+
+    /// Sectors at a known position, e.g. the data track inside a CHD
+    fn with_layout(reader: &'a mut R, stride: u64, data_offset: u64) -> Self {
+        Self { reader, stride, data_offset }
+    }
+
+    fn read_sector(&mut self, lba: u64) -> io::Result<Vec<u8>> {
+        let mut sector = vec![0u8; SECTOR_SIZE as usize];
+        self.reader.seek(SeekFrom::Start(lba * self.stride + self.data_offset))?;
+        self.reader.read_exact(&mut sector)?;
+        Ok(sector)
+    }
+
+    fn read_extent(&mut self, lba: u64, length: usize) -> io::Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(length);
+        let sectors = length.div_ceil(SECTOR_SIZE as usize) as u64;
+        for i in 0..sectors {
+            data.extend_from_slice(&self.read_sector(lba + i)?);
+        }
+        data.truncate(length);
+        Ok(data)
+    }
+}
+
+/// Directory record of an ISO9660 directory extent
+struct DirEntry {
+    name: String,
+    lba: u64,
+    size: usize,
+    is_dir: bool,
+}
+
+fn inspect_iso9660<R: Read + Seek>(disc: &mut SectorReader<'_, R>) -> Option<SystemDetection> {
+    let pvd = disc.read_sector(16).ok()?;
+    if pvd[0] != 1 || &pvd[1..6] != b"CD001" {
+        return None;
+    }
+
+    let system_id = ascii_field(&pvd[8..40]);
+    let root = parse_dir_record(&pvd[156..190])?;
+    let entries = read_directory(disc, &root)?;
+
+    if let Some(cnf) = find_entry(&entries, "SYSTEM.CNF") {
+        let data = disc.read_extent(cnf.lba, cnf.size.min(4096)).ok()?;
+        if let Some(detection) = parse_system_cnf(&String::from_utf8_lossy(&data)) {
+            return Some(detection);
+        }
+    }
+
+    if let Some(umd) = find_entry(&entries, "UMD_DATA.BIN") {
+        let data = disc.read_extent(umd.lba, umd.size.min(256)).ok()?;
+        let serial = String::from_utf8_lossy(&data)
+            .split('|')
+            .next()
+            .map(|s| s.trim_matches(char::from(0)).trim().to_string())
+            .filter(|s| !s.is_empty());
+        let detection = SystemDetection::from_header("Sony - PlayStation Portable", "UMD_DATA.BIN");
+        return Some(detection.with_serial(serial));
+    }
+
+    if let Some(psp_game) = find_entry(&entries, "PSP_GAME").filter(|e| e.is_dir) {
+        let game_entries = read_directory(disc, psp_game)?;
+        if let Some(sfo) = find_entry(&game_entries, "PARAM.SFO") {
+            let data = disc.read_extent(sfo.lba, sfo.size.min(64 * 1024)).ok()?;
+            let params = parse_param_sfo(&data);
+            let detection = SystemDetection::from_header("Sony - PlayStation Portable", "PSP_GAME/PARAM.SFO");
+            return Some(detection.with_serial(params.get("DISC_ID").cloned()));
+        }
+    }
+
+    if system_id == "PLAYSTATION" {
+        return Some(SystemDetection::from_header("Sony - PlayStation", "ISO9660 system identifier"));
+    }
+
+    None
+}
+
+fn read_directory<R: Read + Seek>(disc: &mut SectorReader<'_, R>, dir: &DirEntry) -> Option<Vec<DirEntry>> {
+    let data = disc.read_extent(dir.lba, dir.size.min(64 * 1024)).ok()?;
+    let mut entries = Vec::new();
+    let mut offset = 0;
+
+    while offset < data.len() {
+        let len = data[offset] as usize;
+        if len == 0 {
+            // Records never span sectors; skip padding to the next one
+            offset = (offset / SECTOR_SIZE as usize + 1) * SECTOR_SIZE as usize;
+            continue;
+        }
+        if let Some(entry) = data.get(offset..offset + len).and_then(parse_dir_record) {
+            entries.push(entry);
+        }
+        offset += len;
+    }
+
+    Some(entries)
+}
+
+fn parse_dir_record(record: &[u8]) -> Option<DirEntry> {
+    if record.len() < 34 {
+        return None;
+    }
+    let name_len = record[32] as usize;
+    let name = record.get(33..33 + name_len)?;
+
+    Some(DirEntry {
+        name: String::from_utf8_lossy(name).to_string(),
+        lba: u32::from_le_bytes([record[2], record[3], record[4], record[5]]) as u64,
+        size: u32::from_le_bytes([record[10], record[11], record[12], record[13]]) as usize,
+        is_dir: record[25] & 0x02 != 0,
+    })
+}
+
+fn find_entry<'e>(entries: &'e [DirEntry], name: &str) -> Option<&'e DirEntry> {
+    entries.iter().find(|entry| {
+        // Strip the ";1" version suffix
+        let entry_name = entry.name.split(';').next().unwrap_or("");
+        entry_name.eq_ignore_ascii_case(name)
+    })
+}
+
+/// PS2 discs boot through `BOOT2`, PS1 discs through `BOOT`
+fn parse_system_cnf(content: &str) -> Option<SystemDetection> {
+    for line in content.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim().to_uppercase();
+        let serial = boot_path_serial(value.trim());
+
+        match key.as_str() {
+            "BOOT2" => {
+                let detection = SystemDetection::from_header("Sony - PlayStation 2", "SYSTEM.CNF BOOT2");
+                return Some(detection.with_serial(serial));
+            }
+            "BOOT" => {
+                let detection = SystemDetection::from_header("Sony - PlayStation", "SYSTEM.CNF BOOT");
+                return Some(detection.with_serial(serial));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// `cdrom0:\SLUS_203.12;1` -> `SLUS-20312`
+fn boot_path_serial(boot: &str) -> Option<String> {
+    let file = boot.rsplit(['\\', '/', ':']).next()?;
+    let file = file.split(';').next()?.trim();
+    let serial: String = file.replace('_', "-").chars().filter(|c| *c != '.').collect();
+
+    let (prefix, number) = serial.split_once('-')?;
+    if prefix.len() == 4 && !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) {
+        Some(serial.to_uppercase())
+    } else {
+        None
+    }
+}
+
+/// Sega CD, Saturn and Dreamcast identify themselves in the IP.BIN boot sector
+fn inspect_sega_boot(sector: &[u8]) -> Option<SystemDetection> {
+    let hardware_id = sector.get(0..16)?;
+
+    let (system, evidence, serial_range) = if hardware_id.starts_with(b"SEGA SEGASATURN") {
+        ("Sega - Saturn", "Saturn IP.BIN", 0x20..0x2A)
+    } else if hardware_id.starts_with(b"SEGA SEGAKATANA") {
+        ("Sega - Dreamcast", "Dreamcast IP.BIN", 0x40..0x4A)
+    } else if hardware_id.starts_with(b"SEGADISCSYSTEM") || hardware_id.starts_with(b"SEGABOOTDISC") {
+        ("Sega - Mega-CD - Sega CD", "Sega CD IP.BIN", 0x180..0x18E)
+    } else {
+        return None;
+    };
+
+    let serial = sector.get(serial_range)
+        .map(ascii_field)
+        .filter(|s| !s.is_empty());
+
+    Some(SystemDetection::from_header(system, evidence).with_serial(serial))
+}
+
+/// GameCube/Wii disc header magic, plus the copy of that header inside RVZ/WIA images
+fn inspect_nintendo_optical(head: &[u8]) -> Option<SystemDetection> {
+    let (disc_header, container) = if head.starts_with(b"RVZ\x01") || head.starts_with(b"WIA\x01") {
+        (head.get(0x58..)?, true)
+    } else {
+        (head, false)
+    };
+
+    let (system, evidence) = if disc_header.get(0x1C..0x20)? == GAMECUBE_MAGIC {
+        ("Nintendo - GameCube", if container { "RVZ/WIA GameCube header" } else { "GameCube disc magic" })
+    } else if disc_header.get(0x18..0x1C)? == WII_MAGIC {
+        ("Nintendo - Wii", if container { "RVZ/WIA Wii header" } else { "Wii disc magic" })
+    } else {
+        return None;
+    };
+
+    let game_id = ascii_field(disc_header.get(0..6)?);
+    let serial = Some(game_id).filter(|id| id.len() == 6);
+
+    Some(SystemDetection::from_header(system, evidence).with_serial(serial))
+}
+
+/// PSP EBOOT / PS1 classics: PARAM.SFO is the first section of the PBP container
+fn inspect_pbp<R: Read + Seek>(reader: &mut R) -> Option<SystemDetection> {
+    let mut header = [0u8; 0x28];
+    read_at(reader, 0, &mut header).ok()?;
+
+    let sfo_start = u32::from_le_bytes(header[0x08..0x0C].try_into().ok()?) as u64;
+    let sfo_end = u32::from_le_bytes(header[0x0C..0x10].try_into().ok()?) as u64;
+    let sfo_len = sfo_end.checked_sub(sfo_start)?.min(64 * 1024) as usize;
+
+    let mut sfo = vec![0u8; sfo_len];
+    read_at(reader, sfo_start, &mut sfo).ok()?;
+    let params = parse_param_sfo(&sfo);
+
+    // Category "ME" marks a PS1 game wrapped for the PSP
+    let detection = if params.get("CATEGORY").map(String::as_str) == Some("ME") {
+        SystemDetection::from_header("Sony - PlayStation", "PBP PARAM.SFO (PS1 classic)")
+    } else {
+        SystemDetection::from_header("Sony - PlayStation Portable", "PBP PARAM.SFO")
+    };
+
+    Some(detection.with_serial(params.get("DISC_ID").cloned()))
+}
+
+/// Track described by the CHT2/CHTR (CD) or CHGD (GD-ROM) metadata of a CHD
+struct ChdTrack {
+    kind: String,
+    frames: u64,
+    /// Pregap frames stored in the image ahead of the track data
+    stored_pregap: u64,
+}
+
+impl ChdTrack {
+    fn parse(text: &str) -> Option<Self> {
+        let fields: HashMap<&str, &str> = text
+            .trim_matches(|c: char| c == '\0' || c.is_whitespace())
+            .split_whitespace()
+            .filter_map(|field| field.split_once(':'))
+            .collect();
+
+        let number = |key: &str| fields.get(key).and_then(|value| value.parse::<u64>().ok()).unwrap_or(0);
+        // PGTYPE "V..." means the pregap is part of the image
+        let stored_pregap = match fields.get("PGTYPE") {
+            Some(kind) if kind.starts_with('V') => number("PREGAP"),
+            _ => 0,
+        };
+
+        Some(Self {
+            kind: fields.get("TYPE")?.to_string(),
+            frames: number("FRAMES"),
+            stored_pregap,
+        })
+    }
+
+    /// Offset of the 2048 bytes of user data inside each frame; `None` for audio
+    fn data_offset(&self) -> Option<u64> {
+        match self.kind.as_str() {
+            "MODE1" | "MODE2_FORM1" => Some(0),
+            "MODE1_RAW" => Some(16),
+            "MODE2_RAW" => Some(24),
+            "MODE2" | "MODE2_FORM_MIX" => Some(8),
+            _ => None,
+        }
+    }
+}
+
+/// MAME CHD (v5). The track metadata is always readable, so GD-ROMs are
+/// recognised from it; the boot sector and ISO9660 filesystem are only read
+/// when the hunks are stored uncompressed, as decoding the zlib/LZMA/FLAC
+/// codecs is out of scope. Compressed CD images fall back to the extension.
+fn inspect_chd<R: Read + Seek>(reader: &mut R) -> Option<SystemDetection> {
+    let mut header = [0u8; 64];
+    if read_at(reader, 0, &mut header).ok()? < header.len() {
+        return None;
+    }
+
+    let u32_at = |offset: usize| u32::from_be_bytes(header[offset..offset + 4].try_into().unwrap());
+    let u64_at = |offset: usize| u64::from_be_bytes(header[offset..offset + 8].try_into().unwrap());
+
+    if u32_at(12) != 5 {
+        return None;
+    }
+    let compressed = u32_at(16) != 0;
+    let logical_bytes = u64_at(32);
+    let map_offset = u64_at(40);
+    let meta_offset = u64_at(48);
+    let hunk_bytes = u32_at(56) as u64;
+
+    let (tracks, gdrom) = read_chd_tracks(reader, meta_offset);
+    let gdrom_detection = || gdrom.then(|| SystemDetection::from_header("Sega - Dreamcast", "CHD GD-ROM metadata"));
+
+    if compressed || hunk_bytes == 0 || !hunk_bytes.is_multiple_of(CHD_FRAME_SIZE) {
+        return gdrom_detection();
+    }
+
+    // First data track, counting the padding chdman adds after each track
+    let mut start_frame = 0;
+    let mut data_track = None;
+    for track in &tracks {
+        if let Some(offset) = track.data_offset() {
+            data_track = Some((start_frame + track.stored_pregap, offset));
+            break;
+        }
+        start_frame += track.frames.next_multiple_of(CHD_TRACK_PADDING);
+    }
+    let Some((first_frame, data_offset)) = data_track else {
+        return gdrom_detection();
+    };
+
+    let hunk_count = logical_bytes.div_ceil(hunk_bytes);
+    if hunk_count > 1 << 24 {
+        return None;
+    }
+    let mut map = vec![0u8; hunk_count as usize * 4];
+    if read_at(reader, map_offset, &mut map).ok()? < map.len() {
+        return None;
+    }
+
+    let mut hunks = ChdHunkReader {
+        reader,
+        map: map.chunks_exact(4).map(|entry| u32::from_be_bytes(entry.try_into().unwrap())).collect(),
+        hunk_bytes,
+        logical_bytes,
+        position: 0,
+    };
+    let mut disc = SectorReader::with_layout(&mut hunks, CHD_FRAME_SIZE, first_frame * CHD_FRAME_SIZE + data_offset);
+
+    if let Some(detection) = disc.read_sector(0).ok().and_then(|boot| inspect_sega_boot(&boot)) {
+        return Some(detection);
+    }
+    inspect_iso9660(&mut disc).or_else(gdrom_detection)
+}
+
+/// Walk the metadata chain of a CHD, returning its tracks and whether they
+/// describe a GD-ROM
+fn read_chd_tracks<R: Read + Seek>(reader: &mut R, mut offset: u64) -> (Vec<ChdTrack>, bool) {
+    let mut tracks = Vec::new();
+    let mut gdrom = false;
+
+    // Bounded so a corrupt chain can't loop forever
+    for _ in 0..256 {
+        if offset == 0 {
+            break;
+        }
+
+        let mut entry = [0u8; 16];
+        if !matches!(read_at(reader, offset, &mut entry), Ok(16)) {
+            break;
+        }
+        let tag = &entry[0..4];
+        let length = (u32::from_be_bytes(entry[4..8].try_into().unwrap()) & 0x00FF_FFFF) as usize;
+        let next = u64::from_be_bytes(entry[8..16].try_into().unwrap());
+
+        if matches!(tag, b"CHT2" | b"CHTR" | b"CHGD") && length <= 1024 {
+            let mut text = vec![0u8; length];
+            if read_at(reader, offset + 16, &mut text).is_ok()
+                && let Some(track) = ChdTrack::parse(&String::from_utf8_lossy(&text))
+            {
+                gdrom |= tag == b"CHGD";
+                tracks.push(track);
+            }
+        }
+
+        offset = next;
+    }
+
+    (tracks, gdrom)
+}
+
+/// Logical byte stream of an uncompressed CHD, resolved through its hunk map
+struct ChdHunkReader<'a, R: Read + Seek> {
+    reader: &'a mut R,
+    /// File offset of each hunk, in units of `hunk_bytes`; 0 = never written
+    map: Vec<u32>,
+    hunk_bytes: u64,
+    logical_bytes: u64,
+    position: u64,
+}
+
+impl<R: Read + Seek> Read for ChdHunkReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.logical_bytes || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let hunk = (self.position / self.hunk_bytes) as usize;
+        let within = self.position % self.hunk_bytes;
+        let len = (buf.len() as u64)
+            .min(self.hunk_bytes - within)
+            .min(self.logical_bytes - self.position) as usize;
+
+        match self.map.get(hunk).copied().unwrap_or(0) {
+            0 => buf[..len].fill(0),
+            entry => {
+                self.reader.seek(SeekFrom::Start(entry as u64 * self.hunk_bytes + within))?;
+                self.reader.read_exact(&mut buf[..len])?;
+            }
+        }
+
+        self.position += len as u64;
+        Ok(len)
+    }
+}
+
+impl<R: Read + Seek> Seek for ChdHunkReader<'_, R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(delta) => self.logical_bytes.checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+        };
+        self.position = position.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "seek before start"))?;
+        Ok(self.position)
+    }
+}
+
+/// Extract the string values of a PARAM.SFO table
+fn parse_param_sfo(data: &[u8]) -> HashMap<String, String> {
+    let mut params = HashMap::new();
+    if data.len() < 0x14 || !data.starts_with(b"\0PSF") {
+        return params;
+    }
+
+    let u16_at = |offset: usize| data.get(offset..offset + 2).map(|b| u16::from_le_bytes([b[0], b[1]]));
+    let u32_at = |offset: usize| data.get(offset..offset + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+
+    let (Some(key_table), Some(data_table), Some(count)) = (u32_at(0x08), u32_at(0x0C), u32_at(0x10)) else {
+        return params;
+    };
+
+    for i in 0..count as usize {
+        let index = 0x14 + i * 16;
+        let (Some(key_offset), Some(format), Some(len), Some(value_offset)) =
+            (u16_at(index), u16_at(index + 2), u32_at(index + 4), u32_at(index + 12))
+        else {
+            break;
+        };
+
+        // 0x0204 = UTF-8 string, 0x0004 = UTF-8 special (not null-terminated)
+        if format != 0x0204 && format != 0x0004 {
+            continue;
+        }
+
+        let key_start = key_table as usize + key_offset as usize;
+        let value_start = data_table as usize + value_offset as usize;
+        let Some(key) = data.get(key_start..).map(|k| ascii_field(k.split(|b| *b == 0).next().unwrap_or(&[]))) else {
+            continue;
+        };
+        let Some(value) = data.get(value_start..value_start + len as usize) else {
+            continue;
+        };
+
+        params.insert(key, ascii_field(value));
+    }
+
+    params
+}
+
+fn read_at<R: Read + Seek>(reader: &mut R, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+    reader.seek(SeekFrom::Start(offset))?;
+    let mut total = 0;
+    while total < buf.len() {
+        match reader.read(&mut buf[total..])? {
+            0 => break,
+            n => total += n,
+        }
+    }
+    Ok(total)
+}
+
+fn ascii_field(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes)
+        .trim_matches(|c: char| c == '\0' || c.is_whitespace())
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn dir_record(name: &str, lba: u32, size: u32, is_dir: bool) -> Vec<u8> {
+        let len = 33 + name.len() + (name.len() + 1) % 2;
+        let mut record = vec![0u8; len];
+        record[0] = len as u8;
+        record[2..6].copy_from_slice(&lba.to_le_bytes());
+        record[10..14].copy_from_slice(&size.to_le_bytes());
+        record[25] = if is_dir { 0x02 } else { 0 };
+        record[32] = name.len() as u8;
+        record[33..33 + name.len()].copy_from_slice(name.as_bytes());
+        record
+    }
+
+    /// Minimal cooked ISO with a root directory holding a single file
+    fn build_iso(system_id: &str, file_name: &str, content: &[u8]) -> Vec<u8> {
+        let mut image = vec![0u8; 20 * SECTOR_SIZE as usize];
+
+        let pvd = 16 * SECTOR_SIZE as usize;
+        image[pvd] = 1;
+        image[pvd + 1..pvd + 6].copy_from_slice(b"CD001");
+        image[pvd + 8..pvd + 8 + system_id.len()].copy_from_slice(system_id.as_bytes());
+        let root = dir_record("\0", 18, SECTOR_SIZE as u32, true);
+        image[pvd + 156..pvd + 156 + 34].copy_from_slice(&root[..34]);
+
+        let dir = 18 * SECTOR_SIZE as usize;
+        let record = dir_record(file_name, 19, content.len() as u32, false);
+        image[dir..dir + record.len()].copy_from_slice(&record);
+
+        let data = 19 * SECTOR_SIZE as usize;
+        image[data..data + content.len()].copy_from_slice(content);
+        image
+    }
+
+    #[test]
+    fn test_ps2_system_cnf() {
+        let iso = build_iso("PLAYSTATION", "SYSTEM.CNF;1", b"BOOT2 = cdrom0:\\SLUS_203.12;1\r\nVER = 1.00\r\n");
+        let detection = inspect_reader(&mut Cursor::new(iso)).unwrap();
+
+        assert_eq!(detection.system, "Sony - PlayStation 2");
+        assert_eq!(detection.serial.as_deref(), Some("SLUS-20312"));
+    }
+
+    #[test]
+    fn test_ps1_raw_mode2_image() {
+        let iso = build_iso("PLAYSTATION", "SYSTEM.CNF;1", b"BOOT = cdrom:\\SCES_003.44;1\r\n");
+
+        // Re-wrap every cooked sector as a raw Mode 2 sector
+        let mut raw = Vec::new();
+        for sector in iso.chunks(SECTOR_SIZE as usize) {
+            let mut frame = vec![0u8; 2352];
+            frame[..12].copy_from_slice(&CD_SYNC);
+            frame[15] = 2;
+            frame[24..24 + sector.len()].copy_from_slice(sector);
+            raw.extend_from_slice(&frame);
+        }
+
+        let detection = inspect_reader(&mut Cursor::new(raw)).unwrap();
+        assert_eq!(detection.system, "Sony - PlayStation");
+        assert_eq!(detection.serial.as_deref(), Some("SCES-00344"));
+    }
+
+    #[test]
+    fn test_psp_umd_data() {
+        let iso = build_iso("PSP GAME", "UMD_DATA.BIN;1", b"ULUS-10041|0001|G");
+        let detection = inspect_reader(&mut Cursor::new(iso)).unwrap();
+
+        assert_eq!(detection.system, "Sony - PlayStation Portable");
+        assert_eq!(detection.serial.as_deref(), Some("ULUS-10041"));
+    }
+
+    #[test]
+    fn test_saturn_ip_bin() {
+        let mut image = vec![0u8; 4 * SECTOR_SIZE as usize];
+        image[..16].copy_from_slice(b"SEGA SEGASATURN ");
+        image[0x20..0x2A].copy_from_slice(b"MK-81009  ");
+
+        let detection = inspect_reader(&mut Cursor::new(image)).unwrap();
+        assert_eq!(detection.system, "Sega - Saturn");
+        assert_eq!(detection.serial.as_deref(), Some("MK-81009"));
+    }
+
+    #[test]
+    fn test_gamecube_magic() {
+        let mut image = vec![0u8; 0x440];
+        image[..6].copy_from_slice(b"GALE01");
+        image[0x1C..0x20].copy_from_slice(&GAMECUBE_MAGIC);
+
+        let detection = inspect_reader(&mut Cursor::new(image)).unwrap();
+        assert_eq!(detection.system, "Nintendo - GameCube");
+        assert_eq!(detection.serial.as_deref(), Some("GALE01"));
+    }
+
+    /// CHD v5 with the given track metadata. Without `frames` the header
+    /// claims zlib compression and no hunks are stored.
+    fn build_chd(tracks: &[(&[u8; 4], &str)], frames: Option<&[u8]>) -> Vec<u8> {
+        let hunk_bytes = 8 * CHD_FRAME_SIZE as usize;
+        let logical_bytes = frames.map_or(0, <[u8]>::len);
+        let hunk_count = logical_bytes.div_ceil(hunk_bytes);
+
+        let mut image = vec![0u8; 124];
+        image[..8].copy_from_slice(CHD_MAGIC);
+        image[8..12].copy_from_slice(&124u32.to_be_bytes());
+        image[12..16].copy_from_slice(&5u32.to_be_bytes());
+        if frames.is_none() {
+            image[16..20].copy_from_slice(b"zlib");
+        }
+        image[32..40].copy_from_slice(&(logical_bytes as u64).to_be_bytes());
+        image[40..48].copy_from_slice(&124u64.to_be_bytes());
+        image[56..60].copy_from_slice(&(hunk_bytes as u32).to_be_bytes());
+        image[60..64].copy_from_slice(&(CHD_FRAME_SIZE as u32).to_be_bytes());
+
+        // Hunks go after the map and metadata, each at a hunk-aligned offset
+        let meta_offset = 124 + hunk_count * 4;
+        image[48..56].copy_from_slice(&(meta_offset as u64).to_be_bytes());
+        for hunk in 0..hunk_count {
+            image.extend_from_slice(&(hunk as u32 + 1).to_be_bytes());
+        }
+
+        for (i, (tag, text)) in tracks.iter().enumerate() {
+            let data = format!("{}\0", text);
+            let next = if i + 1 < tracks.len() { image.len() + 16 + data.len() } else { 0 };
+            image.extend_from_slice(*tag);
+            image.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            image.extend_from_slice(&(next as u64).to_be_bytes());
+            image.extend_from_slice(data.as_bytes());
+        }
+
+        if let Some(frames) = frames {
+            image.resize(hunk_bytes, 0);
+            image.extend_from_slice(frames);
+            image.resize((hunk_count + 1) * hunk_bytes, 0);
+        }
+        image
+    }
+
+    #[test]
+    fn test_uncompressed_chd_data_track() {
+        // Audio track first, so the data track starts after its padded frames
+        let mut frames = vec![0u8; 4 * CHD_FRAME_SIZE as usize];
+        let mut sector = vec![0u8; 2448];
+        sector[..12].copy_from_slice(&CD_SYNC);
+        sector[16..32].copy_from_slice(b"SEGA SEGASATURN ");
+        sector[16 + 0x20..16 + 0x2A].copy_from_slice(b"T-4507G   ");
+        frames.extend_from_slice(&sector);
+        frames.resize(8 * CHD_FRAME_SIZE as usize, 0);
+
+        let chd = build_chd(&[
+            (b"CHT2", "TRACK:1 TYPE:AUDIO SUBTYPE:NONE FRAMES:3 PREGAP:0 PGTYPE:MODE1 PGSUB:RW POSTGAP:0"),
+            (b"CHT2", "TRACK:2 TYPE:MODE1_RAW SUBTYPE:NONE FRAMES:4 PREGAP:0 PGTYPE:MODE1 PGSUB:RW POSTGAP:0"),
+        ], Some(&frames));
+
+        let detection = inspect_reader(&mut Cursor::new(chd)).unwrap();
+        assert_eq!(detection.system, "Sega - Saturn");
+        assert_eq!(detection.serial.as_deref(), Some("T-4507G"));
+    }
+
+    #[test]
+    fn test_compressed_chd() {
+        // GD-ROM metadata alone identifies a Dreamcast disc
+        let gdrom = build_chd(&[
+            (b"CHGD", "TRACK:1 TYPE:MODE1 SUBTYPE:NONE FRAMES:300 PAD:0 PREGAP:0 PGTYPE:MODE1 PGSUB:NONE POSTGAP:0"),
+            (b"CHGD", "TRACK:3 TYPE:MODE1_RAW SUBTYPE:NONE FRAMES:500000 PAD:0 PREGAP:0 PGTYPE:MODE1 PGSUB:NONE POSTGAP:0"),
+        ], None);
+        let detection = inspect_reader(&mut Cursor::new(gdrom)).unwrap();
+        assert_eq!(detection.system, "Sega - Dreamcast");
+
+        // Compressed CD sectors can't be read, the extension decides as before
+        let cd = build_chd(&[
+            (b"CHT2", "TRACK:1 TYPE:MODE2_RAW SUBTYPE:NONE FRAMES:1000 PREGAP:0 PGTYPE:MODE2_RAW PGSUB:RW POSTGAP:0"),
+        ], None);
+        assert!(inspect_reader(&mut Cursor::new(cd)).is_none());
+    }
+
+    #[test]
+    fn test_unknown_image() {
+        let image = vec![0u8; 20 * SECTOR_SIZE as usize];
+        assert!(inspect_reader(&mut Cursor::new(image)).is_none());
+    }
+}
@@ -0,0 +1,255 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// Kind of sheet describing a multi-track disc, or a multi-disc game (M3U)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SheetKind {
+    Cue,
+    Gdi,
+    M3u,
+}
+
+impl SheetKind {
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_lowercase().as_str() {
+            "cue" => Some(SheetKind::Cue),
+            "gdi" => Some(SheetKind::Gdi),
+            "m3u" => Some(SheetKind::M3u),
+            _ => None,
+        }
+    }
+}
+
+/// A track listed in a CUE or GDI sheet, or a disc listed in an M3U
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SheetTrack {
+    pub number: u32,
+    /// Track file resolved relative to the sheet
+    pub file: PathBuf,
+    pub is_data: bool,
+}
+
+/// Parsed CUE/GDI/M3U sheet
+#[derive(Debug, Clone)]
+pub struct DiscSheet {
+    pub path: PathBuf,
+    pub kind: SheetKind,
+    pub tracks: Vec<SheetTrack>,
+}
+
+impl DiscSheet {
+    /// Parse a `.cue`, `.gdi` or `.m3u` file, detected from its extension
+    pub fn load(path: &Path) -> Result<Self> {
+        let extension = path.extension()
+            .unwrap_or_default()
+            .to_string_lossy();
+        let kind = SheetKind::from_extension(&extension)
+            .with_context(|| format!("Não é um arquivo CUE/GDI/M3U: {}", path.display()))?;
+
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Falha ao ler {}", path.display()))?;
+        let base = path.parent().unwrap_or(Path::new(""));
+
+        let tracks = match kind {
+            SheetKind::Cue => parse_cue(&content, base),
+            SheetKind::Gdi => parse_gdi(&content, base),
+            SheetKind::M3u => parse_m3u(&content, base),
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            kind,
+            tracks,
+        })
+    }
+
+    /// Distinct track files in sheet order (a CUE may list several tracks per file).
+    /// CUE/GDI discs listed in an M3U contribute their tracks, then the sheet itself.
+    pub fn files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = Vec::new();
+        for track in &self.tracks {
+            let nested = match self.kind {
+                SheetKind::M3u => Self::load_disc(&track.file).map(|disc| disc.files()).unwrap_or_default(),
+                _ => Vec::new(),
+            };
+            for file in nested.into_iter().chain(std::iter::once(track.file.clone())) {
+                if !files.contains(&file) {
+                    files.push(file);
+                }
+            }
+        }
+        files
+    }
+
+    /// CUE/GDI sheet of a disc listed in an M3U
+    fn load_disc(path: &Path) -> Option<Self> {
+        let extension = path.extension().unwrap_or_default().to_string_lossy();
+        match SheetKind::from_extension(&extension)? {
+            SheetKind::Cue | SheetKind::Gdi => Self::load(path).ok(),
+            SheetKind::M3u => None,
+        }
+    }
+
+    /// Track files that exist on disk
+    pub fn present_files(&self) -> Vec<PathBuf> {
+        self.files().into_iter().filter(|f| f.is_file()).collect()
+    }
+
+    /// Track files referenced by the sheet but not found on disk
+    pub fn missing_files(&self) -> Vec<PathBuf> {
+        self.files().into_iter().filter(|f| !f.is_file()).collect()
+    }
+
+    /// File holding the disc's boot data: GD-ROM track 3 for GDI, first data track
+    /// otherwise. For an M3U this is the first disc, which may itself be a sheet.
+    pub fn data_track(&self) -> Option<&SheetTrack> {
+        if self.kind == SheetKind::Gdi
+            && let Some(track) = self.tracks.iter().find(|t| t.number == 3 && t.is_data)
+        {
+            return Some(track);
+        }
+        self.tracks.iter().find(|t| t.is_data).or_else(|| self.tracks.first())
+    }
+}
+
+/// `FILE "name.bin" BINARY` followed by `TRACK nn MODE1/2352|MODE2/2352|AUDIO` lines
+fn parse_cue(content: &str, base: &Path) -> Vec<SheetTrack> {
+    let mut tracks = Vec::new();
+    let mut current_file: Option<PathBuf> = None;
+
+    for line in content.lines() {
+        let line = line.trim();
+        let keyword = line.split_whitespace().next().unwrap_or("").to_uppercase();
+
+        match keyword.as_str() {
+            "FILE" => {
+                current_file = sheet_file_name(line, 1).map(|name| base.join(name));
+            }
+            "TRACK" => {
+                let mut fields = line.split_whitespace().skip(1);
+                let number = fields.next().and_then(|n| n.parse().ok()).unwrap_or(0);
+                let mode = fields.next().unwrap_or("").to_uppercase();
+
+                if let Some(file) = &current_file {
+                    tracks.push(SheetTrack {
+                        number,
+                        file: file.clone(),
+                        is_data: mode != "AUDIO",
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    tracks
+}
+
+/// Track count line, then `<track> <lba> <type> <sector size> <file> <offset>` (type 4 = data)
+fn parse_gdi(content: &str, base: &Path) -> Vec<SheetTrack> {
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let line = line.trim();
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 5 {
+                return None;
+            }
+
+            let number = fields[0].parse().ok()?;
+            let file = sheet_file_name(line, 4)?;
+
+            Some(SheetTrack {
+                number,
+                file: base.join(file),
+                is_data: fields[2] == "4",
+            })
+        })
+        .collect()
+}
+
+/// One disc image (or CUE/GDI sheet) per line; `#` lines are comments or extended tags
+fn parse_m3u(content: &str, base: &Path) -> Vec<SheetTrack> {
+    content
+        .lines()
+        .map(|line| line.trim().trim_start_matches('\u{feff}'))
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .enumerate()
+        .map(|(index, line)| SheetTrack {
+            number: index as u32 + 1,
+            file: base.join(line),
+            is_data: true,
+        })
+        .collect()
+}
+
+/// File names may be quoted (and contain spaces) or bare at the given field index
+fn sheet_file_name(line: &str, field: usize) -> Option<String> {
+    if let Some(quoted) = line.split('"').nth(1) {
+        return Some(quoted.to_string());
+    }
+    line.split_whitespace().nth(field).map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cue_tracks_and_missing_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let cue = dir.path().join("Game (USA).cue");
+        std::fs::write(&cue, concat!(
+            "FILE \"Game (USA) (Track 1).bin\" BINARY\n",
+            "  TRACK 01 MODE2/2352\n",
+            "    INDEX 01 00:00:00\n",
+            "FILE \"Game (USA) (Track 2).bin\" BINARY\n",
+            "  TRACK 02 AUDIO\n",
+            "    INDEX 00 00:00:00\n",
+        )).unwrap();
+        std::fs::write(dir.path().join("Game (USA) (Track 1).bin"), b"data").unwrap();
+
+        let sheet = DiscSheet::load(&cue).unwrap();
+        assert_eq!(sheet.tracks.len(), 2);
+        assert!(sheet.tracks[0].is_data);
+        assert!(!sheet.tracks[1].is_data);
+        assert_eq!(sheet.data_track().unwrap().number, 1);
+        assert_eq!(sheet.present_files(), vec![dir.path().join("Game (USA) (Track 1).bin")]);
+        assert_eq!(sheet.missing_files(), vec![dir.path().join("Game (USA) (Track 2).bin")]);
+    }
+
+    #[test]
+    fn test_gdi_data_track() {
+        let dir = tempfile::tempdir().unwrap();
+        let gdi = dir.path().join("disc.gdi");
+        std::fs::write(&gdi, concat!(
+            "3\n",
+            "1 0 4 2352 track01.bin 0\n",
+            "2 756 0 2352 track02.raw 0\n",
+            "3 45000 4 2352 \"track 03.bin\" 0\n",
+        )).unwrap();
+
+        let sheet = DiscSheet::load(&gdi).unwrap();
+        assert_eq!(sheet.tracks.len(), 3);
+        assert_eq!(sheet.data_track().unwrap().file, dir.path().join("track 03.bin"));
+        assert_eq!(sheet.missing_files().len(), 3);
+    }
+
+    #[test]
+    fn test_m3u_expands_disc_sheets() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("Game (Disc 1).cue"), "FILE \"Game (Disc 1).bin\" BINARY\n  TRACK 01 MODE2/2352\n").unwrap();
+        let m3u = dir.path().join("Game.m3u");
+        std::fs::write(&m3u, "#EXTM3U\nGame (Disc 1).cue\n\nGame (Disc 2).chd\n").unwrap();
+
+        let sheet = DiscSheet::load(&m3u).unwrap();
+        assert_eq!(sheet.tracks.len(), 2);
+        assert_eq!(sheet.data_track().unwrap().file, dir.path().join("Game (Disc 1).cue"));
+        assert_eq!(sheet.files(), vec![
+            dir.path().join("Game (Disc 1).bin"),
+            dir.path().join("Game (Disc 1).cue"),
+            dir.path().join("Game (Disc 2).chd"),
+        ]);
+    }
+}
@@ -103,3 +103,39 @@ pub enum ConfigError {
     #[error("Falha na serialização da configuração: {0}")]
     SerializationFailed(#[from] toml::ser::Error),
 }
+
+#[derive(Error, Debug)]
+pub enum CacheError {
+    #[error("Arquivo de cache inválido: {0}")]
+    InvalidHeader(PathBuf),
+
+    #[error("Versão {version} do cache em {path} não é suportada")]
+    UnsupportedVersion {
+        path: PathBuf,
+        version: u32,
+    },
+
+    #[error("Cache em {0} está em uso por outro processo")]
+    Locked(PathBuf),
+}
+
+#[derive(Error, Debug)]
+pub enum HookError {
+    #[error("Falha ao executar hook `{command}`: {source}")]
+    SpawnFailed {
+        command: String,
+        source: std::io::Error,
+    },
+
+    #[error("Hook `{command}` terminou com {status}")]
+    Failed {
+        command: String,
+        status: std::process::ExitStatus,
+    },
+
+    #[error("Hook `{command}` excedeu o tempo limite de {secs}s")]
+    TimedOut {
+        command: String,
+        secs: u64,
+    },
+}
@@ -0,0 +1,495 @@
+use anyhow::Result;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::path::Path;
+
+use crate::error::DatError;
+
+/// Bytes read from the start of a file to evaluate skipper rules
+pub const SKIPPER_PROBE_SIZE: usize = 0x200;
+
+// Header skipper definitions in the clrmamepro detector format used by No-Intro DATs
+// (`<clrmamepro header="No-Intro_NES.xml"/>`). Offsets and values are hexadecimal.
+const NES_SKIPPER: &str = r#"<?xml version="1.0"?>
+<detector>
+    <name>No-Intro_NES.xml</name>
+    <rule start_offset="10" end_offset="EOF" operation="none">
+        <data offset="0" value="4E45531A" result="true"/>
+    </rule>
+</detector>"#;
+
+const FDS_SKIPPER: &str = r#"<?xml version="1.0"?>
+<detector>
+    <name>No-Intro_FDS.xml</name>
+    <rule start_offset="10" end_offset="EOF" operation="none">
+        <data offset="0" value="4644531A" result="true"/>
+    </rule>
+</detector>"#;
+
+const LYNX_SKIPPER: &str = r#"<?xml version="1.0"?>
+<detector>
+    <name>No-Intro_LNX.xml</name>
+    <rule start_offset="40" end_offset="EOF" operation="none">
+        <data offset="0" value="4C594E58" result="true"/>
+    </rule>
+</detector>"#;
+
+const A7800_SKIPPER: &str = r#"<?xml version="1.0"?>
+<detector>
+    <name>No-Intro_A7800.xml</name>
+    <rule start_offset="80" end_offset="EOF" operation="none">
+        <data offset="1" value="415441524937383030" result="true"/>
+    </rule>
+    <rule start_offset="80" end_offset="EOF" operation="none">
+        <data offset="64" value="41435455414C20434152542044415441205354415254532048455245" result="true"/>
+    </rule>
+</detector>"#;
+
+const SNES_SKIPPER: &str = r#"<?xml version="1.0"?>
+<detector>
+    <name>SNES copier header</name>
+    <rule start_offset="200" end_offset="EOF" operation="none">
+        <data offset="8" value="AABB04" result="true"/>
+    </rule>
+    <rule start_offset="200" end_offset="EOF" operation="none">
+        <data offset="8" value="5355504552554641" result="true"/>
+    </rule>
+    <rule start_offset="200" end_offset="EOF" operation="none">
+        <data offset="0" value="47414D4520444F43544F522053462033" result="true"/>
+    </rule>
+</detector>"#;
+
+/// Comparison used by `<file size=".." operator=".."/>` tests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeOperator {
+    Equal,
+    Less,
+    Greater,
+}
+
+/// Expected size in a `<file>` test: a hex byte count or "PO2" (power of two)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileSizeSpec {
+    Bytes(u64),
+    PowerOfTwo,
+}
+
+/// A single condition of a skipper rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipTest {
+    /// `<data offset=".." value=".." result=".."/>`
+    Data { offset: u64, value: Vec<u8>, result: bool },
+    /// `<file size=".." operator=".." result=".."/>`
+    File { size: FileSizeSpec, operator: SizeOperator, result: bool },
+    /// File size leaves `remainder` over a multiple of `modulo` (copier headers with no signature)
+    SizeModulo { modulo: u64, remainder: u64 },
+}
+
+impl SkipTest {
+    fn matches(&self, probe: &[u8], file_size: u64) -> bool {
+        match self {
+            SkipTest::Data { offset, value, result } => {
+                let start = *offset as usize;
+                let found = probe.get(start..start + value.len()) == Some(value.as_slice());
+                found == *result
+            }
+            SkipTest::File { size, operator, result } => {
+                let found = match (size, operator) {
+                    (FileSizeSpec::PowerOfTwo, SizeOperator::Equal) => file_size.is_power_of_two(),
+                    (FileSizeSpec::PowerOfTwo, _) => false,
+                    (FileSizeSpec::Bytes(expected), SizeOperator::Equal) => file_size == *expected,
+                    (FileSizeSpec::Bytes(expected), SizeOperator::Less) => file_size < *expected,
+                    (FileSizeSpec::Bytes(expected), SizeOperator::Greater) => file_size > *expected,
+                };
+                found == *result
+            }
+            SkipTest::SizeModulo { modulo, remainder } => file_size % modulo == *remainder,
+        }
+    }
+}
+
+/// Byte range to hash when every test of the rule passes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkipRule {
+    pub start_offset: u64,
+    /// `None` means end of file
+    pub end_offset: Option<u64>,
+    pub tests: Vec<SkipTest>,
+}
+
+impl SkipRule {
+    pub fn matches(&self, probe: &[u8], file_size: u64) -> bool {
+        self.start_offset < file_size && self.tests.iter().all(|test| test.matches(probe, file_size))
+    }
+}
+
+/// A clrmamepro header skipper (detector) definition
+#[derive(Debug, Clone)]
+pub struct HeaderSkipper {
+    pub name: String,
+    /// File the definition was read from, as DATs reference it
+    pub file_name: String,
+    /// Extensions this skipper applies to; empty means any file
+    pub extensions: Vec<String>,
+    pub rules: Vec<SkipRule>,
+}
+
+impl HeaderSkipper {
+    /// Parse a clrmamepro detector XML. Rules with byte-swapping operations are ignored.
+    pub fn from_xml(content: &str, source: &Path) -> Result<Self> {
+        let invalid = |message: String| DatError::InvalidFormat {
+            path: source.to_path_buf(),
+            message,
+        };
+
+        let mut reader = Reader::from_str(content);
+        reader.config_mut().trim_text(true);
+
+        let mut buf = Vec::new();
+        let mut name = String::new();
+        let mut in_name = false;
+        let mut rules = Vec::new();
+        let mut current_rule: Option<SkipRule> = None;
+
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(ref e)) => match e.name().as_ref() {
+                    b"name" => in_name = true,
+                    b"rule" => current_rule = parse_rule(e).map_err(&invalid)?,
+                    _ => {}
+                },
+                Ok(Event::Empty(ref e)) => match e.name().as_ref() {
+                    b"rule" => {
+                        // A rule without tests always applies
+                        if let Some(rule) = parse_rule(e).map_err(&invalid)? {
+                            rules.push(rule);
+                        }
+                    }
+                    b"data" | b"file" => {
+                        if let Some(ref mut rule) = current_rule {
+                            rule.tests.push(parse_test(e).map_err(&invalid)?);
+                        }
+                    }
+                    _ => {}
+                },
+                Ok(Event::Text(e)) if in_name => {
+                    name = e.unescape().map(|text| text.to_string()).unwrap_or_default();
+                }
+                Ok(Event::End(ref e)) => match e.name().as_ref() {
+                    b"name" => in_name = false,
+                    b"rule" => {
+                        if let Some(rule) = current_rule.take() {
+                            rules.push(rule);
+                        }
+                    }
+                    _ => {}
+                },
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(invalid(e.to_string()).into()),
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        let file_name = source.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if name.is_empty() {
+            name = file_name.clone();
+        }
+
+        Ok(Self {
+            name,
+            file_name,
+            extensions: Vec::new(),
+            rules,
+        })
+    }
+
+    /// Load a detector XML file from disk
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| DatError::LoadFailed {
+                path: path.to_path_buf(),
+                source: e,
+            })?;
+        Self::from_xml(&content, path)
+    }
+
+    /// Whether a DAT's `<clrmamepro header="..."/>` refers to this skipper,
+    /// by file or detector name, with or without the `.xml` suffix
+    pub fn is_named(&self, reference: &str) -> bool {
+        let normalize = |name: &str| name.trim().trim_end_matches(".xml").to_lowercase();
+        let reference = normalize(reference);
+        normalize(&self.name) == reference || normalize(&self.file_name) == reference
+    }
+
+    pub fn applies_to(&self, extension: &str) -> bool {
+        self.extensions.is_empty() || self.extensions.iter().any(|ext| ext == extension)
+    }
+
+    /// First rule whose tests pass for this file
+    pub fn find_rule(&self, probe: &[u8], file_size: u64) -> Option<&SkipRule> {
+        self.rules.iter().find(|rule| rule.matches(probe, file_size))
+    }
+}
+
+/// Collection of header skippers consulted while hashing
+#[derive(Debug, Clone, Default)]
+pub struct SkipperSet {
+    skippers: Vec<HeaderSkipper>,
+}
+
+impl SkipperSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Skippers for NES, FDS, Lynx, Atari 7800 and SNES copier headers
+    pub fn builtin() -> Self {
+        let definitions: [(&str, &[&str]); 5] = [
+            (NES_SKIPPER, &["nes"]),
+            (FDS_SKIPPER, &["fds"]),
+            (LYNX_SKIPPER, &["lnx"]),
+            (A7800_SKIPPER, &["a78"]),
+            (SNES_SKIPPER, &["smc", "sfc", "swc", "fig"]),
+        ];
+
+        let mut set = Self::new();
+        for (xml, extensions) in definitions {
+            let mut skipper = HeaderSkipper::from_xml(xml, Path::new("builtin"))
+                .expect("built-in header skipper must parse");
+            skipper.file_name = skipper.name.clone();
+            skipper.extensions = extensions.iter().map(|ext| ext.to_string()).collect();
+            set.add(skipper);
+        }
+
+        // Most SNES copier headers are zero-filled; the 512-byte remainder is the only tell
+        if let Some(snes) = set.skippers.iter_mut().find(|s| s.name == "SNES copier header") {
+            snes.rules.push(SkipRule {
+                start_offset: 0x200,
+                end_offset: None,
+                tests: vec![SkipTest::SizeModulo { modulo: 1024, remainder: 512 }],
+            });
+        }
+
+        set
+    }
+
+    /// Built-in skippers plus every detector XML in `dir`. A file with the
+    /// name of a built-in skipper replaces its rules and keeps its extensions.
+    pub fn load_directory(dir: &Path) -> Result<Self> {
+        let mut set = Self::builtin();
+        let entries = std::fs::read_dir(dir)
+            .map_err(|e| DatError::LoadFailed {
+                path: dir.to_path_buf(),
+                source: e,
+            })?;
+
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("xml")))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let skipper = HeaderSkipper::load(&path)?;
+            match set.skippers.iter_mut().find(|s| s.is_named(&skipper.name) || s.is_named(&skipper.file_name)) {
+                Some(existing) => {
+                    existing.name = skipper.name;
+                    existing.file_name = skipper.file_name;
+                    existing.rules = skipper.rules;
+                }
+                None => set.add(skipper),
+            }
+        }
+
+        Ok(set)
+    }
+
+    pub fn add(&mut self, skipper: HeaderSkipper) {
+        self.skippers.push(skipper);
+    }
+
+    /// Try the skippers named by DAT headers before the others, so the
+    /// headerless CRC is the one those DATs were made with. Returns the
+    /// names that match no known skipper.
+    pub fn select(&mut self, references: &[String]) -> Vec<String> {
+        let missing = references
+            .iter()
+            .filter(|reference| !self.skippers.iter().any(|s| s.is_named(reference)))
+            .cloned()
+            .collect();
+
+        // Stable sort: selected skippers keep their relative order
+        self.skippers.sort_by_key(|skipper| !references.iter().any(|reference| skipper.is_named(reference)));
+        missing
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.skippers.is_empty()
+    }
+
+    /// Whether any skipper could apply to files with this extension
+    pub fn handles_extension(&self, extension: &str) -> bool {
+        self.skippers.iter().any(|s| s.applies_to(extension))
+    }
+
+    /// Matching skipper and rule for a file
+    pub fn find(&self, extension: &str, probe: &[u8], file_size: u64) -> Option<(&HeaderSkipper, &SkipRule)> {
+        self.skippers
+            .iter()
+            .filter(|skipper| skipper.applies_to(extension))
+            .find_map(|skipper| skipper.find_rule(probe, file_size).map(|rule| (skipper, rule)))
+    }
+}
+
+fn parse_rule(element: &BytesStart) -> std::result::Result<Option<SkipRule>, String> {
+    let operation = attribute(element, b"operation").unwrap_or_else(|| "none".to_string());
+    if operation != "none" {
+        // bitswap/byteswap/wordswap transform the data instead of skipping a header
+        return Ok(None);
+    }
+
+    let start_offset = match attribute(element, b"start_offset") {
+        Some(value) => parse_hex(&value)?,
+        None => 0,
+    };
+    let end_offset = match attribute(element, b"end_offset").as_deref() {
+        None | Some("EOF") => None,
+        Some(value) => Some(parse_hex(value)?),
+    };
+
+    Ok(Some(SkipRule {
+        start_offset,
+        end_offset,
+        tests: Vec::new(),
+    }))
+}
+
+fn parse_test(element: &BytesStart) -> std::result::Result<SkipTest, String> {
+    let result = attribute(element, b"result").map(|r| r != "false").unwrap_or(true);
+
+    match element.name().as_ref() {
+        b"data" => {
+            let offset = parse_hex(&attribute(element, b"offset").unwrap_or_else(|| "0".to_string()))?;
+            let value = attribute(element, b"value").ok_or("data test sem value")?;
+            Ok(SkipTest::Data {
+                offset,
+                value: parse_hex_bytes(&value)?,
+                result,
+            })
+        }
+        _ => {
+            let size = match attribute(element, b"size").as_deref() {
+                Some("PO2") => FileSizeSpec::PowerOfTwo,
+                Some(value) => FileSizeSpec::Bytes(parse_hex(value)?),
+                None => return Err("file test sem size".to_string()),
+            };
+            let operator = match attribute(element, b"operator").as_deref() {
+                Some("less") => SizeOperator::Less,
+                Some("greater") => SizeOperator::Greater,
+                _ => SizeOperator::Equal,
+            };
+            Ok(SkipTest::File { size, operator, result })
+        }
+    }
+}
+
+fn attribute(element: &BytesStart, key: &[u8]) -> Option<String> {
+    element.attributes()
+        .filter_map(|attr| attr.ok())
+        .find(|attr| attr.key.as_ref() == key)
+        .and_then(|attr| attr.unescape_value().ok().map(|value| value.to_string()))
+}
+
+fn parse_hex(value: &str) -> std::result::Result<u64, String> {
+    u64::from_str_radix(value.trim_start_matches("0x"), 16)
+        .map_err(|_| format!("valor hexadecimal inválido: {}", value))
+}
+
+fn parse_hex_bytes(value: &str) -> std::result::Result<Vec<u8>, String> {
+    if !value.len().is_multiple_of(2) {
+        return Err(format!("valor hexadecimal inválido: {}", value));
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&value[i..i + 2], 16)
+            .map_err(|_| format!("valor hexadecimal inválido: {}", value)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_nes_skipper() {
+        let set = SkipperSet::builtin();
+        let mut probe = vec![0u8; 32];
+        probe[..4].copy_from_slice(b"NES\x1A");
+
+        let (skipper, rule) = set.find("nes", &probe, 16 + 0x8000).unwrap();
+        assert_eq!(skipper.name, "No-Intro_NES.xml");
+        assert_eq!(rule.start_offset, 0x10);
+        assert_eq!(rule.end_offset, None);
+
+        // Wrong extension or missing magic: no skipper
+        assert!(set.find("gb", &probe, 16 + 0x8000).is_none());
+        assert!(set.find("nes", &[0u8; 32], 0x8000).is_none());
+    }
+
+    #[test]
+    fn test_snes_size_remainder() {
+        let set = SkipperSet::builtin();
+        let probe = vec![0u8; SKIPPER_PROBE_SIZE];
+
+        assert_eq!(set.find("smc", &probe, 0x80200).unwrap().1.start_offset, 0x200);
+        assert!(set.find("smc", &probe, 0x80000).is_none());
+    }
+
+    #[test]
+    fn test_parse_file_test_and_swap_rules() {
+        let xml = r#"<detector>
+            <name>test</name>
+            <rule start_offset="0" end_offset="EOF" operation="byteswap">
+                <data offset="0" value="37804012"/>
+            </rule>
+            <rule start_offset="20" end_offset="1000" operation="none">
+                <file size="PO2" result="false"/>
+            </rule>
+        </detector>"#;
+
+        let skipper = HeaderSkipper::from_xml(xml, Path::new("test.xml")).unwrap();
+        assert_eq!(skipper.rules.len(), 1);
+        assert_eq!(skipper.rules[0].end_offset, Some(0x1000));
+        assert!(skipper.find_rule(&[], 0x1020).is_some());
+        assert!(skipper.find_rule(&[], 0x1000).is_none());
+    }
+
+    #[test]
+    fn test_load_directory_and_select_dat_skipper() {
+        let dir = tempfile::tempdir().unwrap();
+        // Replaces the built-in NES rule, and adds a second .nes skipper
+        std::fs::write(dir.path().join("No-Intro_NES.xml"), r#"<detector>
+            <name>No-Intro_NES</name>
+            <rule start_offset="10"><data offset="0" value="4E45531A"/></rule>
+        </detector>"#).unwrap();
+        std::fs::write(dir.path().join("trainer.xml"), r#"<detector>
+            <name>NES trainer</name>
+            <rule start_offset="210"><data offset="0" value="4E45531A"/></rule>
+        </detector>"#).unwrap();
+
+        let mut set = SkipperSet::load_directory(dir.path()).unwrap();
+        let mut probe = vec![0u8; 32];
+        probe[..4].copy_from_slice(b"NES\x1A");
+        let (skipper, _) = set.find("nes", &probe, 0x8010).unwrap();
+        assert_eq!(skipper.name, "No-Intro_NES");
+        assert_eq!(skipper.extensions, vec!["nes".to_string()]);
+
+        let missing = set.select(&["trainer.xml".to_string(), "No-Intro_PCE.xml".to_string()]);
+        assert_eq!(missing, vec!["No-Intro_PCE.xml".to_string()]);
+        assert_eq!(set.find("nes", &probe, 0x8010).unwrap().1.start_offset, 0x210);
+    }
+}
@@ -0,0 +1,265 @@
+use anyhow::Result;
+use log::{debug, info, warn};
+use serde::Serialize;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::config::{HookFailurePolicy, HooksConfig};
+use crate::error::HookError;
+
+/// Moment a hook runs at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookEvent {
+    #[default]
+    PlaylistWritten,
+    WatchBatch,
+    IndexComplete,
+}
+
+/// Details handed to a hook, as JSON on stdin and as environment variables
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HookContext {
+    pub event: HookEvent,
+    /// Playlists written, or removed because their last ROM went away
+    pub playlists: Vec<PathBuf>,
+    /// System of the playlist, for `PlaylistWritten`
+    pub system: Option<String>,
+    /// ROMs in the written playlist, or in the whole index
+    pub roms: usize,
+    pub rescanned: usize,
+    pub removed: usize,
+    pub output_dir: PathBuf,
+}
+
+/// Runs the commands configured in `[hooks]`
+#[derive(Debug, Clone, Default)]
+pub struct HookRunner {
+    config: HooksConfig,
+}
+
+impl HookEvent {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::PlaylistWritten => "playlist_written",
+            Self::WatchBatch => "watch_batch",
+            Self::IndexComplete => "index_complete",
+        }
+    }
+}
+
+impl HookContext {
+    pub fn new(event: HookEvent, output_dir: PathBuf) -> Self {
+        Self {
+            event,
+            output_dir,
+            ..Default::default()
+        }
+    }
+
+    fn env(&self) -> Vec<(&'static str, String)> {
+        let playlists: Vec<String> = self.playlists.iter().map(|path| path.display().to_string()).collect();
+        vec![
+            ("RETROARCH_INDEXER_EVENT", self.event.as_str().to_string()),
+            // One path per line, since names contain spaces
+            ("RETROARCH_INDEXER_PLAYLISTS", playlists.join("\n")),
+            ("RETROARCH_INDEXER_SYSTEM", self.system.clone().unwrap_or_default()),
+            ("RETROARCH_INDEXER_ROMS", self.roms.to_string()),
+            ("RETROARCH_INDEXER_RESCANNED", self.rescanned.to_string()),
+            ("RETROARCH_INDEXER_REMOVED", self.removed.to_string()),
+            ("RETROARCH_INDEXER_OUTPUT_DIR", self.output_dir.display().to_string()),
+        ]
+    }
+}
+
+impl HookRunner {
+    pub fn new(config: HooksConfig) -> Self {
+        Self { config }
+    }
+
+    /// Whether any command is configured for `event`
+    pub fn has_hooks(&self, event: HookEvent) -> bool {
+        !self.commands(event).is_empty()
+    }
+
+    /// Run the commands for `context.event` one after another. Failures are
+    /// logged, or returned when the failure policy is `Abort`.
+    pub fn run(&self, context: &HookContext) -> Result<()> {
+        let commands = self.commands(context.event);
+        if commands.is_empty() {
+            return Ok(());
+        }
+
+        let input = serde_json::to_string(context)?;
+        for command in commands {
+            debug!("Executando hook {}: {}", context.event.as_str(), command);
+            match self.run_command(command, context, &input) {
+                Ok(()) => info!("Hook executado: {}", command),
+                Err(e) if self.config.on_failure == HookFailurePolicy::Abort => return Err(e.into()),
+                Err(e) => warn!("{}", e),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn commands(&self, event: HookEvent) -> &[String] {
+        match event {
+            HookEvent::PlaylistWritten => &self.config.after_playlist,
+            HookEvent::WatchBatch => &self.config.after_watch_batch,
+            HookEvent::IndexComplete => &self.config.after_index,
+        }
+    }
+
+    fn run_command(&self, command: &str, context: &HookContext, input: &str) -> Result<(), HookError> {
+        let mut child = shell(command)
+            .envs(context.env())
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|source| HookError::SpawnFailed { command: command.to_string(), source })?;
+
+        // Written from another thread so a hook that never reads its stdin
+        // can't block us past the timeout
+        if let Some(mut stdin) = child.stdin.take() {
+            let input = input.to_string();
+            std::thread::spawn(move || {
+                let _ = stdin.write_all(input.as_bytes());
+            });
+        }
+
+        let timeout = Duration::from_secs(self.config.timeout_secs);
+        let status = wait_with_timeout(&mut child, timeout)
+            .map_err(|source| HookError::SpawnFailed { command: command.to_string(), source })?;
+
+        match status {
+            Some(status) if status.success() => Ok(()),
+            Some(status) => Err(HookError::Failed { command: command.to_string(), status }),
+            None => {
+                kill_tree(&mut child);
+                Err(HookError::TimedOut { command: command.to_string(), secs: self.config.timeout_secs })
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    use std::os::unix::process::CommandExt;
+
+    // In its own process group, so a timeout also reaches what the hook started
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command).process_group(0);
+    shell
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+/// Kill a hook that ran past its timeout, together with its children
+#[cfg(unix)]
+fn kill_tree(child: &mut Child) {
+    // The group id is the shell's pid, see `shell`
+    let group = child.id() as libc::pid_t;
+    // SAFETY: kill(2) with a negative pid only signals that process group
+    if unsafe { libc::kill(-group, libc::SIGKILL) } != 0 {
+        let _ = child.kill();
+    }
+    let _ = child.wait();
+}
+
+#[cfg(windows)]
+fn kill_tree(child: &mut Child) {
+    let killed = Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &child.id().to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success());
+    if !killed {
+        let _ = child.kill();
+    }
+    let _ = child.wait();
+}
+
+/// Exit status of `child`, or None if it is still running after `timeout`
+fn wait_with_timeout(child: &mut Child, timeout: Duration) -> std::io::Result<Option<std::process::ExitStatus>> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            return Ok(None);
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn runner(commands: Vec<String>, timeout_secs: u64, on_failure: HookFailurePolicy) -> HookRunner {
+        HookRunner::new(HooksConfig {
+            after_watch_batch: commands,
+            timeout_secs,
+            on_failure,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_hook_receives_env_and_json() {
+        let dir = tempdir().unwrap();
+        let env_out = dir.path().join("env.txt");
+        let json_out = dir.path().join("context.json");
+        let command = format!(
+            "echo \"$RETROARCH_INDEXER_EVENT $RETROARCH_INDEXER_REMOVED\" > '{}'; cat > '{}'",
+            env_out.display(), json_out.display()
+        );
+
+        let mut context = HookContext::new(HookEvent::WatchBatch, dir.path().to_path_buf());
+        context.playlists = vec![dir.path().join("Nintendo - Game Boy.lpl")];
+        context.removed = 2;
+        runner(vec![command], 10, HookFailurePolicy::Abort).run(&context).unwrap();
+
+        assert_eq!(std::fs::read_to_string(&env_out).unwrap().trim(), "watch_batch 2");
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&json_out).unwrap()).unwrap();
+        assert_eq!(json["event"], "watch_batch");
+        assert_eq!(json["playlists"][0], dir.path().join("Nintendo - Game Boy.lpl").display().to_string());
+    }
+
+    #[test]
+    fn test_failure_policy_and_timeout() {
+        let context = HookContext::new(HookEvent::WatchBatch, PathBuf::from("playlists"));
+
+        assert!(runner(vec!["exit 3".to_string()], 10, HookFailurePolicy::Warn).run(&context).is_ok());
+        assert!(runner(vec!["exit 3".to_string()], 10, HookFailurePolicy::Abort).run(&context).is_err());
+
+        let started = Instant::now();
+        let error = runner(vec!["sleep 30".to_string()], 1, HookFailurePolicy::Abort).run(&context).unwrap_err();
+        assert!(matches!(error.downcast_ref::<HookError>(), Some(HookError::TimedOut { .. })));
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        // Processes started by a timed-out hook are killed with it
+        let dir = tempdir().unwrap();
+        let marker = dir.path().join("survived");
+        let command = format!("(sleep 2; touch '{}') & wait", marker.display());
+        let started = Instant::now();
+        assert!(runner(vec![command], 1, HookFailurePolicy::Abort).run(&context).is_err());
+        std::thread::sleep(Duration::from_secs(3).saturating_sub(started.elapsed()));
+        assert!(!marker.exists());
+
+        // Hooks of other events are not run
+        assert!(runner(vec!["exit 3".to_string()], 10, HookFailurePolicy::Abort)
+            .run(&HookContext::new(HookEvent::IndexComplete, PathBuf::new()))
+            .is_ok());
+    }
+}
@@ -4,11 +4,15 @@ pub mod playlist;
 pub mod crc32;
 pub mod dat_parser;
 pub mod core_mapper;
+pub mod core_info;
+pub mod retroarch_cfg;
 pub mod converter;
 pub mod platform;
 pub mod config;
 pub mod error;
 pub mod cache;
+pub mod cache_log;
+pub mod cache_audit;
 pub mod archive;
 pub mod thread_monitor;
 pub mod i18n;
@@ -16,12 +20,21 @@ pub mod mame_xml;
 
 #[cfg(feature = "watch-mode")]
 pub mod watch;
+#[cfg(feature = "watch-mode")]
+pub mod watch_control;
 
 #[cfg(feature = "dat-download")]
 pub mod dat_downloader;
 
 pub mod validator;
 pub mod deduplicator;
+pub mod rom_header;
+pub mod disc;
+pub mod disc_sheet;
+pub mod header_skipper;
+pub mod n64;
+pub mod system_registry;
+pub mod hooks;
 
 // Re-export main types for convenience
 pub use scanner::Scanner;
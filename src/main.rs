@@ -4,7 +4,7 @@ use colored::*;
 use dialoguer::{Input, Select, Confirm};
 use indicatif::{ProgressBar, ProgressStyle};
 use log::error;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 mod cli;
 mod scanner;
@@ -12,36 +12,58 @@ mod playlist;
 mod crc32;
 mod dat_parser;
 mod core_mapper;
+mod core_info;
+mod retroarch_cfg;
 mod converter;
 mod platform;
 mod config;
 mod error;
 mod cache;
+mod cache_log;
+mod cache_audit;
 mod archive;
 mod thread_monitor;
 mod i18n;
 mod mame_xml;
 #[cfg(feature = "watch-mode")]
 mod watch;
+#[cfg(feature = "watch-mode")]
+mod watch_control;
 #[cfg(feature = "dat-download")]
 mod dat_downloader;
 mod validator;
 mod deduplicator;
+mod rom_header;
+mod disc;
+mod disc_sheet;
+mod header_skipper;
+mod n64;
+mod system_registry;
+mod hooks;
 
 use cli::{Args, Commands, CacheAction, DeduplicationStrategy};
 use scanner::Scanner;
-use playlist::PlaylistBuilder;
+use playlist::{PlaylistBuilder, PlaylistFormat};
 use converter::PlaylistConverter;
-use platform::Platform;
+use platform::{Platform, PlatformPaths};
 use config::Config;
 
 #[cfg(feature = "watch-mode")]
-use watch::WatchService;
+use watch::{WatchBackend, WatchService};
+#[cfg(feature = "watch-mode")]
+use watch_control::{ControlRequest, ControlResponse};
+#[cfg(feature = "watch-mode")]
+use cli::WatchCtlAction;
 #[cfg(feature = "dat-download")]
 use dat_downloader::DatDownloader;
 use validator::RomValidator;
 use deduplicator::RomDeduplicator;
-use cache::CrcCache;
+use cache::{CrcCache, SharedCrcCache};
+use cache_audit::CacheAuditor;
+use hooks::{HookContext, HookEvent, HookRunner};
+use header_skipper::SkipperSet;
+use crc32::{HashAlgorithm, HashAlgorithms};
+use retroarch_cfg::RetroArchConfig;
 
 #[derive(Debug, Clone)]
 enum ExecutionMode {
@@ -57,6 +79,15 @@ struct ConsoleConfig {
     force_system: bool, // Force all ROMs to be of the selected system
 }
 
+/// Playlist written for one console of a multi-console run
+#[derive(Debug, Default)]
+struct ConsoleIndexed {
+    /// None when no ROMs were found
+    playlist: Option<PathBuf>,
+    roms: usize,
+    scanned: usize,
+}
+
 fn main() -> Result<()> {
     env_logger::init();
     
@@ -72,19 +103,32 @@ fn main() -> Result<()> {
         i18n::set_locale(language);
     }
     
+    // Scripts parse the JSON replies of watch-ctl, so keep stdout clean for them
+    #[cfg(feature = "watch-mode")]
+    let machine_output = matches!(args.command, Some(Commands::WatchCtl { json: true, .. }));
+    #[cfg(not(feature = "watch-mode"))]
+    let machine_output = false;
+
     // Print banner
-    print_banner();
+    if !machine_output {
+        print_banner();
+    }
     
     match &args.command {
-        Some(Commands::Convert { input, source, target, output_dir, validate_paths }) => {
-            handle_convert_command(input.clone(), *source, *target, output_dir.clone(), *validate_paths)?;
+        Some(Commands::Convert { input, source, target, output_dir, validate_paths, format }) => {
+            handle_convert_command(&args, input.clone(), *source, *target, output_dir.clone(), *validate_paths, *format)?;
         }
-        Some(Commands::ConvertAll { input_dir, source, target, output_dir, validate_paths }) => {
-            handle_convert_all_command(input_dir.clone(), *source, *target, output_dir.clone(), *validate_paths)?;
+        Some(Commands::ConvertAll { input_dir, source, target, output_dir, validate_paths, format }) => {
+            handle_convert_all_command(&args, input_dir.clone(), *source, *target, output_dir.clone(), *validate_paths, *format)?;
         }
         #[cfg(feature = "watch-mode")]
-        Some(Commands::Watch { debounce, batch_size, include_archives }) => {
-            handle_watch_command(args.clone(), *debounce, *batch_size, *include_archives)?;
+        Some(Commands::Watch { debounce, batch_size, include_archives, backend, poll_interval, daemon, socket }) => {
+            let socket = daemon.then(|| socket.clone().unwrap_or_else(watch_control::default_socket_path));
+            handle_watch_command(args.clone(), *debounce, *batch_size, *include_archives, *backend, *poll_interval, socket)?;
+        }
+        #[cfg(feature = "watch-mode")]
+        Some(Commands::WatchCtl { socket, json, action }) => {
+            handle_watch_ctl_command(socket.clone(), *json, action.clone())?;
         }
         #[cfg(feature = "dat-download")]
         Some(Commands::DownloadDats { output_dir, systems, force, timeout }) => {
@@ -96,6 +140,9 @@ fn main() -> Result<()> {
         Some(Commands::Deduplicate { strategy, priority_dirs, dry_run, backup, backup_dir, report }) => {
             handle_deduplicate_command(args.clone(), *strategy, priority_dirs.clone(), *dry_run, *backup, backup_dir.clone(), report.clone())?;
         }
+        Some(Commands::Normalize { keep_original, dry_run }) => {
+            handle_normalize_command(args.clone(), *keep_original, *dry_run)?;
+        }
         Some(Commands::Cache { action }) => {
             handle_cache_command(action.clone())?;
         }
@@ -234,7 +281,7 @@ fn prompt_for_output_dir() -> Result<PathBuf> {
     Ok(output_path)
 }
 
-fn handle_index_command(args: Args) -> Result<()> {
+fn handle_index_command(mut args: Args) -> Result<()> {
     if args.roms_dirs.is_empty() {
         eprintln!("{}", i18n::t("error-roms-dir-required").red());
         eprintln!("{}", i18n::t_with_arg("usage-instruction", "retroarch-indexer").cyan());
@@ -242,18 +289,30 @@ fn handle_index_command(args: Args) -> Result<()> {
     }
 
     // Load or create config
-    let config = Config::load_or_create(args.config.as_deref())?;
+    let mut config = Config::load_or_create(args.config.as_deref())?;
     
     // Determine platforms
     let (source_platform, target_platform) = determine_platforms(&args, &config)?;
+    let (source_paths, target_paths) = apply_retroarch_configs(&mut args, &mut config, source_platform, target_platform);
     
     println!("{}", i18n::t_path("scanning-directory", &format_paths(&args.roms_dirs)));
     println!("{}", i18n::t_conversion(&source_platform.display_name(), &target_platform.display_name()));
     println!("{}", i18n::t_count("threads-info", args.threads.unwrap_or_else(num_cpus::get) as i32));
     println!();
 
+    // Load DAT files if available; their <header> picks the header skipper
+    let dat_collection = if let Some(dat_dir) = &args.dat_dir {
+        dat_parser::DatCollection::load_directory(dat_dir)?
+    } else {
+        dat_parser::DatCollection::new()
+    };
+
     // Initialize scanner
+    let crc_cache = open_crc_cache();
     let scanner = Scanner::new()
+        .with_crc_cache(crc_cache.clone())
+        .with_header_skippers(header_skippers(&args, &config, &dat_collection.header_skippers()))
+        .with_hash_algorithms(hash_algorithms(&args, &config)?)
         .with_threads(args.threads.unwrap_or_else(num_cpus::get))
         .with_recursive(!args.no_recursive)
         .with_calculate_crc(!args.no_crc)
@@ -307,19 +366,26 @@ fn handle_index_command(args: Args) -> Result<()> {
     println!("{}", i18n::t_count("total-roms-stat", total_scanned_files as i32).bright_green());
     println!("{}", i18n::t_conversion(&source_platform.display_name(), &target_platform.display_name()).bright_yellow());
     println!("🧵 Threads: {}", args.threads.unwrap_or_else(num_cpus::get));
+    if let Ok(stats) = crc_cache.lock().get_stats()
+        && stats.total_requests > 0
+    {
+        println!("💾 Cache de CRC: {} reaproveitados, {} calculados", stats.cache_hits, stats.cache_misses);
+        if stats.relinked > 0 {
+            println!("   └─ {} reconhecidos após mover/renomear", stats.relinked);
+        }
+    }
+    report_incomplete_discs(&all_roms);
     println!();
 
-    // Load DAT files if available
-    let dat_collection = if let Some(dat_dir) = &args.dat_dir {
-        dat_parser::DatCollection::load_directory(dat_dir)?
-    } else {
-        dat_parser::DatCollection::new()
-    };
-
     // Build playlists
     let playlist_builder = PlaylistBuilder::new()
         .with_platforms(source_platform, target_platform)
+        .with_device_paths(source_paths, target_paths)
+        .with_format(args.playlist_format.unwrap_or(config.general.playlist_format))
         .with_dat_collection(dat_collection)
+        .with_m3u_generation(args.generate_m3u || config.general.generate_m3u)
+        .with_m3u_directory(args.m3u_dir.clone().or_else(|| config.paths.m3u_directory.clone()))
+        .with_core_mapper(configured_core_mapper(&args, &config))
         .with_verbose(args.verbose > 0);
 
     // Create output directory
@@ -327,6 +393,8 @@ fn handle_index_command(args: Args) -> Result<()> {
 
     // Generate individual playlists by system
     let playlists_by_system = playlist_builder.build_by_system(&all_roms)?;
+    let hooks = HookRunner::new(config.hooks.clone());
+    let mut written = Vec::new();
     
     println!("\n📊 Sistemas Detectados:");
     let mut total_roms = 0;
@@ -339,6 +407,13 @@ fn handle_index_command(args: Args) -> Result<()> {
         let filename = format!("{}.lpl", system_name);
         let output_path = args.output_dir.join(&filename);
         playlist.save(&output_path)?;
+        hooks.run(&HookContext {
+            playlists: vec![output_path.clone()],
+            system: Some(system_name.clone()),
+            roms: count,
+            ..HookContext::new(HookEvent::PlaylistWritten, args.output_dir.clone())
+        })?;
+        written.push(output_path);
     }
 
     // Generate master playlist if requested
@@ -346,6 +421,12 @@ fn handle_index_command(args: Args) -> Result<()> {
         let master_playlist = playlist_builder.build_master(&all_roms)?;
         let master_path = args.output_dir.join("roms.lpl");
         master_playlist.save(&master_path)?;
+        hooks.run(&HookContext {
+            playlists: vec![master_path.clone()],
+            roms: master_playlist.items.len(),
+            ..HookContext::new(HookEvent::PlaylistWritten, args.output_dir.clone())
+        })?;
+        written.push(master_path);
         
         println!("└─ {}: {} ROMs", 
             "Master Playlist".bright_cyan(), 
@@ -367,17 +448,26 @@ fn handle_index_command(args: Args) -> Result<()> {
         println!("\n{}", i18n::t_path("report-generated", &report_path.display().to_string()).bright_blue());
     }
 
+    hooks.run(&HookContext {
+        playlists: written,
+        roms: total_roms,
+        rescanned: total_scanned_files,
+        ..HookContext::new(HookEvent::IndexComplete, args.output_dir.clone())
+    })?;
+
     println!("\n{}", i18n::t("indexing-complete").bright_green().bold());
     
     Ok(())
 }
 
 fn handle_convert_command(
+    args: &Args,
     input: PathBuf,
     source: Option<Platform>,
     target: Platform,
     output_dir: Option<PathBuf>,
     validate_paths: bool,
+    format: Option<PlaylistFormat>,
 ) -> Result<()> {
     println!("{}", i18n::t("playlist-conversion-mode"));
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
@@ -398,10 +488,18 @@ fn handle_convert_command(
     };
 
     println!("✅ Plataforma detectada: {}", source_platform.display_name().bright_yellow());
+
+    let converter = converter.with_device_paths(
+        args.retroarch_cfg.as_deref().map(|path| device_paths(path, source_platform)).unwrap_or_default(),
+        args.target_retroarch_cfg.as_deref().map(|path| device_paths(path, target)).unwrap_or_default(),
+    );
     
     // Convert playlist
     println!("{}", i18n::t_with_arg("converting-to", &target.display_name().to_string()).bright_green());
-    let converted_playlist = converter.convert_playlist(&playlist, source_platform, target)?;
+    let mut converted_playlist = converter.convert_playlist(&playlist, source_platform, target)?;
+    if let Some(format) = format {
+        converted_playlist.format = format;
+    }
 
     // Determine output path
     let output_path = if let Some(dir) = output_dir {
@@ -423,17 +521,23 @@ fn handle_convert_command(
 }
 
 fn handle_convert_all_command(
+    args: &Args,
     input_dir: PathBuf,
     source: Platform,
     target: Platform,
     output_dir: Option<PathBuf>,
     validate_paths: bool,
+    format: Option<PlaylistFormat>,
 ) -> Result<()> {
     println!("{}", i18n::t("batch-conversion-mode"));
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
 
     let converter = PlaylistConverter::new()
-        .with_path_validation(validate_paths);
+        .with_path_validation(validate_paths)
+        .with_device_paths(
+            args.retroarch_cfg.as_deref().map(|path| device_paths(path, source)).unwrap_or_default(),
+            args.target_retroarch_cfg.as_deref().map(|path| device_paths(path, target)).unwrap_or_default(),
+        );
 
     // Find all .lpl files
     let lpl_files: Vec<_> = walkdir::WalkDir::new(&input_dir)
@@ -470,7 +574,10 @@ fn handle_convert_all_command(
         match converter.load_playlist(&lpl_file) {
             Ok(playlist) => {
                 match converter.convert_playlist(&playlist, source, target) {
-                    Ok(converted) => {
+                    Ok(mut converted) => {
+                        if let Some(format) = format {
+                            converted.format = format;
+                        }
                         let output_path = out_dir.join(format!("{} [{}].lpl", filename, target.display_name()));
                         if let Err(e) = converted.save(&output_path) {
                             error!("Erro ao salvar {}: {}", output_path.display(), e);
@@ -508,10 +615,13 @@ fn handle_convert_all_command(
 
 #[cfg(feature = "watch-mode")]
 fn handle_watch_command(
-    args: Args,
+    mut args: Args,
     debounce: u64,
     batch_size: usize,
     include_archives: bool,
+    backend: WatchBackend,
+    poll_interval: u64,
+    socket: Option<PathBuf>,
 ) -> Result<()> {
     println!("{}", i18n::t("watch-mode-active"));
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
@@ -521,15 +631,33 @@ fn handle_watch_command(
         std::process::exit(1);
     }
 
-    let config = Config::load_or_create(args.config.as_deref())?;
+    let mut config = Config::load_or_create(args.config.as_deref())?;
     let (source_platform, target_platform) = determine_platforms(&args, &config)?;
+    let (source_paths, target_paths) = apply_retroarch_configs(&mut args, &mut config, source_platform, target_platform);
 
     let mut watch_service = WatchService::new()
         .with_debounce_duration(std::time::Duration::from_millis(debounce))
         .with_batch_size(batch_size)
         .with_include_archives(include_archives)
+        .with_backend(backend)
+        .with_poll_interval(std::time::Duration::from_secs(poll_interval))
         .with_platforms(source_platform, target_platform)
-        .with_output_dir(args.output_dir.clone());
+        .with_device_paths(source_paths, target_paths)
+        .with_playlist_format(args.playlist_format.unwrap_or(config.general.playlist_format))
+        .with_output_dir(args.output_dir.clone())
+        .with_master_playlist(!args.skip_master)
+        .with_hooks(HookRunner::new(config.hooks.clone()))
+        .with_core_mapper(configured_core_mapper(&args, &config))
+        .with_scanner(
+            Scanner::new()
+                .with_crc_cache(open_crc_cache())
+                .with_header_skippers(header_skippers(&args, &config, &[]))
+                .with_hash_algorithms(hash_algorithms(&args, &config)?)
+                .with_threads(args.threads.unwrap_or_else(num_cpus::get))
+                .with_recursive(!args.no_recursive)
+                .with_calculate_crc(!args.no_crc)
+                .with_extensions(args.extensions.as_deref()),
+        );
 
     // Add watch directories
     for dir in &args.roms_dirs {
@@ -540,9 +668,16 @@ fn handle_watch_command(
     println!("\n{}", i18n::t("settings"));
     println!("├─ Debounce: {}ms", debounce);
     println!("├─ Batch Size: {} arquivos", batch_size);
-    println!("{}", i18n::t_with_arg("include-archives", if include_archives { &i18n::t("yes") } else { &i18n::t("no") }));
+    println!("├─ Backend: {:?} (polling a cada {}s)", backend, poll_interval);
+    println!("{}", i18n::t_with_arg("include-archives", &i18n::t(if include_archives { "yes" } else { "no" })));
     println!("└─ Output: {}", args.output_dir.display());
 
+    if let Some(socket) = socket {
+        println!("\n🔌 Daemon escutando em {}", socket.display().to_string().bright_blue());
+        println!("   Use `watch-ctl status` para consultar e `watch-ctl stop` para encerrar");
+        return watch_service.run_daemon(&socket);
+    }
+
     println!("\n{}", i18n::t("watch-active-press-ctrl-c").bright_green());
     
     // Start watching
@@ -551,6 +686,66 @@ fn handle_watch_command(
     Ok(())
 }
 
+#[cfg(feature = "watch-mode")]
+fn handle_watch_ctl_command(socket: Option<PathBuf>, json: bool, action: WatchCtlAction) -> Result<()> {
+    let socket = socket.unwrap_or_else(watch_control::default_socket_path);
+    // The daemon may run from another working directory
+    let absolute = |path: PathBuf| std::fs::canonicalize(&path).unwrap_or(path);
+
+    let request = match action {
+        WatchCtlAction::Status => ControlRequest::Status,
+        WatchCtlAction::Pause => ControlRequest::Pause,
+        WatchCtlAction::Resume => ControlRequest::Resume,
+        WatchCtlAction::Add { path } => ControlRequest::Watch { path: absolute(path) },
+        WatchCtlAction::Remove { path } => ControlRequest::Unwatch { path: absolute(path) },
+        WatchCtlAction::Rescan => ControlRequest::Rescan,
+        WatchCtlAction::Stop => ControlRequest::Stop,
+    };
+
+    let response = watch_control::send(&socket, &request)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&response)?);
+    } else {
+        match &response {
+            ControlResponse::Ok { message } => println!("✅ {}", message),
+            ControlResponse::Error { message } => eprintln!("{}", format!("❌ {}", message).red()),
+            ControlResponse::Status(status) => {
+                let when = |time: Option<chrono::DateTime<chrono::Utc>>| {
+                    time.map(|time| time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+                        .unwrap_or_else(|| "nunca".to_string())
+                };
+                println!("👁️  Daemon de monitoramento");
+                let state = if status.rebuilding {
+                    "varredura completa em andamento"
+                } else if status.paused {
+                    "pausado"
+                } else {
+                    "ativo"
+                };
+                println!("├─ Estado: {}", state);
+                println!("├─ Diretórios monitorados: {}", status.watched_directories.len());
+                for dir in &status.watched_directories {
+                    let mode = if status.polled_directories.contains(dir) { " (polling)" } else { "" };
+                    println!("│  • {}{}", dir.display(), mode);
+                }
+                println!("├─ Eventos na fila: {}", status.queued_events);
+                println!("├─ Varreduras agendadas: {}", status.scheduled_scans);
+                println!("├─ Arquivos indexados: {}", status.indexed_files);
+                println!("├─ Atualizações aplicadas: {}", status.updates_applied);
+                println!("├─ Iniciado em: {}", when(status.started_at));
+                println!("├─ Última varredura completa: {}", when(status.last_rebuild));
+                println!("├─ Última atualização: {}", when(status.last_update));
+                println!("└─ Último erro: {}", status.last_error.as_deref().unwrap_or("nenhum"));
+            }
+        }
+    }
+
+    if matches!(response, ControlResponse::Error { .. }) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
 #[cfg(feature = "dat-download")]
 fn handle_download_dats_command(
     output_dir: PathBuf,
@@ -633,6 +828,8 @@ fn handle_validate_command(
         std::process::exit(1);
     }
 
+    let config = Config::load_or_create(args.config.as_deref())?;
+
     // Load validator
     let mut validator = RomValidator::new();
 
@@ -662,7 +859,13 @@ fn handle_validate_command(
     }
 
     // Scan ROMs
+    let skippers = header_skippers(&args, &config, &validator.header_skipper_names());
+    let validator = validator.with_header_skippers(skippers.clone());
+    let crc_cache = open_crc_cache();
     let scanner = Scanner::new()
+        .with_crc_cache(crc_cache.clone())
+        .with_header_skippers(skippers)
+        .with_hash_algorithms(hash_algorithms(&args, &config)?)
         .with_threads(args.threads.unwrap_or_else(num_cpus::get))
         .with_recursive(!args.no_recursive)
         .with_calculate_crc(true); // Always calculate CRC for validation
@@ -696,6 +899,25 @@ fn handle_validate_command(
     println!("{}", i18n::t_count("validation-bad-dumps", validation_report.bad_dumps as i32));
     println!("{}", i18n::t_count("validation-corrupted", validation_report.corrupted_roms as i32));
 
+    let headerless_matches = validation_report.matched_variants.values()
+        .filter(|variant| **variant == crc32::CrcVariant::Headerless)
+        .count();
+    if headerless_matches > 0 {
+        println!("🧩 {} ROMs identificadas pelo CRC sem header", headerless_matches);
+    }
+
+    for matched_by in [dat_parser::MatchedBy::Sha1, dat_parser::MatchedBy::Md5, dat_parser::MatchedBy::Size] {
+        let count = validation_report.matched_by.values().filter(|by| **by == matched_by).count();
+        if count > 0 {
+            println!("🔐 {} ROMs confirmadas por {}", count, matched_by.describe());
+        }
+    }
+
+    // Remember which CRC variant matched each ROM
+    if let Err(e) = record_matched_variants(&crc_cache, &all_roms, &validation_report.matched_variants) {
+        log::warn!("Falha ao atualizar cache de CRC: {}", e);
+    }
+
     // Generate detailed report if requested
     if let Some(report_path) = report {
         validator.generate_report(&all_roms, &report_path)?;
@@ -705,6 +927,64 @@ fn handle_validate_command(
     Ok(())
 }
 
+fn record_matched_variants(
+    crc_cache: &SharedCrcCache,
+    roms: &[scanner::RomFile],
+    matched_variants: &std::collections::HashMap<PathBuf, crc32::CrcVariant>,
+) -> Result<()> {
+    if matched_variants.is_empty() {
+        return Ok(());
+    }
+
+    let mut cache = crc_cache.lock();
+    for rom in roms {
+        if let Some(variant) = matched_variants.get(&rom.playlist_path()) {
+            cache.record_matched_variant(&rom.cache_key(), *variant);
+        }
+    }
+    cache.auto_save()
+}
+
+/// Persistent CRC cache shared by every command that hashes ROMs
+fn open_crc_cache() -> SharedCrcCache {
+    CrcCache::with_default_location()
+        .unwrap_or_else(|e| {
+            log::warn!("Cache de CRC indisponível, usando cache em memória: {}", e);
+            CrcCache::new()
+        })
+        .into_shared()
+}
+
+/// Built-in header skippers plus the XMLs from `--header-skippers` or
+/// `paths.header_skipper_directory`, trying the ones the DATs name first
+fn header_skippers(args: &Args, config: &Config, dat_skippers: &[String]) -> SkipperSet {
+    let directory = args.header_skippers.as_ref().or(config.paths.header_skipper_directory.as_ref());
+    let mut skippers = match directory {
+        Some(dir) => SkipperSet::load_directory(dir).unwrap_or_else(|e| {
+            log::warn!("{:#}", e);
+            SkipperSet::builtin()
+        }),
+        None => SkipperSet::builtin(),
+    };
+
+    for missing in skippers.select(dat_skippers) {
+        println!("⚠️  Header skipper {} citado nos DATs não encontrado (use --header-skippers)", missing.yellow());
+    }
+    skippers
+}
+
+/// Digests from `--hash` or `general.hash_algorithms`, always with CRC32
+fn hash_algorithms(args: &Args, config: &Config) -> Result<HashAlgorithms> {
+    let Some(requested) = args.hash_algorithms.as_ref().or(config.general.hash_algorithms.as_ref()) else {
+        return Ok(HashAlgorithms::default());
+    };
+    let algorithms: HashAlgorithms = requested.iter().copied()
+        .chain(std::iter::once(HashAlgorithm::Crc32))
+        .collect();
+    algorithms.ensure_available()?;
+    Ok(algorithms)
+}
+
 fn handle_deduplicate_command(
     args: Args,
     strategy: DeduplicationStrategy,
@@ -745,7 +1025,12 @@ fn handle_deduplicate_command(
     }
 
     // Scan for ROMs
+    let config = Config::load_or_create(args.config.as_deref())?;
+    let crc_cache = open_crc_cache();
     let scanner = Scanner::new()
+        .with_crc_cache(crc_cache.clone())
+        .with_header_skippers(header_skippers(&args, &config, &[]))
+        .with_hash_algorithms(hash_algorithms(&args, &config)?)
         .with_threads(args.threads.unwrap_or_else(num_cpus::get))
         .with_recursive(!args.no_recursive)
         .with_calculate_crc(true); // Need CRC for duplicate detection
@@ -796,6 +1081,68 @@ fn handle_deduplicate_command(
     Ok(())
 }
 
+fn handle_normalize_command(args: Args, keep_original: bool, dry_run: bool) -> Result<()> {
+    println!("🔄 Normalização de ROMs de N64 para .z64");
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+
+    if args.roms_dirs.is_empty() {
+        eprintln!("{}", i18n::t("error-roms-dir-required").red());
+        std::process::exit(1);
+    }
+
+    let normalizer = n64::N64Normalizer::new()
+        .with_keep_original(keep_original)
+        .with_dry_run(dry_run);
+    if dry_run {
+        println!("{}", i18n::t("simulation-mode-active"));
+    }
+
+    let max_depth = if args.no_recursive { 1 } else { usize::MAX };
+    let (mut converted, mut skipped, mut failed) = (0, 0, 0);
+
+    for roms_dir in &args.roms_dirs {
+        println!("🔍 Escaneando: {}", roms_dir.display());
+        let files = walkdir::WalkDir::new(roms_dir)
+            .max_depth(max_depth)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file())
+            .filter(|entry| entry.path().extension()
+                .is_some_and(|ext| n64::is_n64_extension(&ext.to_string_lossy().to_lowercase())));
+
+        for entry in files {
+            let path = entry.path();
+            match normalizer.normalize_file(path) {
+                Ok(n64::NormalizeOutcome::Converted { target, from, crc32 }) => {
+                    converted += 1;
+                    println!("✅ {} ({}) -> {} [CRC32 {:08X}]", path.display(), from.describe(), target.display(), crc32);
+                }
+                Ok(n64::NormalizeOutcome::TargetExists(target)) => {
+                    skipped += 1;
+                    println!("⚠️  {} já existe, ignorando {}", target.display(), path.display());
+                }
+                Ok(n64::NormalizeOutcome::NotN64) => {
+                    skipped += 1;
+                    log::warn!("Cabeçalho de N64 não reconhecido: {}", path.display());
+                }
+                Ok(n64::NormalizeOutcome::AlreadyNormalized) => {}
+                Err(e) => {
+                    failed += 1;
+                    eprintln!("{} {}: {}", "❌".red(), path.display(), e);
+                }
+            }
+        }
+    }
+
+    println!("\n📊 Convertidas: {} | Ignoradas: {} | Falhas: {}", converted, skipped, failed);
+
+    if failed > 0 {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
 fn handle_cache_command(action: CacheAction) -> Result<()> {
     println!("🗄️  Gerenciamento de Cache");
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
@@ -817,12 +1164,51 @@ fn handle_cache_command(action: CacheAction) -> Result<()> {
                     (stats.cache_hits as f64 / stats.total_requests as f64) * 100.0 
                 } else { 0.0 });
             println!("├─ Misses: {}", stats.cache_misses);
+            println!("├─ Religadas após mover/renomear: {}", stats.relinked_entries);
             println!("└─ Last updated: {}", stats.last_updated);
         }
         CacheAction::Clean { max_age } => {
             let removed = cache.clean_old_entries(max_age)?;
             println!("✅ {} entradas antigas removidas (mais de {} dias)", removed, max_age);
         }
+        CacheAction::Verify { sample, seed, report } => {
+            let mut auditor = CacheAuditor::new(&cache);
+            if let Some(sample) = sample {
+                auditor = auditor.with_sample(sample);
+            }
+            if let Some(seed) = seed {
+                auditor = auditor.with_seed(seed);
+            }
+
+            println!("🔍 Verificando arquivos em cache...");
+            let audit = auditor.run();
+
+            println!("📊 Verificação do cache:");
+            println!("├─ Verificados: {}", audit.checked);
+            println!("├─ Íntegros: {}", audit.verified);
+            println!("├─ Alterados (tamanho/data): {}", audit.modified);
+            println!("├─ Ausentes: {}", audit.missing);
+            println!("├─ Dentro de arquivos compactados (ignorados): {}", audit.skipped);
+            println!("├─ Erros de leitura: {}", audit.errors.len());
+            println!("└─ Corrompidos: {}", audit.mismatches.len());
+
+            for mismatch in &audit.mismatches {
+                println!("   ❌ {} (CRC32 {} → {})", mismatch.path.display(), mismatch.expected_crc32, mismatch.actual_crc32);
+            }
+            for error in &audit.errors {
+                println!("   ⚠️  {}: {}", error.path.display(), error.error);
+            }
+
+            if let Some(report_path) = report {
+                let json = serde_json::to_string_pretty(&audit)?;
+                std::fs::write(&report_path, json)?;
+                println!("\n📄 Relatório salvo em {}", report_path.display());
+            }
+
+            if audit.has_failures() {
+                std::process::exit(1);
+            }
+        }
     }
 
     Ok(())
@@ -841,6 +1227,97 @@ fn format_size(bytes: u64) -> String {
     format!("{:.1} {}", size, UNITS[unit_index])
 }
 
+/// Core mapping with the `[cores]` settings and `--core-paths` applied
+fn configured_core_mapper(args: &Args, config: &Config) -> core_mapper::CoreMapper {
+    let mut mapper = core_mapper::CoreMapper::new()
+        .with_core_paths(args.core_paths || config.cores.write_core_paths)
+        .with_overrides(config.cores.override_system_cores.clone())
+        .with_cores_directories(config.cores.custom_core_paths.clone());
+
+    let info_dir = args.core_info_dir.clone()
+        .or_else(|| config.cores.info_directory.clone())
+        .or_else(core_info::CoreInfoRepository::discover);
+    let Some(info_dir) = info_dir else {
+        return mapper;
+    };
+
+    match core_info::CoreInfoRepository::load_directory(&info_dir) {
+        Ok(mut repository) => {
+            if let Some(installed_dir) = &config.cores.installed_cores_directory {
+                match repository.mark_installed(installed_dir) {
+                    Ok(count) => log::info!("{} cores instalados em {}", count, installed_dir.display()),
+                    Err(e) => log::warn!("{:#}", e),
+                }
+            }
+            log::info!("{} arquivos .info de cores carregados de {}", repository.len(), info_dir.display());
+            mapper = mapper.with_core_info(&repository);
+        }
+        Err(e) => log::warn!("{:#}", e),
+    }
+
+    mapper
+}
+
+/// Defaults from `retroarch.cfg`: the directories of the local install when
+/// it is also the target, plus the ROM and core directories of both devices
+/// for path conversion
+fn apply_retroarch_configs(
+    args: &mut Args,
+    config: &mut Config,
+    source: Platform,
+    target: Platform,
+) -> (PlatformPaths, PlatformPaths) {
+    let mut source_paths = PlatformPaths::default();
+    let named_cfg = args.retroarch_cfg.clone().or_else(|| config.paths.retroarch_config.clone());
+    let discovered = named_cfg.is_none();
+    let local_cfg = named_cfg.or_else(RetroArchConfig::discover);
+
+    if let Some(path) = local_cfg {
+        match RetroArchConfig::load(&path) {
+            Ok(retroarch) => {
+                println!("⚙️  Configuração do RetroArch: {}", path.display().to_string().bright_blue());
+                if source == target {
+                    retroarch.apply_to(config, target);
+                    // A discovered install only lends its cores; playlists go
+                    // there only when the user pointed at its cfg
+                    if !discovered {
+                        retroarch.apply_playlist_directory(config);
+                        if args.output_dir == Path::new("./playlists") {
+                            args.output_dir = config.paths.output_directory.clone();
+                        }
+                    }
+                } else if let Some(info) = retroarch.libretro_info_path() {
+                    // The .info files describe the cores of every platform
+                    config.cores.info_directory.get_or_insert_with(|| PathBuf::from(info));
+                }
+                source_paths = retroarch.platform_paths();
+            }
+            Err(e) => log::warn!("{:#}", e),
+        }
+    }
+
+    let target_paths = args.target_retroarch_cfg.clone()
+        .or_else(|| config.paths.target_retroarch_config.clone())
+        .map(|path| device_paths(&path, target))
+        .unwrap_or_default();
+    if let Some(cores) = &target_paths.cores {
+        config.cores.custom_core_paths.entry(target).or_insert_with(|| PathBuf::from(cores));
+    }
+
+    (source_paths, target_paths)
+}
+
+/// ROM and core directories from a `retroarch.cfg` copied from a device
+fn device_paths(path: &Path, platform: Platform) -> PlatformPaths {
+    match RetroArchConfig::load_from_device(path, platform) {
+        Ok(retroarch) => retroarch.platform_paths(),
+        Err(e) => {
+            log::warn!("{:#}", e);
+            PlatformPaths::default()
+        }
+    }
+}
+
 fn determine_platforms(args: &Args, config: &Config) -> Result<(Platform, Platform)> {
     let source = args.source_platform
         .or(config.general.source_platform)
@@ -903,6 +1380,25 @@ fn format_paths(paths: &[PathBuf]) -> String {
     }
 }
 
+/// List CUE/GDI sheets whose referenced tracks are missing on disk
+fn report_incomplete_discs(roms: &[scanner::RomFile]) {
+    let incomplete: Vec<_> = roms.iter()
+        .filter(|rom| !rom.missing_tracks.is_empty())
+        .collect();
+
+    if incomplete.is_empty() {
+        return;
+    }
+
+    println!("⚠️  {} disco(s) com faixas ausentes:", incomplete.len().to_string().yellow());
+    for rom in incomplete {
+        println!("   {}", rom.path.display().to_string().yellow());
+        for track in &rom.missing_tracks {
+            println!("      - {}", track.display());
+        }
+    }
+}
+
 fn generate_report(
     _all_roms: &[scanner::RomFile], 
     _playlists: &std::collections::HashMap<String, playlist::Playlist>,
@@ -1054,43 +1550,42 @@ fn handle_interactive_console_selection(args: &mut Args) -> Result<()> {
     let total_threads = args.threads.unwrap_or_else(num_cpus::get);
     let consoles_count = console_configs.len();
     
-    if consoles_count == 1 {
+    // One cache for the whole run: the cache file is locked by its first
+    // opener, so a cache per console would leave the others read-only
+    let crc_cache = open_crc_cache();
+    let indexed = if consoles_count == 1 {
         // Single console: use all available threads
         let config = &console_configs[0];
         println!("\n{} Processando: {} (usando {} threads)", "🔄".bright_blue(), config.system_name.bright_green(), total_threads);
-        process_single_console_config(args, config)?;
+        vec![process_single_console_config(args, config, &crc_cache)?]
     } else if consoles_count <= 4 && total_threads >= 8 {
         // Multiple consoles with sufficient threads: process in parallel
         println!("\n{} Processamento paralelo ativado para {} consoles", "⚡".bright_yellow(), consoles_count);
-        process_consoles_parallel(args, console_configs)?;
+        process_consoles_parallel(args, console_configs, &crc_cache)?
     } else {
         // Multiple consoles but limited threads: process sequentially  
         println!("\n{} Processamento sequencial para {} consoles", "🔄".bright_blue(), consoles_count);
-        process_consoles_sequential(args, console_configs)?;
-    }
+        process_consoles_sequential(args, console_configs, &crc_cache)?
+    };
+
+    // after_index hooks run once for the whole run, not once per console
+    let config = Config::load_or_create(args.config.as_deref())?;
+    HookRunner::new(config.hooks).run(&HookContext {
+        playlists: indexed.iter().filter_map(|console| console.playlist.clone()).collect(),
+        roms: indexed.iter().map(|console| console.roms).sum(),
+        rescanned: indexed.iter().map(|console| console.scanned).sum(),
+        ..HookContext::new(HookEvent::IndexComplete, args.output_dir.clone())
+    })?;
     
     println!("\n{}", i18n::t("processing-all-consoles-complete").bright_green().bold());
     Ok(())
 }
 
 /// Get list of available systems from core mapper
-fn get_available_sistemas(_core_mapper: &core_mapper::CoreMapper) -> Vec<String> {
-    // Since get_supported_systems() is not available, let's create manually
-    vec![
-        "Nintendo - Nintendo 64".to_string(),
-        "Nintendo - Super Nintendo Entertainment System".to_string(),
-        "Nintendo - Game Boy Advance".to_string(),
-        "Nintendo - Game Boy Color".to_string(),
-        "Nintendo - Game Boy".to_string(),
-        "Nintendo - Nintendo Entertainment System".to_string(),
-        "Sega - Mega Drive - Genesis".to_string(),
-        "Sega - 32X".to_string(),
-        "Sega - Master System - Mark III".to_string(),
-        "Sega - Game Gear".to_string(),
-        "Sony - PlayStation".to_string(),
-        "MAME".to_string(),
-        "Arcade".to_string(),
-    ]
+fn get_available_sistemas(core_mapper: &core_mapper::CoreMapper) -> Vec<String> {
+    let mut systems = core_mapper.get_supported_systems();
+    systems.sort();
+    systems
 }
 
 /// Prompt for system selection
@@ -1227,7 +1722,7 @@ fn prompt_for_console_config(system_name: &str) -> Result<ConsoleConfig> {
 }
 
 /// Function for indexing with forced system (interactive mode)
-fn handle_index_command_forced_system(args: Args, forced_system: &str) -> Result<()> {
+fn handle_index_command_forced_system(mut args: Args, forced_system: &str, crc_cache: &SharedCrcCache) -> Result<ConsoleIndexed> {
     if args.roms_dirs.is_empty() {
         eprintln!("{}", i18n::t("error-roms-dir-required").red());
         eprintln!("{}", i18n::t_with_arg("usage-instruction", "retroarch-indexer").cyan());
@@ -1235,18 +1730,29 @@ fn handle_index_command_forced_system(args: Args, forced_system: &str) -> Result
     }
 
     // Load or create config
-    let config = Config::load_or_create(args.config.as_deref())?;
+    let mut config = Config::load_or_create(args.config.as_deref())?;
     
     // Determine platforms
     let (source_platform, target_platform) = determine_platforms(&args, &config)?;
+    let (source_paths, target_paths) = apply_retroarch_configs(&mut args, &mut config, source_platform, target_platform);
     
     println!("{}", i18n::t_path("scanning-directory", &format_paths(&args.roms_dirs)));
     println!("{}", i18n::t_with_arg("forced-system", forced_system).bright_cyan());    println!("{}", i18n::t_conversion(&source_platform.display_name(), &target_platform.display_name()).bright_yellow());
     println!("🧵 Threads: {}", args.threads.unwrap_or_else(num_cpus::get));
     println!();
 
+    // Load DAT files if available; their <header> picks the header skipper
+    let dat_collection = if let Some(dat_dir) = &args.dat_dir {
+        dat_parser::DatCollection::load_directory(dat_dir)?
+    } else {
+        dat_parser::DatCollection::new()
+    };
+
     // Initialize scanner
     let scanner = Scanner::new()
+        .with_crc_cache(crc_cache.clone())
+        .with_header_skippers(header_skippers(&args, &config, &dat_collection.header_skippers()))
+        .with_hash_algorithms(hash_algorithms(&args, &config)?)
         .with_threads(args.threads.unwrap_or_else(num_cpus::get))
         .with_recursive(!args.no_recursive)
         .with_calculate_crc(!args.no_crc)
@@ -1299,7 +1805,7 @@ fn handle_index_command_forced_system(args: Args, forced_system: &str) -> Result
 
     if all_roms.is_empty() {
         println!("{}", i18n::t("no-roms-found").yellow());
-        return Ok(());
+        return Ok(ConsoleIndexed::default());
     }
 
     // Show scanning summary
@@ -1313,17 +1819,15 @@ fn handle_index_command_forced_system(args: Args, forced_system: &str) -> Result
     println!("└─ Threads utilizadas: {}", args.threads.unwrap_or_else(num_cpus::get).to_string().bright_yellow());
     println!();
 
-    // Load DAT files if available
-    let dat_collection = if let Some(dat_dir) = &args.dat_dir {
-        dat_parser::DatCollection::load_directory(dat_dir)?
-    } else {
-        dat_parser::DatCollection::new()
-    };
-
     // Build playlists - only for forced system
     let playlist_builder = PlaylistBuilder::new()
         .with_platforms(source_platform, target_platform)
+        .with_device_paths(source_paths, target_paths)
+        .with_format(args.playlist_format.unwrap_or(config.general.playlist_format))
         .with_dat_collection(dat_collection)
+        .with_m3u_generation(args.generate_m3u || config.general.generate_m3u)
+        .with_m3u_directory(args.m3u_dir.clone().or_else(|| config.paths.m3u_directory.clone()))
+        .with_core_mapper(configured_core_mapper(&args, &config))
         .with_verbose(args.verbose > 0);
 
     // Create output directory
@@ -1337,8 +1841,17 @@ fn handle_index_command_forced_system(args: Args, forced_system: &str) -> Result
     // Save playlist
     let filename = format!("{}.lpl", forced_system);
     let output_path = args.output_dir.join(&filename);
+    let hooks = HookRunner::new(config.hooks.clone());
+    let mut total_roms = 0;
     if let Some(playlist) = playlists_by_system.get(forced_system) {
         playlist.save(&output_path)?;
+        total_roms = playlist.items.len();
+        hooks.run(&HookContext {
+            playlists: vec![output_path.clone()],
+            system: Some(forced_system.to_string()),
+            roms: total_roms,
+            ..HookContext::new(HookEvent::PlaylistWritten, args.output_dir.clone())
+        })?;
         
         println!("📊 Sistema Processado:");
         println!("└─ {}: {} ROMs", forced_system.bright_white(), playlist.items.len().to_string().bright_green());
@@ -1355,25 +1868,39 @@ fn handle_index_command_forced_system(args: Args, forced_system: &str) -> Result
     if let Some(report_path) = &args.report {
         generate_report(&all_roms, &playlists_by_system, report_path)?;
     }
-    
-    Ok(())
+
+    Ok(ConsoleIndexed {
+        playlist: Some(output_path),
+        roms: total_roms,
+        scanned: total_scanned_files,
+    })
 }
 
 /// Versão otimizada do handle_index_command_forced_system para modo paralelo
-/// Retorna o número de ROMs processadas para estatísticas
-fn handle_index_command_forced_system_optimized(args: Args, forced_system: &str) -> Result<usize> {
+fn handle_index_command_forced_system_optimized(mut args: Args, forced_system: &str, crc_cache: &SharedCrcCache) -> Result<ConsoleIndexed> {
     if args.roms_dirs.is_empty() {
         return Err(anyhow::anyhow!("Diretório de ROMs não especificado"));
     }
 
     // Load or create config
-    let config = Config::load_or_create(args.config.as_deref())?;
+    let mut config = Config::load_or_create(args.config.as_deref())?;
     
     // Determine platforms
     let (source_platform, target_platform) = determine_platforms(&args, &config)?;
+    let (source_paths, target_paths) = apply_retroarch_configs(&mut args, &mut config, source_platform, target_platform);
+
+    // Load DAT files if available; their <header> picks the header skipper
+    let dat_collection = if let Some(dat_dir) = &args.dat_dir {
+        dat_parser::DatCollection::load_directory(dat_dir)?
+    } else {
+        dat_parser::DatCollection::new()
+    };
 
     // Initialize scanner with simplified monitoring (no verbose progress bars)
     let scanner = Scanner::new()
+        .with_crc_cache(crc_cache.clone())
+        .with_header_skippers(header_skippers(&args, &config, &dat_collection.header_skippers()))
+        .with_hash_algorithms(hash_algorithms(&args, &config)?)
         .with_threads(args.threads.unwrap_or_else(num_cpus::get))
         .with_recursive(!args.no_recursive)
         .with_calculate_crc(!args.no_crc)
@@ -1395,20 +1922,18 @@ fn handle_index_command_forced_system_optimized(args: Args, forced_system: &str)
     }
 
     if all_roms.is_empty() {
-        return Ok(0);
+        return Ok(ConsoleIndexed::default());
     }
 
-    // Load DAT files if available
-    let dat_collection = if let Some(dat_dir) = &args.dat_dir {
-        dat_parser::DatCollection::load_directory(dat_dir)?
-    } else {
-        dat_parser::DatCollection::new()
-    };
-
     // Build playlists - only for forced system
     let playlist_builder = PlaylistBuilder::new()
         .with_platforms(source_platform, target_platform)
+        .with_device_paths(source_paths, target_paths)
+        .with_format(args.playlist_format.unwrap_or(config.general.playlist_format))
         .with_dat_collection(dat_collection)
+        .with_m3u_generation(args.generate_m3u || config.general.generate_m3u)
+        .with_m3u_directory(args.m3u_dir.clone().or_else(|| config.paths.m3u_directory.clone()))
+        .with_core_mapper(configured_core_mapper(&args, &config))
         .with_verbose(false); // Desabilita verbose para performance
 
     // Create output directory
@@ -1421,12 +1946,23 @@ fn handle_index_command_forced_system_optimized(args: Args, forced_system: &str)
     let filename = format!("{}.lpl", forced_system);
     let output_path = args.output_dir.join(&filename);
     playlist.save(&output_path)?;
-    
-    Ok(all_roms.len())
+
+    HookRunner::new(config.hooks.clone()).run(&HookContext {
+        playlists: vec![output_path.clone()],
+        system: Some(forced_system.to_string()),
+        roms: playlist.items.len(),
+        ..HookContext::new(HookEvent::PlaylistWritten, args.output_dir.clone())
+    })?;
+    
+    Ok(ConsoleIndexed {
+        playlist: Some(output_path),
+        roms: playlist.items.len(),
+        scanned: all_roms.len(),
+    })
 }
 
 /// Process a single console configuration (uses all available threads)
-fn process_single_console_config(args: &Args, config: &ConsoleConfig) -> Result<()> {
+fn process_single_console_config(args: &Args, config: &ConsoleConfig, crc_cache: &SharedCrcCache) -> Result<ConsoleIndexed> {
     // Configure args temporarily for this console
     let mut temp_args = args.clone();
     temp_args.roms_dirs = vec![config.roms_dir.clone()];
@@ -1438,18 +1974,20 @@ fn process_single_console_config(args: &Args, config: &ConsoleConfig) -> Result<
     }
     
     // Execute indexing for this specific console
-    match handle_index_command_forced_system(temp_args, &config.system_name) {
-        Ok(_) => println!("  {} {}", "✅".green(), i18n::t("completed-successfully").bright_green()),
+    match handle_index_command_forced_system(temp_args, &config.system_name, crc_cache) {
+        Ok(indexed) => {
+            println!("  {} {}", "✅".green(), i18n::t("completed-successfully").bright_green());
+            Ok(indexed)
+        }
         Err(e) => {
             eprintln!("  {}", i18n::t_with_arg("error-processing-system", &format!("{}: {}", config.system_name, e)).red());
-            return Err(e);
+            Err(e)
         }
     }
-    Ok(())
 }
 
 /// Process multiple consoles in parallel (when sufficient threads available)
-fn process_consoles_parallel(args: &Args, console_configs: Vec<ConsoleConfig>) -> Result<()> {
+fn process_consoles_parallel(args: &Args, console_configs: Vec<ConsoleConfig>, crc_cache: &SharedCrcCache) -> Result<Vec<ConsoleIndexed>> {
     use rayon::prelude::*;
     
     let total_threads = args.threads.unwrap_or_else(num_cpus::get);
@@ -1465,7 +2003,7 @@ fn process_consoles_parallel(args: &Args, console_configs: Vec<ConsoleConfig>) -
         .map_err(|e| anyhow::anyhow!("Falha ao criar thread pool: {}", e))?;
     
     // Process consoles in parallel using custom pool
-    let results: Vec<Result<()>> = pool.install(|| {
+    let results: Vec<Result<ConsoleIndexed>> = pool.install(|| {
         console_configs
             .par_iter()
             .map(|config| {
@@ -1488,15 +2026,15 @@ fn process_consoles_parallel(args: &Args, console_configs: Vec<ConsoleConfig>) -
                 
                 // Execute indexing for this specific console
                 let start_time = std::time::Instant::now();
-                match handle_index_command_forced_system_optimized(temp_args, &config.system_name) {
-                    Ok(rom_count) => {
+                match handle_index_command_forced_system_optimized(temp_args, &config.system_name, crc_cache) {
+                    Ok(indexed) => {
                         let duration = start_time.elapsed();
                         println!("  ✅ [T{}] {}: {} ROMs em {:.2}s", 
                                 thread_id, 
                                 config.system_name.bright_green(), 
-                                rom_count, 
+                                indexed.scanned, 
                                 duration.as_secs_f32());
-                        Ok(())
+                        Ok(indexed)
                     },
                     Err(e) => {
                         eprintln!("  ❌ [T{}] Erro em {}: {}", thread_id, config.system_name.bright_red(), e);
@@ -1508,15 +2046,12 @@ fn process_consoles_parallel(args: &Args, console_configs: Vec<ConsoleConfig>) -
     });
     
     // Check for errors
-    for result in results {
-        result?;
-    }
-    
-    Ok(())
+    results.into_iter().collect()
 }
 
 /// Process multiple consoles sequentially (when limited threads)
-fn process_consoles_sequential(args: &Args, console_configs: Vec<ConsoleConfig>) -> Result<()> {
+fn process_consoles_sequential(args: &Args, console_configs: Vec<ConsoleConfig>, crc_cache: &SharedCrcCache) -> Result<Vec<ConsoleIndexed>> {
+    let mut indexed = Vec::new();
     for config in console_configs {
         println!("\n{} Processando: {}", "🔄".bright_blue(), config.system_name.bright_green());
         
@@ -1531,13 +2066,55 @@ fn process_consoles_sequential(args: &Args, console_configs: Vec<ConsoleConfig>)
         }
         
         // Execute indexing for this specific console
-        match handle_index_command_forced_system(temp_args, &config.system_name) {
-            Ok(_) => println!("  {} {}", "✅".green(), i18n::t("completed-successfully").bright_green()),
+        match handle_index_command_forced_system(temp_args, &config.system_name, crc_cache) {
+            Ok(console) => {
+                println!("  {} {}", "✅".green(), i18n::t("completed-successfully").bright_green());
+                indexed.push(console);
+            }
             Err(e) => {
                 eprintln!("  {}", i18n::t_with_arg("error-processing-system", &format!("{}: {}", config.system_name, e)).red());
                 continue;
             }
         }
     }
-    Ok(())
+    Ok(indexed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parallel_consoles_share_one_crc_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut console_configs = Vec::new();
+        for (system, rom) in [("Nintendo - Game Boy", "Alpha.gb"), ("Nintendo - Game Boy Advance", "Beta.gba")] {
+            let roms_dir = dir.path().join(system);
+            std::fs::create_dir(&roms_dir).unwrap();
+            std::fs::write(roms_dir.join(rom), rom.as_bytes()).unwrap();
+            console_configs.push(ConsoleConfig {
+                system_name: system.to_string(),
+                roms_dir,
+                output_dir: dir.path().join("playlists"),
+                force_system: true,
+            });
+        }
+
+        let config_path = dir.path().join("config.toml");
+        let args = Args::parse_from([
+            "retroarch-indexer",
+            "--config", config_path.to_str().unwrap(),
+            "--source-platform", "linux",
+            "--target-platform", "linux",
+            "--threads", "8",
+        ]);
+        let crc_cache = CrcCache::new().into_shared();
+        let indexed = process_consoles_parallel(&args, console_configs, &crc_cache).unwrap();
+        assert_eq!(indexed.len(), 2);
+
+        let cache = crc_cache.lock();
+        let keys: Vec<&Path> = cache.iter().map(|(key, _)| key).collect();
+        assert!(keys.iter().any(|key| key.ends_with("Alpha.gb")));
+        assert!(keys.iter().any(|key| key.ends_with("Beta.gba")));
+    }
 }
@@ -0,0 +1,275 @@
+use anyhow::{bail, Context, Result};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+const BUFFER_SIZE: usize = 64 * 1024; // 64KB buffer, a multiple of the 4-byte word size
+
+/// Extensions used for Nintendo 64 cartridge images
+pub const N64_EXTENSIONS: &[&str] = &["z64", "n64", "v64"];
+
+/// Byte order of a Nintendo 64 cartridge image, identified by its first word
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum N64ByteOrder {
+    /// Native big-endian order (.z64), the order used by No-Intro DATs
+    BigEndian,
+    /// Bytes swapped within each 16-bit half word (.v64)
+    ByteSwapped,
+    /// Bytes reversed within each 32-bit word (.n64)
+    LittleEndian,
+}
+
+impl N64ByteOrder {
+    /// Detect the byte order from the first four bytes of the image
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        match data.get(0..4)? {
+            [0x80, 0x37, 0x12, 0x40] => Some(Self::BigEndian),
+            [0x37, 0x80, 0x40, 0x12] => Some(Self::ByteSwapped),
+            [0x40, 0x12, 0x37, 0x80] => Some(Self::LittleEndian),
+            _ => None,
+        }
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Self::BigEndian => "big-endian (z64)",
+            Self::ByteSwapped => "byte-swapped (v64)",
+            Self::LittleEndian => "little-endian (n64)",
+        }
+    }
+
+    /// Rewrite `data` in place to big-endian order. Trailing bytes that do not
+    /// fill a whole word are left untouched.
+    pub fn normalize(&self, data: &mut [u8]) {
+        match self {
+            Self::BigEndian => {}
+            Self::ByteSwapped => {
+                for half in data.chunks_exact_mut(2) {
+                    half.swap(0, 1);
+                }
+            }
+            Self::LittleEndian => {
+                for word in data.chunks_exact_mut(4) {
+                    word.reverse();
+                }
+            }
+        }
+    }
+}
+
+/// Whether the extension belongs to an N64 image whose byte order should be probed
+pub fn is_n64_extension(extension: &str) -> bool {
+    N64_EXTENSIONS.contains(&extension)
+}
+
+/// Read until `buffer` is full or EOF so chunks stay word aligned
+pub(crate) fn read_full<R: Read + ?Sized>(reader: &mut R, buffer: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        let bytes_read = reader.read(&mut buffer[filled..])?;
+        if bytes_read == 0 {
+            break;
+        }
+        filled += bytes_read;
+    }
+    Ok(filled)
+}
+
+/// Result of normalizing a single image
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NormalizeOutcome {
+    /// Written to `target` after converting from `from`
+    Converted { target: PathBuf, from: N64ByteOrder, crc32: u32 },
+    /// Already a big-endian .z64 file
+    AlreadyNormalized,
+    /// `target` already exists and was left alone
+    TargetExists(PathBuf),
+    /// First word is not a known N64 magic
+    NotN64,
+}
+
+/// Rewrites N64 images to big-endian `.z64` files
+#[derive(Debug, Clone, Default)]
+pub struct N64Normalizer {
+    keep_original: bool,
+    dry_run: bool,
+}
+
+impl N64Normalizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_keep_original(mut self, keep_original: bool) -> Self {
+        self.keep_original = keep_original;
+        self
+    }
+
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// `.z64` path the normalized image is written to
+    pub fn target_path(source: &Path) -> PathBuf {
+        source.with_extension("z64")
+    }
+
+    /// Convert `source` to a big-endian `.z64` next to it.
+    ///
+    /// The image is written to a temporary file, synced, re-read and compared
+    /// against the CRC of the normalized stream before it replaces anything.
+    pub fn normalize_file(&self, source: &Path) -> Result<NormalizeOutcome> {
+        let mut reader = BufReader::with_capacity(BUFFER_SIZE, File::open(source)
+            .with_context(|| format!("Falha ao abrir arquivo: {}", source.display()))?);
+        let mut buffer = vec![0u8; BUFFER_SIZE];
+
+        let chunk_len = read_full(&mut reader, &mut buffer)
+            .with_context(|| format!("Falha ao ler arquivo: {}", source.display()))?;
+        let Some(order) = N64ByteOrder::detect(&buffer[..chunk_len]) else {
+            return Ok(NormalizeOutcome::NotN64);
+        };
+
+        let target = Self::target_path(source);
+        let is_z64 = source.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("z64"));
+        if order == N64ByteOrder::BigEndian && is_z64 {
+            return Ok(NormalizeOutcome::AlreadyNormalized);
+        }
+        if !is_z64 && target.exists() {
+            return Ok(NormalizeOutcome::TargetExists(target));
+        }
+
+        // A dry run hashes the normalized stream without writing it, so the
+        // CRC reported is the one the .z64 would have
+        if self.dry_run {
+            let crc32 = normalize_stream(&mut reader, &mut buffer, chunk_len, order, source, |_| Ok(()))?;
+            return Ok(NormalizeOutcome::Converted { target, from: order, crc32 });
+        }
+
+        let temp_path = target.with_extension("z64.tmp");
+        let expected = {
+            let temp = File::create(&temp_path)
+                .with_context(|| format!("Falha ao criar arquivo: {}", temp_path.display()))?;
+            let mut writer = BufWriter::with_capacity(BUFFER_SIZE, temp);
+
+            let crc32 = normalize_stream(&mut reader, &mut buffer, chunk_len, order, source, |chunk| {
+                writer.write_all(chunk)
+                    .with_context(|| format!("Falha ao escrever arquivo: {}", temp_path.display()))
+            })?;
+
+            let temp = writer.into_inner().map_err(|e| e.into_error())
+                .with_context(|| format!("Falha ao escrever arquivo: {}", temp_path.display()))?;
+            temp.sync_all()
+                .with_context(|| format!("Falha ao sincronizar arquivo: {}", temp_path.display()))?;
+            crc32
+        };
+
+        // Verify what actually reached the disk before touching the original
+        let written = crate::crc32::calculate_crc32(&temp_path)?;
+        if written != expected {
+            let _ = fs::remove_file(&temp_path);
+            bail!(
+                "Verificação falhou para {}: CRC32 esperado {:08X}, gravado {:08X}",
+                target.display(), expected, written
+            );
+        }
+
+        fs::rename(&temp_path, &target)
+            .with_context(|| format!("Falha ao renomear {} para {}", temp_path.display(), target.display()))?;
+
+        if !self.keep_original && !is_z64 {
+            fs::remove_file(source)
+                .with_context(|| format!("Falha ao remover arquivo: {}", source.display()))?;
+        }
+
+        Ok(NormalizeOutcome::Converted { target, from: order, crc32: expected })
+    }
+}
+
+/// Normalize the rest of an image, starting with the `chunk_len` bytes already
+/// in `buffer`, passing each chunk to `sink`. Returns the CRC32 of the output.
+fn normalize_stream<R: Read>(
+    reader: &mut R,
+    buffer: &mut [u8],
+    mut chunk_len: usize,
+    order: N64ByteOrder,
+    source: &Path,
+    mut sink: impl FnMut(&[u8]) -> Result<()>,
+) -> Result<u32> {
+    let mut crc = crc32fast::Hasher::new();
+    while chunk_len > 0 {
+        let chunk = &mut buffer[..chunk_len];
+        order.normalize(chunk);
+        crc.update(chunk);
+        sink(chunk)?;
+
+        chunk_len = read_full(reader, buffer)
+            .with_context(|| format!("Falha ao ler arquivo: {}", source.display()))?;
+    }
+    Ok(crc.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn z64_image() -> Vec<u8> {
+        let mut data = vec![0x80, 0x37, 0x12, 0x40];
+        data.extend((0..70_000u32).map(|i| (i % 253) as u8));
+        data.truncate(data.len() / 4 * 4);
+        data
+    }
+
+    fn to_order(data: &[u8], order: N64ByteOrder) -> Vec<u8> {
+        // Both swaps are their own inverse
+        let mut converted = data.to_vec();
+        order.normalize(&mut converted);
+        converted
+    }
+
+    #[test]
+    fn test_detect_byte_order() {
+        let z64 = z64_image();
+        assert_eq!(N64ByteOrder::detect(&z64), Some(N64ByteOrder::BigEndian));
+        assert_eq!(N64ByteOrder::detect(&to_order(&z64, N64ByteOrder::ByteSwapped)), Some(N64ByteOrder::ByteSwapped));
+        assert_eq!(N64ByteOrder::detect(&to_order(&z64, N64ByteOrder::LittleEndian)), Some(N64ByteOrder::LittleEndian));
+        assert_eq!(N64ByteOrder::detect(b"NES\x1A"), None);
+    }
+
+    #[test]
+    fn test_normalize_file_verifies_and_replaces() {
+        let dir = tempfile::tempdir().unwrap();
+        let z64 = z64_image();
+        let source = dir.path().join("game.v64");
+        fs::write(&source, to_order(&z64, N64ByteOrder::ByteSwapped)).unwrap();
+
+        let outcome = N64Normalizer::new().normalize_file(&source).unwrap();
+        let target = dir.path().join("game.z64");
+        assert_eq!(outcome, NormalizeOutcome::Converted {
+            target: target.clone(),
+            from: N64ByteOrder::ByteSwapped,
+            crc32: crc32fast::hash(&z64),
+        });
+        assert_eq!(fs::read(&target).unwrap(), z64);
+        assert!(!source.exists());
+
+        assert_eq!(N64Normalizer::new().normalize_file(&target).unwrap(), NormalizeOutcome::AlreadyNormalized);
+    }
+
+    #[test]
+    fn test_dry_run_reports_normalized_crc() {
+        let dir = tempfile::tempdir().unwrap();
+        let z64 = z64_image();
+        let source = dir.path().join("game.n64");
+        fs::write(&source, to_order(&z64, N64ByteOrder::LittleEndian)).unwrap();
+
+        let outcome = N64Normalizer::new().with_dry_run(true).normalize_file(&source).unwrap();
+        assert_eq!(outcome, NormalizeOutcome::Converted {
+            target: dir.path().join("game.z64"),
+            from: N64ByteOrder::LittleEndian,
+            crc32: crc32fast::hash(&z64),
+        });
+        assert!(source.exists());
+        assert!(!dir.path().join("game.z64").exists());
+    }
+}
@@ -104,15 +104,43 @@ impl fmt::Display for Platform {
     }
 }
 
+/// ROM and core directories of one device, replacing the platform's
+/// `default_roms_path`/`default_cores_path` guesses when known
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlatformPaths {
+    pub roms: Option<String>,
+    pub cores: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct PlatformPathConverter {
     source: Platform,
     target: Platform,
+    source_paths: PlatformPaths,
+    target_paths: PlatformPaths,
 }
 
 impl PlatformPathConverter {
     pub fn new(source: Platform, target: Platform) -> Self {
-        Self { source, target }
+        Self {
+            source,
+            target,
+            source_paths: PlatformPaths::default(),
+            target_paths: PlatformPaths::default(),
+        }
+    }
+
+    /// Directories of the device the paths are converted from; ROM paths
+    /// under its ROM directory keep everything below it
+    pub fn with_source_paths(mut self, paths: PlatformPaths) -> Self {
+        self.source_paths = paths;
+        self
+    }
+
+    /// Directories of the device the paths are converted to
+    pub fn with_target_paths(mut self, paths: PlatformPaths) -> Self {
+        self.target_paths = paths;
+        self
     }
 
     pub fn convert_rom_path(&self, path: &str) -> String {
@@ -136,8 +164,13 @@ impl PlatformPathConverter {
         let core_name = self.extract_core_name(path);
         
         // Build target core path
-        format!("{}/{}{}",
-            self.target.default_cores_path(),
+        let cores_path = self.target_paths.cores.as_deref()
+            .map(|dir| dir.trim_end_matches(['/', '\\']))
+            .unwrap_or_else(|| self.target.default_cores_path());
+        let separator = if cores_path.contains('\\') { '\\' } else { '/' };
+        format!("{}{}{}{}",
+            cores_path,
+            separator,
             core_name,
             self.target.core_extension()
         )
@@ -146,7 +179,15 @@ impl PlatformPathConverter {
     fn extract_relative_path(&self, path: &str) -> String {
         // Try to find common ROM directory patterns and extract relative path
         let normalized = path.replace('\\', "/");
-        
+
+        if let Some(root) = &self.source_paths.roms {
+            let root = root.replace('\\', "/");
+            let root = root.trim_end_matches('/');
+            if let Some(relative) = normalized.strip_prefix(root).and_then(|rest| rest.strip_prefix('/')) {
+                return relative.to_string();
+            }
+        }
+
         // Common patterns to strip
         let patterns = [
             "roms/", "ROMs/", "games/", "Games/", 
@@ -172,7 +213,9 @@ impl PlatformPathConverter {
     }
 
     fn extract_core_name(&self, path: &str) -> String {
-        let filename = std::path::Path::new(path)
+        // Split on both separators, the path may come from another OS
+        let filename = path.rsplit(['/', '\\']).next().unwrap_or(path);
+        let filename = std::path::Path::new(filename)
             .file_stem()
             .unwrap_or_default()
             .to_string_lossy();
@@ -187,11 +230,14 @@ impl PlatformPathConverter {
     }
 
     fn build_target_path(&self, relative_path: &str, is_rom: bool) -> String {
-        let base_path = if is_rom {
-            self.target.default_roms_path()
+        let (custom_path, default_path) = if is_rom {
+            (&self.target_paths.roms, self.target.default_roms_path())
         } else {
-            self.target.default_cores_path()
+            (&self.target_paths.cores, self.target.default_cores_path())
         };
+        let base_path = custom_path.as_deref()
+            .map(|dir| dir.trim_end_matches(['/', '\\']))
+            .unwrap_or(default_path);
 
         let separator = self.target.path_separator();
         let converted_path = relative_path.replace(['/', '\\'], &separator.to_string());
@@ -230,4 +276,23 @@ mod tests {
         assert!(switch_core.contains("mupen64plus_next"));
         assert!(switch_core.ends_with("_libnx.a"));
     }
+
+    #[test]
+    fn test_conversion_with_device_paths() {
+        let converter = PlatformPathConverter::new(Platform::Windows, Platform::Switch)
+            .with_source_paths(PlatformPaths { roms: Some("E:\\Retro\\Library\\".to_string()), cores: None })
+            .with_target_paths(PlatformPaths {
+                roms: Some("/media/Retro".to_string()),
+                cores: Some("/retroarch/cores/".to_string()),
+            });
+
+        assert_eq!(
+            converter.convert_rom_path("E:\\Retro\\Library\\Nintendo 64\\USA\\Super Mario 64.z64"),
+            "/media/Retro/Nintendo 64/USA/Super Mario 64.z64"
+        );
+        assert_eq!(
+            converter.convert_core_path("C:\\RetroArch\\cores\\mupen64plus_next_libretro.dll"),
+            "/retroarch/cores/mupen64plus_next_libnx.a"
+        );
+    }
 }
@@ -1,14 +1,37 @@
 use anyhow::Result;
+use clap::ValueEnum;
+use log::debug;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::Path;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 use crate::scanner::RomFile;
-use crate::platform::{Platform, PlatformPathConverter};
+use crate::platform::{Platform, PlatformPathConverter, PlatformPaths};
 use crate::dat_parser::DatCollection;
 use crate::core_mapper::CoreMapper;
+use crate::system_registry::SystemRegistry;
+use crate::deduplicator::{normalize_title, DiscTag, Region, RomQuality};
 use crate::error::PlaylistError;
 
+/// System, normalized title, region and quality score shared by the discs of one game
+type DiscGroupKey = (String, String, Region, u32);
+
+/// Lines per entry of the legacy playlist format
+const LEGACY_ENTRY_LINES: usize = 6;
+
+/// On-disk layout of an `.lpl` file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaylistFormat {
+    /// JSON, used since RetroArch 1.7.6
+    #[default]
+    Json,
+    /// Six plain-text lines per entry (path, label, core path, core name,
+    /// crc|db, db name), read by older RetroArch builds
+    Legacy,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Playlist {
     pub version: String,
@@ -24,7 +47,32 @@ pub struct Playlist {
     pub left_thumbnail_mode: u32,
     #[serde(default)]
     pub sort_mode: u32,
+    /// Directory the entries' paths were relative to when the playlist was
+    /// written, used by RetroArch to fix them up on another device
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_content_directory: Option<String>,
+    // Settings of the manual content scan that produced the playlist
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scan_content_dir: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scan_file_exts: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scan_dat_file_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scan_search_recursively: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scan_search_archives: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scan_filter_dat_content: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scan_overwrite_playlist: Option<bool>,
     pub items: Vec<PlaylistItem>,
+    /// Keys we don't model, kept so a load and save doesn't drop them
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+    /// Format the playlist was loaded from and is saved in
+    #[serde(skip)]
+    pub format: PlaylistFormat,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +87,20 @@ pub struct PlaylistItem {
     pub crc32: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub db_name: Option<String>,
+    /// Save state slot the entry was last launched with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_slot: Option<u32>,
+    // Subsystem launch (e.g. Super Game Boy, Sufami Turbo): the core's
+    // subsystem and the extra content files it loads
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subsystem_ident: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subsystem_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subsystem_roms: Option<Vec<String>>,
+    /// Keys we don't model, kept so a load and save doesn't drop them
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 impl Playlist {
@@ -51,10 +113,25 @@ impl Playlist {
             right_thumbnail_mode: 0,
             left_thumbnail_mode: 0,
             sort_mode: 0,
+            base_content_directory: None,
+            scan_content_dir: None,
+            scan_file_exts: None,
+            scan_dat_file_path: None,
+            scan_search_recursively: None,
+            scan_search_archives: None,
+            scan_filter_dat_content: None,
+            scan_overwrite_playlist: None,
             items: Vec::new(),
+            extra: serde_json::Map::new(),
+            format: PlaylistFormat::Json,
         }
     }
 
+    pub fn with_format(mut self, format: PlaylistFormat) -> Self {
+        self.format = format;
+        self
+    }
+
     pub fn with_default_core(mut self, core_path: String, core_name: String) -> Self {
         self.default_core_path = Some(core_path);
         self.default_core_name = Some(core_name);
@@ -66,8 +143,7 @@ impl Playlist {
     }
 
     pub fn save(&self, path: &Path) -> Result<()> {
-        let json = serde_json::to_string_pretty(self)
-            .map_err(PlaylistError::SerializationFailed)?;
+        let content = self.to_file_content()?;
 
         // Ensure parent directory exists
         if let Some(parent) = path.parent() {
@@ -78,7 +154,7 @@ impl Playlist {
                 })?;
         }
 
-        std::fs::write(path, json)
+        std::fs::write(path, content)
             .map_err(|e| PlaylistError::SaveFailed {
                 path: path.to_path_buf(),
                 source: e,
@@ -87,6 +163,19 @@ impl Playlist {
         Ok(())
     }
 
+    /// Save only when the file would change, so untouched playlists keep their
+    /// bytes and modification time. Returns whether the file was written.
+    pub fn save_if_changed(&self, path: &Path) -> Result<bool> {
+        let content = self.to_file_content()?;
+
+        if std::fs::read(path).is_ok_and(|existing| existing == content.as_bytes()) {
+            return Ok(false);
+        }
+
+        self.save(path)?;
+        Ok(true)
+    }
+
     pub fn load(path: &Path) -> Result<Self> {
         let content = std::fs::read_to_string(path)
             .map_err(|e| PlaylistError::LoadFailed {
@@ -94,12 +183,78 @@ impl Playlist {
                 source: e,
             })?;
 
-        let playlist: Playlist = serde_json::from_str(&content)
-            .map_err(PlaylistError::SerializationFailed)?;
+        Self::parse(&content)
+    }
+
+    /// Parse a playlist in either format, telling them apart by the JSON
+    /// object's opening brace
+    pub fn parse(content: &str) -> Result<Self> {
+        let content = content.trim_start_matches('\u{feff}');
+        if content.trim_start().starts_with('{') {
+            let playlist: Playlist = serde_json::from_str(content)
+                .map_err(PlaylistError::SerializationFailed)?;
+            Ok(playlist)
+        } else {
+            Self::parse_legacy(content)
+        }
+    }
+
+    /// Contents of the playlist file in its format
+    pub fn to_file_content(&self) -> Result<String> {
+        match self.format {
+            PlaylistFormat::Json => Ok(serde_json::to_string_pretty(self)
+                .map_err(PlaylistError::SerializationFailed)?),
+            PlaylistFormat::Legacy => Ok(self.to_legacy()),
+        }
+    }
+
+    fn parse_legacy(content: &str) -> Result<Self> {
+        let mut lines: Vec<&str> = content.lines().collect();
+        // Blank lines after the last entry, stopping before an empty final db_name
+        while !lines.len().is_multiple_of(LEGACY_ENTRY_LINES) && lines.last() == Some(&"") {
+            lines.pop();
+        }
+        if !lines.len().is_multiple_of(LEGACY_ENTRY_LINES) {
+            return Err(PlaylistError::InvalidFormat(format!(
+                "{} linhas não formam entradas de {} linhas",
+                lines.len(), LEGACY_ENTRY_LINES
+            )).into());
+        }
+
+        let field = |line: &str| (!line.is_empty()).then(|| line.to_string());
+        let mut playlist = Self::new().with_format(PlaylistFormat::Legacy);
+        for entry in lines.chunks_exact(LEGACY_ENTRY_LINES) {
+            let mut item = PlaylistItem::new(entry[0].to_string(), entry[1].to_string());
+            item.core_path = field(entry[2]);
+            item.core_name = field(entry[3]);
+            item.crc32 = field(entry[4]);
+            item.db_name = field(entry[5]);
+            playlist.add_item(item);
+        }
 
         Ok(playlist)
     }
 
+    /// Six lines per entry. The default core, scan settings and subsystem
+    /// details have no place in this format and are left out.
+    fn to_legacy(&self) -> String {
+        let mut content = String::new();
+        for item in &self.items {
+            for line in [
+                item.path.as_str(),
+                item.label.as_str(),
+                item.core_path.as_deref().unwrap_or("DETECT"),
+                item.core_name.as_deref().unwrap_or("DETECT"),
+                item.crc32.as_deref().unwrap_or(""),
+                item.db_name.as_deref().unwrap_or(""),
+            ] {
+                content.push_str(line);
+                content.push('\n');
+            }
+        }
+        content
+    }
+
     pub fn sort_by_label(&mut self) {
         self.items.sort_by(|a, b| a.label.cmp(&b.label));
     }
@@ -128,6 +283,11 @@ impl PlaylistItem {
             core_name: None,
             crc32: None,
             db_name: None,
+            entry_slot: None,
+            subsystem_ident: None,
+            subsystem_name: None,
+            subsystem_roms: None,
+            extra: serde_json::Map::new(),
         }
     }
 
@@ -152,9 +312,14 @@ pub struct PlaylistBuilder {
     source_platform: Platform,
     target_platform: Platform,
     path_converter: PlatformPathConverter,
+    source_paths: PlatformPaths,
+    target_paths: PlatformPaths,
     dat_collection: DatCollection,
     core_mapper: CoreMapper,
     verbose: bool,
+    generate_m3u: bool,
+    m3u_directory: Option<PathBuf>,
+    format: PlaylistFormat,
 }
 
 impl PlaylistBuilder {
@@ -167,19 +332,39 @@ impl PlaylistBuilder {
             source_platform: source,
             target_platform: target,
             path_converter,
+            source_paths: PlatformPaths::default(),
+            target_paths: PlatformPaths::default(),
             dat_collection: DatCollection::new(),
             core_mapper: CoreMapper::new(),
             verbose: false,
+            generate_m3u: false,
+            m3u_directory: None,
+            format: PlaylistFormat::Json,
         }
     }
 
     pub fn with_platforms(mut self, source: Platform, target: Platform) -> Self {
         self.source_platform = source;
         self.target_platform = target;
-        self.path_converter = PlatformPathConverter::new(source, target);
+        self.path_converter = self.path_converter();
+        self
+    }
+
+    /// ROM and core directories of the source and target devices, e.g. read
+    /// from their `retroarch.cfg`
+    pub fn with_device_paths(mut self, source: PlatformPaths, target: PlatformPaths) -> Self {
+        self.source_paths = source;
+        self.target_paths = target;
+        self.path_converter = self.path_converter();
         self
     }
 
+    fn path_converter(&self) -> PlatformPathConverter {
+        PlatformPathConverter::new(self.source_platform, self.target_platform)
+            .with_source_paths(self.source_paths.clone())
+            .with_target_paths(self.target_paths.clone())
+    }
+
     pub fn with_dat_collection(mut self, dat_collection: DatCollection) -> Self {
         self.dat_collection = dat_collection;
         self
@@ -190,13 +375,38 @@ impl PlaylistBuilder {
         self
     }
 
+    /// Mapping used to fill in the core of each entry
+    pub fn with_core_mapper(mut self, core_mapper: CoreMapper) -> Self {
+        self.core_mapper = core_mapper;
+        self
+    }
+
+    /// Collapse "(Disc N)" files into a single `.m3u` playlist item
+    pub fn with_m3u_generation(mut self, enabled: bool) -> Self {
+        self.generate_m3u = enabled;
+        self
+    }
+
+    /// Write generated `.m3u` files here instead of next to the discs
+    pub fn with_m3u_directory(mut self, directory: Option<PathBuf>) -> Self {
+        self.m3u_directory = directory;
+        self
+    }
+
+    /// Format of the playlists built
+    pub fn with_format(mut self, format: PlaylistFormat) -> Self {
+        self.format = format;
+        self
+    }
+
     pub fn build_by_system(&self, roms: &[RomFile]) -> Result<HashMap<String, Playlist>> {
         let mut playlists: HashMap<String, Playlist> = HashMap::new();
+        let roms = self.collapse_multi_disc(roms, None)?;
 
-        for rom in roms {
+        for rom in roms.iter() {
             if let Some(system) = &rom.system {
                 let playlist = playlists.entry(system.clone()).or_insert_with(|| {
-                    let mut playlist = Playlist::new();
+                    let mut playlist = Playlist::new().with_format(self.format);
                     
                     // Set default core for this system
                     if let Some((core_path, core_name)) = self.core_mapper.get_default_core(system, self.target_platform) {
@@ -221,9 +431,10 @@ impl PlaylistBuilder {
     }
 
     pub fn build_master(&self, roms: &[RomFile]) -> Result<Playlist> {
-        let mut playlist = Playlist::new();
+        let mut playlist = Playlist::new().with_format(self.format);
+        let roms = self.collapse_multi_disc(roms, None)?;
 
-        for rom in roms {
+        for rom in roms.iter() {
             if let Some(system) = &rom.system {
                 let item = self.create_playlist_item(rom, system)?;
                 playlist.add_item(item);
@@ -237,7 +448,7 @@ impl PlaylistBuilder {
     }
 
     pub fn build_single_system_playlist(&self, roms: &[RomFile], forced_system: &str) -> Result<Playlist> {
-        let mut playlist = Playlist::new();
+        let mut playlist = Playlist::new().with_format(self.format);
         
         // Set default core for this system
         if let Some((core_path, core_name)) = self.core_mapper.get_default_core(forced_system, self.target_platform) {
@@ -245,7 +456,8 @@ impl PlaylistBuilder {
         }
 
         // Add all ROMs to the playlist as if they belong to the forced system
-        for rom in roms {
+        let roms = self.collapse_multi_disc(roms, Some(forced_system))?;
+        for rom in roms.iter() {
             let item = self.create_playlist_item(rom, forced_system)?;
             playlist.add_item(item);
         }
@@ -256,17 +468,23 @@ impl PlaylistBuilder {
         Ok(playlist)
     }
 
-    fn create_playlist_item(&self, rom: &RomFile, system: &str) -> Result<PlaylistItem> {
+    /// Playlist entry for `rom` as it would appear in the playlist of `system`
+    pub fn create_playlist_item(&self, rom: &RomFile, system: &str) -> Result<PlaylistItem> {
         // Convert path to target platform format
         let converted_path = self.path_converter.convert_rom_path(
-            &rom.path.to_string_lossy()
+            &rom.playlist_path().to_string_lossy()
         );
 
         // Get ROM label with new enhanced DAT→XML logic
-        let label = if let Some(crc32) = rom.crc32 {
+        let label = if rom.crc32.is_some() {
+            // Headered dumps only match No-Intro DATs through their headerless CRC
+            let dat_name = self.dat_collection
+                .find_match(&rom.hash_candidates())
+                .map(|found| found.entry.name.clone());
+
             // For MAME systems, use enhanced DAT→XML→Description lookup
             if system.to_lowercase().contains("mame") || system.to_lowercase().contains("arcade") {
-                if let Some(dat_name) = self.dat_collection.get_name_by_crc(crc32) {
+                if let Some(dat_name) = dat_name {
                     // Got ROM name from DAT, now try to get description from MAME XML
                     if let Some(xml_description) = self.dat_collection.get_mame_description_by_dat_name(&dat_name) {
                         xml_description
@@ -281,7 +499,7 @@ impl PlaylistBuilder {
                 }
             } else {
                 // For non-MAME systems, use traditional DAT lookup
-                if let Some(dat_name) = self.dat_collection.get_name_by_crc(crc32) {
+                if let Some(dat_name) = dat_name {
                     dat_name
                 } else {
                     self.clean_filename(&rom.filename)
@@ -299,12 +517,17 @@ impl PlaylistBuilder {
 
         // Get core for this system
         let (core_path, core_name) = self.core_mapper
-            .get_default_core(system, self.target_platform)
+            .get_core_for_file(system, &rom.extension, self.target_platform)
             .unwrap_or_else(|| ("DETECT".to_string(), "DETECT".to_string()));
 
         let mut item = PlaylistItem::new(converted_path, label)
             .with_core(core_path, core_name)
-            .with_db_name(format!("{}.lpl", system));
+            .with_db_name(
+                SystemRegistry::global()
+                    .system_by_name(system)
+                    .map(|definition| definition.db_name())
+                    .unwrap_or_else(|| format!("{}.lpl", system)),
+            );
 
         if let Some(crc32) = rom.crc32 {
             item = item.with_crc32(crc32);
@@ -313,6 +536,116 @@ impl PlaylistBuilder {
         Ok(item)
     }
 
+    /// Replace each group of "(Disc N)" files with one ROM pointing at its `.m3u`
+    fn collapse_multi_disc<'a>(&self, roms: &'a [RomFile], forced_system: Option<&str>) -> Result<Cow<'a, [RomFile]>> {
+        if !self.generate_m3u {
+            return Ok(Cow::Borrowed(roms));
+        }
+
+        // Discs belong together when system, title, region and dump quality agree
+        let mut groups: HashMap<DiscGroupKey, Vec<(u32, usize)>> = HashMap::new();
+        for (index, rom) in roms.iter().enumerate() {
+            let Some(system) = forced_system.or(rom.system.as_deref()) else {
+                continue;
+            };
+            let Some(disc) = DiscTag::from_filename(&rom.filename) else {
+                continue;
+            };
+
+            let key = (
+                system.to_string(),
+                normalize_title(&rom.filename),
+                Region::from_filename(&rom.filename),
+                RomQuality::assess_from_filename(&rom.filename).score(),
+            );
+            groups.entry(key).or_default().push((disc.number, index));
+        }
+
+        let mut collapsed_indices = HashSet::new();
+        let mut m3u_roms = Vec::new();
+
+        for mut discs in groups.into_values() {
+            if discs.len() < 2 {
+                continue;
+            }
+            discs.sort();
+
+            let members: Vec<&RomFile> = discs.iter().map(|(_, index)| &roms[*index]).collect();
+            let m3u_path = self.write_m3u(&members)?;
+
+            let mut m3u_rom = RomFile::new(m3u_path);
+            m3u_rom.system = forced_system.map(str::to_string).or_else(|| members[0].system.clone());
+            m3u_rom.size = members.iter().map(|rom| rom.size).sum();
+            m3u_rom.serial = members[0].serial.clone();
+            m3u_roms.push(m3u_rom);
+
+            collapsed_indices.extend(discs.iter().map(|(_, index)| *index));
+        }
+
+        if m3u_roms.is_empty() {
+            return Ok(Cow::Borrowed(roms));
+        }
+
+        let mut result: Vec<RomFile> = roms.iter()
+            .enumerate()
+            .filter(|(index, _)| !collapsed_indices.contains(index))
+            .map(|(_, rom)| rom.clone())
+            .collect();
+        result.extend(m3u_roms);
+
+        Ok(Cow::Owned(result))
+    }
+
+    /// Write the `.m3u` for a disc set, reusing any existing one that already lists these discs
+    fn write_m3u(&self, discs: &[&RomFile]) -> Result<PathBuf> {
+        let disc_dir = discs[0].path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let m3u_dir = self.m3u_directory.clone().unwrap_or_else(|| disc_dir.clone());
+
+        for dir in [&m3u_dir, &disc_dir] {
+            if let Some(existing) = find_existing_m3u(dir, discs) {
+                debug!("Usando .m3u existente: {}", existing.display());
+                return Ok(existing);
+            }
+        }
+
+        let stem = Path::new(&discs[0].filename)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let m3u_path = m3u_dir.join(format!("{}.m3u", DiscTag::strip(&stem)));
+
+        // Never overwrite a hand-written playlist
+        if m3u_path.exists() {
+            debug!("Mantendo .m3u existente: {}", m3u_path.display());
+            return Ok(m3u_path);
+        }
+
+        let mut content = String::new();
+        for disc in discs {
+            // Relative entries when the .m3u sits next to the discs, target-platform paths otherwise
+            let entry = if disc.path.parent() == Some(m3u_dir.as_path()) {
+                disc.filename.clone()
+            } else {
+                self.path_converter.convert_rom_path(&disc.path.to_string_lossy())
+            };
+            content.push_str(&entry);
+            content.push('\n');
+        }
+
+        std::fs::create_dir_all(&m3u_dir)
+            .and_then(|_| std::fs::write(&m3u_path, content))
+            .map_err(|e| PlaylistError::SaveFailed {
+                path: m3u_path.clone(),
+                source: e,
+            })?;
+
+        if self.verbose {
+            debug!("Gerado {} ({} discos)", m3u_path.display(), discs.len());
+        }
+
+        Ok(m3u_path)
+    }
+
     fn clean_filename(&self, filename: &str) -> String {
         // Remove file extension
         let name = if let Some(dot_pos) = filename.rfind('.') {
@@ -331,6 +664,36 @@ impl PlaylistBuilder {
     }
 }
 
+/// Find an `.m3u` in `dir` that already references one of the discs
+fn find_existing_m3u(dir: &Path, discs: &[&RomFile]) -> Option<PathBuf> {
+    let entries = std::fs::read_dir(dir).ok()?;
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let is_m3u = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("m3u"));
+        if !is_m3u {
+            continue;
+        }
+
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+
+        let references_disc = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| dir.join(line))
+            .any(|listed| discs.iter().any(|disc| disc.path == listed));
+
+        if references_disc {
+            return Some(path);
+        }
+    }
+
+    None
+}
+
 impl Default for PlaylistBuilder {
     fn default() -> Self {
         Self::new()
@@ -373,6 +736,124 @@ mod tests {
         assert!(json.contains("12345678"));
     }
 
+    #[test]
+    fn test_full_schema_round_trip() {
+        let original = serde_json::json!({
+            "version": "1.5",
+            "default_core_path": "",
+            "default_core_name": "",
+            "base_content_directory": "/home/deck/ROMs",
+            "label_display_mode": 0,
+            "right_thumbnail_mode": 0,
+            "left_thumbnail_mode": 0,
+            "thumbnail_match_mode": 0,
+            "sort_mode": 1,
+            "scan_content_dir": "/home/deck/ROMs/snes",
+            "scan_file_exts": "sfc|smc",
+            "scan_dat_file_path": "",
+            "scan_search_recursively": true,
+            "scan_search_archives": false,
+            "scan_filter_dat_content": false,
+            "scan_overwrite_playlist": true,
+            "items": [{
+                "path": "/home/deck/ROMs/gb/Pokemon Red.gb",
+                "label": "Pokemon Red",
+                "core_path": "DETECT",
+                "core_name": "DETECT",
+                "crc32": "DETECT",
+                "db_name": "Nintendo - Super Nintendo Entertainment System.lpl",
+                "entry_slot": 3,
+                "subsystem_ident": "gb",
+                "subsystem_name": "Super Game Boy",
+                "subsystem_roms": ["/home/deck/ROMs/gb/Pokemon Red.gb"],
+                "last_played_str": "2024-01-01"
+            }]
+        });
+
+        let playlist: Playlist = serde_json::from_value(original.clone()).unwrap();
+        assert_eq!(playlist.scan_file_exts.as_deref(), Some("sfc|smc"));
+        assert_eq!(playlist.items[0].entry_slot, Some(3));
+        assert!(playlist.extra.contains_key("thumbnail_match_mode"));
+
+        assert_eq!(serde_json::to_value(&playlist).unwrap(), original);
+    }
+
+    #[test]
+    fn test_legacy_format_detection_and_round_trip() {
+        let legacy = "/roms/gba/Alpha.gba\nAlpha\nDETECT\nDETECT\nDETECT\nNintendo - Game Boy Advance.lpl\n\
+                      /roms/gba/Beta.gba\nBeta\n/cores/mgba_libretro.so\nmGBA\n1234ABCD|crc\n\n";
+
+        let playlist = Playlist::parse(legacy).unwrap();
+        assert_eq!(playlist.format, PlaylistFormat::Legacy);
+        assert_eq!(playlist.items.len(), 2);
+        assert_eq!(playlist.items[1].core_name.as_deref(), Some("mGBA"));
+        assert_eq!(playlist.items[1].db_name, None);
+        assert_eq!(playlist.to_file_content().unwrap(), legacy);
+
+        // Upgrading keeps the entries, and JSON is still detected
+        let json = playlist.clone().with_format(PlaylistFormat::Json).to_file_content().unwrap();
+        let upgraded = Playlist::parse(&json).unwrap();
+        assert_eq!(upgraded.format, PlaylistFormat::Json);
+        assert_eq!(upgraded.items[1].crc32.as_deref(), Some("1234ABCD|crc"));
+
+        assert!(Playlist::parse("/roms/gba/Alpha.gba\nAlpha\n").is_err());
+
+        // A trailing blank line after an entry whose crc and db_name are empty
+        let trailing = "/roms/gba/Alpha.gba\nAlpha\nDETECT\nDETECT\n\n\n\n";
+        assert_eq!(trailing.lines().count(), 7);
+        let playlist = Playlist::parse(trailing).unwrap();
+        assert_eq!(playlist.items.len(), 1);
+        assert_eq!((playlist.items[0].crc32.as_deref(), playlist.items[0].db_name.as_deref()), (None, None));
+    }
+
+    #[test]
+    fn test_multi_disc_m3u_generation() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut roms = Vec::new();
+        for name in ["Game (USA) (Disc 2).cue", "Game (USA) (Disc 1).cue", "Other (USA).cue"] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+            let mut rom = RomFile::new(dir.path().join(name));
+            rom.system = Some("Sony - PlayStation".to_string());
+            roms.push(rom);
+        }
+
+        let builder = PlaylistBuilder::new().with_m3u_generation(true);
+        let playlist = builder.build_master(&roms).unwrap();
+        assert_eq!(playlist.items.len(), 2);
+        assert!(playlist.items.iter().any(|item| item.path.ends_with("Game (USA).m3u")));
+
+        let m3u = std::fs::read_to_string(dir.path().join("Game (USA).m3u")).unwrap();
+        assert_eq!(m3u, "Game (USA) (Disc 1).cue\nGame (USA) (Disc 2).cue\n");
+
+        // A hand-written playlist is reused and left untouched
+        std::fs::remove_file(dir.path().join("Game (USA).m3u")).unwrap();
+        std::fs::write(dir.path().join("custom.m3u"), "Game (USA) (Disc 1).cue\n").unwrap();
+        let playlist = builder.build_master(&roms).unwrap();
+        assert!(playlist.items.iter().any(|item| item.path.ends_with("custom.m3u")));
+        assert!(!dir.path().join("Game (USA).m3u").exists());
+        assert_eq!(std::fs::read_to_string(dir.path().join("custom.m3u")).unwrap(), "Game (USA) (Disc 1).cue\n");
+    }
+
+    #[test]
+    fn test_concrete_core_paths_opt_in() {
+        let mut rom = RomFile::new(PathBuf::from("/home/deck/ROMs/gba/Game.gba"));
+        rom.system = Some("Nintendo - Game Boy Advance".to_string());
+
+        let detect = PlaylistBuilder::new().with_platforms(Platform::SteamDeck, Platform::SteamDeck);
+        let item = detect.create_playlist_item(&rom, "Nintendo - Game Boy Advance").unwrap();
+        assert_eq!(item.core_path.as_deref(), Some("DETECT"));
+
+        let builder = PlaylistBuilder::new()
+            .with_platforms(Platform::SteamDeck, Platform::SteamDeck)
+            .with_core_mapper(CoreMapper::new().with_core_paths(true));
+        let playlists = builder.build_by_system(&[rom]).unwrap();
+        let playlist = &playlists["Nintendo - Game Boy Advance"];
+        let expected = "/home/deck/.var/app/org.libretro.RetroArch/config/retroarch/cores/mgba_libretro.so";
+        assert_eq!(playlist.default_core_path.as_deref(), Some(expected));
+        assert_eq!(playlist.items[0].core_path.as_deref(), Some(expected));
+        assert_eq!(playlist.items[0].core_name.as_deref(), Some("mGBA"));
+    }
+
     #[test]
     fn test_filename_cleaning() {
         let builder = PlaylistBuilder::new();
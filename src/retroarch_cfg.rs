@@ -0,0 +1,229 @@
+use anyhow::{Context, Result};
+use log::debug;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::core_info::parse_key_values;
+use crate::platform::{Platform, PlatformPaths};
+
+/// Settings read from a `retroarch.cfg`, either the one of the local install
+/// or a copy taken from another device
+#[derive(Debug, Clone, Default)]
+pub struct RetroArchConfig {
+    values: HashMap<String, String>,
+    /// Replaces a leading `~` in paths
+    home_dir: Option<String>,
+    /// Replaces a leading `:`, RetroArch's own directory
+    application_dir: Option<String>,
+}
+
+impl RetroArchConfig {
+    /// Parse the `key = "value"` lines of a `retroarch.cfg`. Paths starting
+    /// with `~` or `:` are kept as written until the matching directory is set.
+    pub fn parse(content: &str) -> Self {
+        Self {
+            values: parse_key_values(content),
+            ..Default::default()
+        }
+    }
+
+    /// Load the config of the RetroArch installed on this machine: `~` is the
+    /// user's home and `:` the directory holding the file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Falha ao ler configuração do RetroArch: {}", path.display()))?;
+
+        let config = Self::parse(&content)
+            .with_home_dir(dirs::home_dir().map(|home| home.display().to_string()))
+            .with_application_dir(path.parent().map(|dir| dir.display().to_string()));
+        debug!("{} opções lidas de {}", config.values.len(), path.display());
+        Ok(config)
+    }
+
+    /// Load a config copied from a device running on `platform`. Its home is
+    /// unknown, so `~` paths stay as written; `:` is the directory above the
+    /// platform's default cores directory.
+    pub fn load_from_device(path: &Path, platform: Platform) -> Result<Self> {
+        let application_dir = Path::new(platform.default_cores_path())
+            .parent()
+            .map(|dir| dir.display().to_string());
+        Ok(Self::load(path)?
+            .with_home_dir(None)
+            .with_application_dir(application_dir))
+    }
+
+    pub fn with_home_dir(mut self, dir: Option<String>) -> Self {
+        self.home_dir = dir;
+        self
+    }
+
+    pub fn with_application_dir(mut self, dir: Option<String>) -> Self {
+        self.application_dir = dir;
+        self
+    }
+
+    /// `retroarch.cfg` of the first RetroArch install found in the usual locations
+    pub fn discover() -> Option<PathBuf> {
+        let mut candidates = Vec::new();
+        if let Some(config) = dirs::config_dir() {
+            candidates.push(config.join("retroarch/retroarch.cfg"));
+            candidates.push(config.join("RetroArch/retroarch.cfg"));
+        }
+        if let Some(home) = dirs::home_dir() {
+            candidates.push(home.join(".var/app/org.libretro.RetroArch/config/retroarch/retroarch.cfg"));
+            candidates.push(home.join(".retroarch.cfg"));
+        }
+        candidates.extend([
+            PathBuf::from("C:/RetroArch/retroarch.cfg"),
+            PathBuf::from("C:/RetroArch-Win64/retroarch.cfg"),
+        ]);
+
+        candidates.into_iter().find(|path| path.is_file())
+    }
+
+    /// Raw value of `key`; RetroArch writes "default" for options left unset
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key)
+            .map(String::as_str)
+            .filter(|value| !value.is_empty() && *value != "default")
+    }
+
+    /// Value of a directory option with its `~` or `:` prefix expanded
+    pub fn directory(&self, key: &str) -> Option<String> {
+        let value = self.get(key)?;
+        let expanded = match value.chars().next() {
+            Some('~') => self.home_dir.as_deref().map(|home| join_prefix(home, &value[1..])),
+            Some(':') => self.application_dir.as_deref().map(|dir| join_prefix(dir, &value[1..])),
+            _ => None,
+        };
+        Some(expanded.unwrap_or_else(|| value.to_string()))
+    }
+
+    pub fn playlist_directory(&self) -> Option<String> {
+        self.directory("playlist_directory")
+    }
+
+    /// Where the installed cores live
+    pub fn libretro_directory(&self) -> Option<String> {
+        self.directory("libretro_directory")
+    }
+
+    /// Where the cores' `.info` files live
+    pub fn libretro_info_path(&self) -> Option<String> {
+        self.directory("libretro_info_path")
+    }
+
+    pub fn thumbnails_directory(&self) -> Option<String> {
+        self.directory("thumbnails_directory")
+    }
+
+    /// Start directory of the file browser, usually the ROM library
+    pub fn rgui_browser_directory(&self) -> Option<String> {
+        self.directory("rgui_browser_directory")
+    }
+
+    /// BIOS and firmware directory
+    pub fn system_directory(&self) -> Option<String> {
+        self.directory("system_directory")
+    }
+
+    /// ROM and core directories, as prefixes for converted paths
+    pub fn platform_paths(&self) -> PlatformPaths {
+        PlatformPaths {
+            roms: self.rgui_browser_directory(),
+            cores: self.libretro_directory(),
+        }
+    }
+
+    /// Fill the core options of `config` still at their defaults with the
+    /// directories of this install, which runs on `platform`
+    pub fn apply_to(&self, config: &mut Config, platform: Platform) {
+        if let Some(cores) = self.libretro_directory() {
+            config.cores.custom_core_paths.entry(platform).or_insert_with(|| PathBuf::from(&cores));
+            config.cores.installed_cores_directory.get_or_insert_with(|| PathBuf::from(&cores));
+        }
+
+        if let Some(info) = self.libretro_info_path() {
+            config.cores.info_directory.get_or_insert_with(|| PathBuf::from(info));
+        }
+    }
+
+    /// Write playlists into this install's playlist directory unless another
+    /// output directory was configured. Only for a cfg the user named, so an
+    /// auto-discovered install's playlists are never overwritten unasked.
+    pub fn apply_playlist_directory(&self, config: &mut Config) {
+        if config.paths.output_directory == Path::new("./playlists")
+            && let Some(playlists) = self.playlist_directory()
+        {
+            config.paths.output_directory = PathBuf::from(playlists);
+        }
+    }
+}
+
+/// `dir` followed by the rest of a `~`/`:` path, e.g. "/cores" or "\cores",
+/// using the separator of `dir`
+fn join_prefix(dir: &str, rest: &str) -> String {
+    let separator = if dir.contains('\\') { '\\' } else { '/' };
+    let dir = dir.trim_end_matches(['/', '\\']);
+    let rest = rest.trim_start_matches(['/', '\\']).replace(['/', '\\'], &separator.to_string());
+    if rest.is_empty() {
+        dir.to_string()
+    } else {
+        format!("{}{}{}", dir, separator, rest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RETROARCH_CFG: &str = r#"# RetroArch config
+playlist_directory = "~/.config/retroarch/playlists"
+libretro_directory = ":\cores"
+libretro_info_path = ":\info"
+thumbnails_directory = "default"
+rgui_browser_directory = "D:\Games\ROMs"
+system_directory = ""
+video_fullscreen = "true"
+"#;
+
+    #[test]
+    fn test_parse_and_expand_directories() {
+        let config = RetroArchConfig::parse(RETROARCH_CFG);
+
+        // Prefixes are kept until the directories are known
+        assert_eq!(config.libretro_directory().as_deref(), Some(":\\cores"));
+
+        let config = config
+            .with_home_dir(Some("/home/deck".to_string()))
+            .with_application_dir(Some("C:\\RetroArch\\".to_string()));
+        assert_eq!(config.playlist_directory().as_deref(), Some("/home/deck/.config/retroarch/playlists"));
+        assert_eq!(config.libretro_directory().as_deref(), Some("C:\\RetroArch\\cores"));
+        assert_eq!(config.libretro_info_path().as_deref(), Some("C:\\RetroArch\\info"));
+        assert_eq!(config.rgui_browser_directory().as_deref(), Some("D:\\Games\\ROMs"));
+        assert_eq!(config.thumbnails_directory(), None);
+        assert_eq!(config.system_directory(), None);
+        assert_eq!(config.get("video_fullscreen"), Some("true"));
+    }
+
+    #[test]
+    fn test_apply_keeps_explicit_settings() {
+        let retroarch = RetroArchConfig::parse(RETROARCH_CFG)
+            .with_home_dir(Some("/home/deck".to_string()))
+            .with_application_dir(Some("/home/deck/.config/retroarch".to_string()));
+
+        let mut config = Config::default();
+        config.cores.info_directory = Some(PathBuf::from("/opt/info"));
+        retroarch.apply_to(&mut config, Platform::SteamDeck);
+        assert_eq!(config.paths.output_directory, PathBuf::from("./playlists"));
+
+        retroarch.apply_playlist_directory(&mut config);
+        assert_eq!(config.paths.output_directory, PathBuf::from("/home/deck/.config/retroarch/playlists"));
+        assert_eq!(
+            config.cores.custom_core_paths.get(&Platform::SteamDeck),
+            Some(&PathBuf::from("/home/deck/.config/retroarch/cores"))
+        );
+        assert_eq!(config.cores.info_directory, Some(PathBuf::from("/opt/info")));
+    }
+}
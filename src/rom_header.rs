@@ -0,0 +1,319 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use crate::n64::N64ByteOrder;
+
+/// Bytes read from the start of a file for header sniffing.
+/// Large enough to reach the SNES HiROM header behind a 512-byte copier header.
+pub const HEADER_PROBE_SIZE: usize = 0x10200 + 0x40;
+
+/// Compressed Nintendo logo present in every Game Boy / Game Boy Color cartridge at 0x104
+const GB_NINTENDO_LOGO: [u8; 48] = [
+    0xCE, 0xED, 0x66, 0x66, 0xCC, 0x0D, 0x00, 0x0B, 0x03, 0x73, 0x00, 0x83,
+    0x00, 0x0C, 0x00, 0x0D, 0x00, 0x08, 0x11, 0x1F, 0x88, 0x89, 0x00, 0x0E,
+    0xDC, 0xCC, 0x6E, 0xE6, 0xDD, 0xDD, 0xD9, 0x99, 0xBB, 0xBB, 0x67, 0x63,
+    0x6E, 0x0E, 0xEC, 0xCC, 0xDD, 0xDC, 0x99, 0x9F, 0xBB, 0xB9, 0x33, 0x3E,
+];
+
+/// First bytes of the compressed Nintendo logo shared by GBA (0x04) and NDS (0xC0) headers
+const AGB_LOGO_PREFIX: [u8; 8] = [0x24, 0xFF, 0xAE, 0x51, 0x69, 0x9A, 0xA2, 0x21];
+
+/// How a system was identified for a ROM file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DetectionEvidence {
+    /// Magic bytes or internal header structure (description of what matched)
+    Header(String),
+    /// File extension that belongs to a single system
+    Extension,
+    /// Directory or file name heuristics
+    Path,
+}
+
+impl DetectionEvidence {
+    pub fn describe(&self) -> String {
+        match self {
+            DetectionEvidence::Header(what) => format!("header: {}", what),
+            DetectionEvidence::Extension => "extension".to_string(),
+            DetectionEvidence::Path => "path".to_string(),
+        }
+    }
+}
+
+/// Result of system detection together with the evidence that produced it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemDetection {
+    pub system: String,
+    pub evidence: DetectionEvidence,
+    /// Serial or game ID read from the image, when the format carries one
+    pub serial: Option<String>,
+}
+
+impl SystemDetection {
+    pub fn from_header(system: &str, what: &str) -> Self {
+        Self {
+            system: system.to_string(),
+            evidence: DetectionEvidence::Header(what.to_string()),
+            serial: None,
+        }
+    }
+
+    pub fn from_extension(system: &str) -> Self {
+        Self {
+            system: system.to_string(),
+            evidence: DetectionEvidence::Extension,
+            serial: None,
+        }
+    }
+
+    pub fn from_path(system: &str) -> Self {
+        Self {
+            system: system.to_string(),
+            evidence: DetectionEvidence::Path,
+            serial: None,
+        }
+    }
+
+    pub fn with_serial(mut self, serial: Option<String>) -> Self {
+        self.serial = serial;
+        self
+    }
+}
+
+/// Extensions of cartridge formats worth sniffing for an internal header
+pub fn is_sniffable_extension(extension: &str) -> bool {
+    matches!(extension,
+        "nes" | "fds" | "unf" | "unif" |
+        "smc" | "sfc" | "swc" | "fig" |
+        "z64" | "n64" | "v64" |
+        "gb" | "gbc" | "gba" |
+        "nds" | "dsi" | "ids" |
+        "sms" | "sg" | "gg" |
+        "md" | "smd" | "gen" | "bin" | "32x" |
+        "a26" | "a78" | "lnx" | "rom"
+    )
+}
+
+/// Read the first bytes of a file for header sniffing
+pub fn read_header_probe(path: &Path) -> std::io::Result<Vec<u8>> {
+    let file = File::open(path)?;
+    let mut data = Vec::with_capacity(HEADER_PROBE_SIZE);
+    file.take(HEADER_PROBE_SIZE as u64).read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// Identify a cartridge system from the file's leading bytes.
+/// `file_size` is the full size on disk, used to spot 512-byte copier headers.
+pub fn detect_from_header(data: &[u8], file_size: u64) -> Option<SystemDetection> {
+    detect_nes(data)
+        .or_else(|| detect_n64(data))
+        .or_else(|| detect_atari_7800(data))
+        .or_else(|| detect_lynx(data))
+        .or_else(|| detect_nds(data))
+        .or_else(|| detect_gba(data))
+        .or_else(|| detect_game_boy(data))
+        .or_else(|| detect_mega_drive(data))
+        .or_else(|| detect_sega_8bit(data))
+        .or_else(|| detect_snes(data, file_size))
+}
+
+fn detect_nes(data: &[u8]) -> Option<SystemDetection> {
+    const NES: &str = "Nintendo - Nintendo Entertainment System";
+    const FDS: &str = "Nintendo - Family Computer Disk System";
+
+    if data.len() >= 16 && data.starts_with(b"NES\x1A") {
+        // NES 2.0 sets bits 2-3 of byte 7 to 0b10
+        if data[7] & 0x0C == 0x08 {
+            return Some(SystemDetection::from_header(NES, "NES 2.0"));
+        }
+        return Some(SystemDetection::from_header(NES, "iNES"));
+    }
+    if data.starts_with(b"UNIF") {
+        return Some(SystemDetection::from_header(NES, "UNIF"));
+    }
+    if data.starts_with(b"FDS\x1A") {
+        return Some(SystemDetection::from_header(FDS, "fwNES FDS header"));
+    }
+    if data.len() >= 15 && &data[1..15] == b"*NINTENDO-HVC*" {
+        return Some(SystemDetection::from_header(FDS, "FDS disk info block"));
+    }
+    None
+}
+
+fn detect_n64(data: &[u8]) -> Option<SystemDetection> {
+    const N64: &str = "Nintendo - Nintendo 64";
+
+    let order = N64ByteOrder::detect(data)?;
+    Some(SystemDetection::from_header(N64, &format!("N64 {}", order.describe())))
+}
+
+fn detect_atari_7800(data: &[u8]) -> Option<SystemDetection> {
+    if data.get(1..10)? == b"ATARI7800" {
+        return Some(SystemDetection::from_header("Atari - 7800", "A78 header"));
+    }
+    None
+}
+
+fn detect_lynx(data: &[u8]) -> Option<SystemDetection> {
+    if data.starts_with(b"LYNX\0") {
+        return Some(SystemDetection::from_header("Atari - Lynx", "LNX header"));
+    }
+    None
+}
+
+fn detect_nds(data: &[u8]) -> Option<SystemDetection> {
+    // Nintendo logo at 0xC0 and its CRC16 (0xCF56) at 0x15C
+    if data.get(0xC0..0xC8)? == AGB_LOGO_PREFIX && data.get(0x15C..0x15E)? == [0x56, 0xCF] {
+        return Some(SystemDetection::from_header("Nintendo - Nintendo DS", "NDS header logo"));
+    }
+    None
+}
+
+fn detect_gba(data: &[u8]) -> Option<SystemDetection> {
+    if data.len() < 0xC0 || data[0x04..0x0C] != AGB_LOGO_PREFIX || data[0xB2] != 0x96 {
+        return None;
+    }
+
+    // Header complement check over 0xA0..=0xBC
+    let sum = data[0xA0..=0xBC].iter().fold(0u8, |acc, b| acc.wrapping_sub(*b));
+    if sum.wrapping_sub(0x19) == data[0xBD] {
+        return Some(SystemDetection::from_header("Nintendo - Game Boy Advance", "GBA fixed header"));
+    }
+    None
+}
+
+fn detect_game_boy(data: &[u8]) -> Option<SystemDetection> {
+    if data.get(0x104..0x134)? != GB_NINTENDO_LOGO {
+        return None;
+    }
+
+    // CGB flag: 0x80 = enhanced, 0xC0 = Color only
+    match data.get(0x143)? {
+        0x80 | 0xC0 => Some(SystemDetection::from_header("Nintendo - Game Boy Color", "Nintendo logo + CGB flag")),
+        _ => Some(SystemDetection::from_header("Nintendo - Game Boy", "Nintendo logo")),
+    }
+}
+
+fn detect_mega_drive(data: &[u8]) -> Option<SystemDetection> {
+    if let Some(console) = data.get(0x100..0x110)
+        && (console.starts_with(b"SEGA") || console.starts_with(b" SEGA"))
+    {
+        let console = String::from_utf8_lossy(console).to_uppercase();
+        if console.contains("32X") {
+            return Some(SystemDetection::from_header("Sega - 32X", "SEGA 32X at 0x100"));
+        }
+        return Some(SystemDetection::from_header("Sega - Mega Drive - Genesis", "SEGA at 0x100"));
+    }
+
+    // Super Magic Drive interleaved dumps carry a 512-byte header with 0xAA 0xBB at
+    // offset 8 and file type 6. Super Wild Card SNES headers share the 0xAA 0xBB
+    // marker but use type 4 (program) or 5 (SRAM), so the type byte must match.
+    if data.len() > 0x200 && data[8] == 0xAA && data[9] == 0xBB && data[10] == 0x06 {
+        return Some(SystemDetection::from_header("Sega - Mega Drive - Genesis", "SMD copier header"));
+    }
+    None
+}
+
+fn detect_sega_8bit(data: &[u8]) -> Option<SystemDetection> {
+    for base in [0x7FF0usize, 0x3FF0, 0x1FF0] {
+        if data.get(base..base + 8) == Some(b"TMR SEGA".as_slice()) {
+            // Upper nibble of the last header byte is the region code: 3/4 = SMS, 5/6/7 = Game Gear
+            let region = data.get(base + 0x0F).map(|b| b >> 4).unwrap_or(0);
+            return match region {
+                5..=7 => Some(SystemDetection::from_header("Sega - Game Gear", "TMR SEGA header")),
+                _ => Some(SystemDetection::from_header("Sega - Master System - Mark III", "TMR SEGA header")),
+            };
+        }
+    }
+    None
+}
+
+fn detect_snes(data: &[u8], file_size: u64) -> Option<SystemDetection> {
+    const SNES: &str = "Nintendo - Super Nintendo Entertainment System";
+
+    // Copier headers make the file size 512 bytes over a 1KB multiple
+    let copier_offset = if file_size % 1024 == 512 { 0x200 } else { 0 };
+
+    for (base, layout) in [(0x7FC0usize, "SNES LoROM header"), (0xFFC0, "SNES HiROM header")] {
+        let start = base + copier_offset;
+        let Some(header) = data.get(start..start + 0x20) else {
+            continue;
+        };
+
+        let complement = u16::from_le_bytes([header[0x1C], header[0x1D]]);
+        let checksum = u16::from_le_bytes([header[0x1E], header[0x1F]]);
+        let map_mode = header[0x15];
+        let title_ok = header[..21].iter().all(|b| (0x20..0x7F).contains(b) || *b == 0);
+
+        if checksum ^ complement == 0xFFFF && (0x20..=0x3F).contains(&map_mode) && title_ok {
+            return Some(SystemDetection::from_header(SNES, layout));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nes_headers() {
+        let mut data = vec![0u8; 32];
+        data[..4].copy_from_slice(b"NES\x1A");
+        assert_eq!(detect_from_header(&data, 32).unwrap().evidence, DetectionEvidence::Header("iNES".to_string()));
+
+        data[7] = 0x08;
+        assert_eq!(detect_from_header(&data, 32).unwrap().evidence, DetectionEvidence::Header("NES 2.0".to_string()));
+    }
+
+    #[test]
+    fn test_game_boy_color_flag() {
+        let mut data = vec![0u8; 0x150];
+        data[0x104..0x134].copy_from_slice(&GB_NINTENDO_LOGO);
+        assert_eq!(detect_from_header(&data, 0x150).unwrap().system, "Nintendo - Game Boy");
+
+        data[0x143] = 0xC0;
+        assert_eq!(detect_from_header(&data, 0x150).unwrap().system, "Nintendo - Game Boy Color");
+    }
+
+    #[test]
+    fn test_mega_drive_and_32x() {
+        let mut data = vec![0u8; 0x200];
+        data[0x100..0x110].copy_from_slice(b"SEGA MEGA DRIVE ");
+        assert_eq!(detect_from_header(&data, 0x200).unwrap().system, "Sega - Mega Drive - Genesis");
+
+        data[0x100..0x110].copy_from_slice(b"SEGA 32X        ");
+        assert_eq!(detect_from_header(&data, 0x200).unwrap().system, "Sega - 32X");
+    }
+
+    #[test]
+    fn test_smd_copier_header() {
+        let mut data = vec![0u8; 0x400];
+        data[8..11].copy_from_slice(&[0xAA, 0xBB, 0x06]);
+        let detection = detect_from_header(&data, 0x400).unwrap();
+        assert_eq!(detection.system, "Sega - Mega Drive - Genesis");
+        assert_eq!(detection.evidence, DetectionEvidence::Header("SMD copier header".to_string()));
+    }
+
+    #[test]
+    fn test_snes_lorom_with_copier_header() {
+        let size = 0x200 + 0x8000;
+        let mut data = vec![0u8; size];
+        // Super Wild Card header: same 0xAA 0xBB marker as SMD dumps, type 4
+        data[8..11].copy_from_slice(&[0xAA, 0xBB, 0x04]);
+        let base = 0x200 + 0x7FC0;
+        data[base..base + 21].copy_from_slice(b"SUPER MARIOWORLD     ");
+        data[base + 0x15] = 0x20;
+        data[base + 0x1C..base + 0x1E].copy_from_slice(&0x5F25u16.to_le_bytes());
+        data[base + 0x1E..base + 0x20].copy_from_slice(&0xA0DAu16.to_le_bytes());
+
+        let detection = detect_from_header(&data, size as u64).unwrap();
+        assert_eq!(detection.system, "Nintendo - Super Nintendo Entertainment System");
+        assert_eq!(detection.evidence, DetectionEvidence::Header("SNES LoROM header".to_string()));
+    }
+
+    #[test]
+    fn test_unknown_data() {
+        assert!(detect_from_header(&[0u8; 64], 64).is_none());
+    }
+}
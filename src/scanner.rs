@@ -1,13 +1,28 @@
 use anyhow::{Result, Context};
-use dashmap::DashMap;
 use log::{debug, info, warn};
 use rayon::prelude::*;
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use walkdir::WalkDir;
 
-use crate::crc32::calculate_crc32;
+use crate::archive::{ArchiveEntry, VirtualRomFile};
+#[cfg(feature = "archive-support")]
+use crate::archive::{ArchiveFormat, ArchiveReader, ArchiveReaderFactory};
+#[cfg(feature = "archive-support")]
+use crate::cache::entry_fingerprint;
+#[cfg(feature = "archive-support")]
+use crate::crc32::calculate_crc32_variants_from_reader;
+use crate::cache::{content_fingerprint, CrcCache, FileStamp, SharedCrcCache};
+use crate::crc32::{calculate_crc32_variants_with, CrcVariants, Digests, HashAlgorithms, HashCandidate, HashOptions};
+use crate::disc;
+use crate::disc_sheet::{DiscSheet, SheetKind};
 use crate::error::ScannerError;
+use crate::header_skipper::SkipperSet;
+#[cfg(feature = "archive-support")]
+use crate::n64::is_n64_extension;
+use crate::rom_header::{self, DetectionEvidence, SystemDetection};
+use crate::system_registry::SystemRegistry;
 use crate::thread_monitor::{ThreadMonitor, ThreadStatus};
 
 #[derive(Debug, Clone)]
@@ -17,8 +32,29 @@ pub struct RomFile {
     pub extension: String,
     pub size: u64,
     pub crc32: Option<u32>,
+    /// CRC32 with the copier/dumper header stripped, when a header skipper applied
+    pub crc32_headerless: Option<u32>,
+    /// Size/MD5/SHA1 of the stream behind `crc32`
+    pub digests: Option<Digests>,
+    /// Size/MD5/SHA1 of the stream behind `crc32_headerless`
+    pub digests_headerless: Option<Digests>,
     pub system: Option<String>,
+    /// Evidence that produced `system`
+    pub detection: Option<DetectionEvidence>,
+    /// Serial/game ID extracted from a disc image
+    pub serial: Option<String>,
     pub is_archive: bool,
+    /// Entry name when this ROM lives inside the ZIP/7z archive at `path`
+    pub archive_entry: Option<String>,
+    /// Track files referenced by a CUE/GDI sheet, or the discs (and their tracks)
+    /// listed in an M3U; the sheet and its tracks form one unit
+    pub tracks: Vec<PathBuf>,
+    /// Track files referenced by the sheet but missing on disk
+    pub missing_tracks: Vec<PathBuf>,
+    /// CRC32 of every track after the first, in `tracks` order
+    pub track_crc32s: Vec<u32>,
+    /// Directory the scan started from; path rules ignore the folders above it
+    pub scan_root: Option<PathBuf>,
 }
 
 impl RomFile {
@@ -39,101 +75,202 @@ impl RomFile {
             extension,
             size: 0,
             crc32: None,
+            crc32_headerless: None,
+            digests: None,
+            digests_headerless: None,
             system: None,
+            detection: None,
+            serial: None,
             is_archive: false,
+            archive_entry: None,
+            tracks: Vec::new(),
+            missing_tracks: Vec::new(),
+            track_crc32s: Vec::new(),
+            scan_root: None,
         }
     }
 
-    pub fn detect_system(&self) -> Option<String> {
-        match self.extension.as_str() {
-            // Nintendo
-            "nes" | "fds" | "unf" | "unif" => Some("Nintendo - Nintendo Entertainment System".to_string()),
-            "smc" | "sfc" | "swc" | "fig" => Some("Nintendo - Super Nintendo Entertainment System".to_string()),
-            "z64" | "n64" | "v64" => Some("Nintendo - Nintendo 64".to_string()),
-            "gcm" | "iso" | "gcz" | "rvz" if self.is_gamecube() => Some("Nintendo - GameCube".to_string()),
-            "gb" => Some("Nintendo - Game Boy".to_string()),
-            "gbc" => Some("Nintendo - Game Boy Color".to_string()),
-            "gba" => Some("Nintendo - Game Boy Advance".to_string()),
-            "nds" | "dsi" | "ids" => Some("Nintendo - Nintendo DS".to_string()),
-            "3ds" | "cci" | "cxi" => Some("Nintendo - Nintendo 3DS".to_string()),
-
-            // Sega
-            "sms" | "sg" => Some("Sega - Master System - Mark III".to_string()),
-            "md" | "smd" | "gen" | "bin" if self.is_genesis() => Some("Sega - Mega Drive - Genesis".to_string()),
-            "32x" => Some("Sega - 32X".to_string()),
-            "gg" => Some("Sega - Game Gear".to_string()),
-            "cdi" | "gdi" | "chd" if self.is_dreamcast() => Some("Sega - Dreamcast".to_string()),
-
-            // Sony
-            "iso" | "cue" | "bin" | "img" | "pbp" | "chd" if self.is_playstation() => Some("Sony - PlayStation".to_string()),
-            "iso" | "bin" | "mdf" | "nrg" if self.is_ps2() => Some("Sony - PlayStation 2".to_string()),
-            "iso" | "cso" | "pbp" if self.is_psp() => Some("Sony - PlayStation Portable".to_string()),
-
-            // Atari
-            "a26" | "bin" if self.is_atari2600() => Some("Atari - 2600".to_string()),
-            "a78" => Some("Atari - 7800".to_string()),
-
-            // Others
-            "pce" | "sgx" => Some("NEC - PC Engine - TurboGrafx 16".to_string()),
-            "ws" | "wsc" => Some("Bandai - WonderSwan".to_string()),
-            "neo" => Some("SNK - Neo Geo".to_string()),
+    /// ROM stored as `entry` inside the archive at `archive_path`
+    pub fn from_archive_entry(archive_path: &Path, entry: &ArchiveEntry) -> Self {
+        let mut rom = Self::new(archive_path.to_path_buf());
+        rom.filename = Path::new(&entry.name)
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        rom.extension = entry.extension.clone();
+        rom.size = entry.size;
+        rom.is_archive = true;
+        rom.archive_entry = Some(entry.name.clone());
+        rom
+    }
 
-            // Archives - handle with better priority logic
-            "zip" | "7z" | "rar" => {
-                // First, check if it's explicitly in a MAME directory structure
-                if self.is_mame() {
-                    Some("MAME".to_string())
-                } else {
-                    // For archives not in MAME directories, be more conservative
-                    // Only detect other systems if there are very clear indicators
-                    self.detect_system_from_path_conservative()
-                }
-            },
+    /// Key of this ROM in the CRC cache: the hashed file, or the playlist path of an archive entry
+    pub fn cache_key(&self) -> PathBuf {
+        match &self.archive_entry {
+            Some(_) => self.playlist_path(),
+            None => self.hash_path().to_path_buf(),
+        }
+    }
 
-            _ => None,
+    /// Path written to playlists: `archive.zip#inner.rom` for archive entries
+    pub fn playlist_path(&self) -> PathBuf {
+        match &self.archive_entry {
+            Some(entry) => VirtualRomFile::entry_path(&self.path, entry),
+            None => self.path.clone(),
         }
     }
 
-    fn is_gamecube(&self) -> bool {
-        // Heuristic: check path for gamecube indicators
-        let path_str = self.path.to_string_lossy().to_lowercase();
-        path_str.contains("gamecube") || path_str.contains("gc") || path_str.contains("ngc")
+    fn crc_variants(&self) -> Option<CrcVariants> {
+        self.crc32.map(|raw| CrcVariants {
+            raw,
+            headerless: self.crc32_headerless,
+            skipper: None,
+            raw_digests: self.digests.clone(),
+            headerless_digests: self.digests_headerless.clone(),
+        })
     }
 
-    fn is_genesis(&self) -> bool {
-        let path_str = self.path.to_string_lossy().to_lowercase();
-        path_str.contains("genesis") || path_str.contains("megadrive") || path_str.contains("md")
+    /// CRCs with the size and digests of each stream, for multi-hash DAT lookup
+    pub fn hash_candidates(&self) -> Vec<HashCandidate> {
+        self.crc_variants()
+            .map(|variants| variants.hash_candidates())
+            .unwrap_or_default()
     }
 
-    fn is_dreamcast(&self) -> bool {
-        let path_str = self.path.to_string_lossy().to_lowercase();
-        path_str.contains("dreamcast") || path_str.contains("dc")
+    fn apply_crc_variants(&mut self, variants: &CrcVariants) {
+        self.crc32 = Some(variants.raw);
+        self.crc32_headerless = variants.headerless;
+        self.digests = variants.raw_digests.clone();
+        self.digests_headerless = variants.headerless_digests.clone();
     }
 
-    fn is_playstation(&self) -> bool {
-        let path_str = self.path.to_string_lossy().to_lowercase();
-        path_str.contains("playstation") || path_str.contains("psx") || path_str.contains("ps1")
+    pub fn is_disc_sheet(&self) -> bool {
+        SheetKind::from_extension(&self.extension).is_some()
     }
 
-    fn is_ps2(&self) -> bool {
-        let path_str = self.path.to_string_lossy().to_lowercase();
-        path_str.contains("ps2") || path_str.contains("playstation2")
+    /// File whose contents identify the ROM: the first track of a disc sheet, the file itself otherwise
+    pub fn hash_path(&self) -> &Path {
+        self.tracks.first().map(PathBuf::as_path).unwrap_or(&self.path)
     }
 
-    fn is_psp(&self) -> bool {
-        let path_str = self.path.to_string_lossy().to_lowercase();
-        path_str.contains("psp") || path_str.contains("playstation portable")
+    /// Every file that makes up this ROM (the sheet plus its tracks)
+    pub fn files(&self) -> Vec<&Path> {
+        std::iter::once(self.path.as_path())
+            .chain(self.tracks.iter().map(PathBuf::as_path))
+            .collect()
     }
 
-    fn is_atari2600(&self) -> bool {
-        let path_str = self.path.to_string_lossy().to_lowercase();
-        path_str.contains("2600") || path_str.contains("atari")
+    /// Resolve the tracks of a CUE/GDI/M3U sheet and add their sizes to the ROM size
+    pub fn attach_disc_tracks(&mut self) {
+        if !self.is_disc_sheet() {
+            return;
+        }
+
+        match DiscSheet::load(&self.path) {
+            Ok(sheet) => {
+                self.tracks = sheet.present_files();
+                self.missing_tracks = sheet.missing_files();
+                self.size += self.tracks.iter()
+                    .filter_map(|track| std::fs::metadata(track).ok())
+                    .map(|m| m.len())
+                    .sum::<u64>();
+
+                for missing in &self.missing_tracks {
+                    warn!("Faixa ausente em {}: {}", self.path.display(), missing.display());
+                }
+            }
+            Err(e) => warn!("Falha ao ler {}: {}", self.path.display(), e),
+        }
     }
 
-    fn is_mame(&self) -> bool {
+    /// Detect the system and store it together with the evidence used
+    pub fn identify_system(&mut self) {
+        match self.detect_system_with_evidence() {
+            Some(detection) => {
+                self.system = Some(detection.system);
+                self.detection = Some(detection.evidence);
+                self.serial = detection.serial;
+            }
+            None => {
+                self.system = None;
+                self.detection = None;
+                self.serial = None;
+            }
+        }
+    }
+
+    pub fn detect_system(&self) -> Option<String> {
+        self.detect_system_with_evidence().map(|detection| detection.system)
+    }
+
+    /// Header evidence takes priority; extension and path heuristics are the fallback
+    pub fn detect_system_with_evidence(&self) -> Option<SystemDetection> {
+        self.detect_system_from_header()
+            .or_else(|| self.detect_system_from_disc())
+            .or_else(|| self.detect_system_from_extension())
+    }
+
+    fn detect_system_from_disc(&self) -> Option<SystemDetection> {
+        if self.archive_entry.is_some() || !disc::is_inspectable_extension(&self.extension) {
+            return None;
+        }
+        disc::inspect_disc(&self.path)
+    }
+
+    fn detect_system_from_header(&self) -> Option<SystemDetection> {
+        // Archive entries are identified by their inner extension and CRC
+        if self.archive_entry.is_some() {
+            return None;
+        }
+
+        let registry = SystemRegistry::global();
+        if !rom_header::is_sniffable_extension(&self.extension) && !registry.has_magic_rules(&self.extension) {
+            return None;
+        }
+
+        let data = match rom_header::read_header_probe(&self.path) {
+            Ok(data) => data,
+            Err(e) => {
+                debug!("Falha ao ler header de {}: {}", self.path.display(), e);
+                return None;
+            }
+        };
+
+        let file_size = if self.size > 0 {
+            self.size
+        } else {
+            std::fs::metadata(&self.path).map(|m| m.len()).unwrap_or(data.len() as u64)
+        };
+
+        rom_header::detect_from_header(&data, file_size)
+            .or_else(|| registry.detect_from_magic(&self.extension, &data))
+    }
+
+    fn detect_system_from_extension(&self) -> Option<SystemDetection> {
+        match self.extension.as_str() {
+            // Archives - handle with better priority logic
+            "zip" | "7z" | "rar" => {
+                let path = SystemDetection::from_path;
+                // First, check if it's explicitly in a MAME directory structure
+                if self.is_mame() {
+                    Some(path("MAME"))
+                } else {
+                    // For archives not in MAME directories, be more conservative
+                    // Only detect other systems if there are very clear indicators
+                    self.detect_system_from_path_conservative().map(|system| path(&system))
+                }
+            },
+
+            // Extensions and path rules come from configs/systems.toml
+            extension => SystemRegistry::global().detect_from_extension(extension, &self.path, self.scan_root.as_deref()),
+        }
+    }
+
+    /// Whether the path sits in a MAME/arcade directory structure
+    fn is_in_arcade_dir(&self) -> bool {
         let path_str = self.path.to_string_lossy().to_lowercase();
-        let filename = self.filename.to_lowercase();
-        
+
         // Check for common MAME directory names and patterns
         if path_str.contains("mame") || 
            path_str.contains("arcade") ||
@@ -154,6 +291,38 @@ impl RomFile {
             return true;
         }
         
+        false
+    }
+
+    /// MAME/FBNeo sets keep every chip dump of a machine in one archive and are
+    /// indexed whole instead of being split into `set.zip#chip` entries
+    #[cfg(feature = "archive-support")]
+    fn is_arcade_set(&self, entries: &[ArchiveEntry]) -> bool {
+        if self.is_in_arcade_dir() {
+            return true;
+        }
+
+        // Chip dumps are named after board locations (.p1, .u12, .6e), not console extensions
+        let has_chip_dumps = entries.iter()
+            .any(|entry| !entry.is_rom && !matches!(entry.extension.as_str(), "txt" | "nfo" | "diz" | "pdf" | "md" | "jpg" | "png"));
+        if has_chip_dumps {
+            return true;
+        }
+
+        // Sets made only of generic .bin/.rom dumps fall back to the filename heuristic
+        let generic_only = entries.iter()
+            .filter(|entry| entry.is_rom)
+            .all(|entry| matches!(entry.extension.as_str(), "bin" | "rom"));
+        generic_only && self.is_mame()
+    }
+
+    fn is_mame(&self) -> bool {
+        if self.is_in_arcade_dir() {
+            return true;
+        }
+
+        let filename = self.filename.to_lowercase();
+        
         // For ZIP/7Z files, use a more permissive approach
         // Most ZIP files in emulation contexts are MAME ROMs
         if self.extension == "zip" || self.extension == "7z" {
@@ -283,7 +452,9 @@ pub struct Scanner {
     calculate_crc: bool,
     extensions: Option<Vec<String>>,
     verbose: bool,
-    crc_cache: Arc<DashMap<PathBuf, u32>>,
+    crc_cache: SharedCrcCache,
+    header_skippers: Arc<SkipperSet>,
+    hash_options: HashOptions,
 }
 
 impl Scanner {
@@ -294,7 +465,9 @@ impl Scanner {
             calculate_crc: true,
             extensions: None,
             verbose: false,
-            crc_cache: Arc::new(DashMap::new()),
+            crc_cache: CrcCache::new().into_shared(),
+            header_skippers: Arc::new(SkipperSet::builtin()),
+            hash_options: HashOptions::default(),
         }
     }
 
@@ -323,6 +496,24 @@ impl Scanner {
         self
     }
 
+    /// Header skippers used to compute headerless CRCs
+    pub fn with_header_skippers(mut self, skippers: SkipperSet) -> Self {
+        self.header_skippers = Arc::new(skippers);
+        self
+    }
+
+    /// Persistent CRC cache shared with other scanners and commands
+    pub fn with_crc_cache(mut self, cache: SharedCrcCache) -> Self {
+        self.crc_cache = cache;
+        self
+    }
+
+    /// Digests computed alongside the CRC32 in the same read of each file
+    pub fn with_hash_algorithms(mut self, algorithms: HashAlgorithms) -> Self {
+        self.hash_options.algorithms = algorithms;
+        self
+    }
+
     pub fn scan_directory(&self, dir: &Path) -> Result<Vec<RomFile>> {
         if !dir.exists() {
             return Err(ScannerError::DirectoryNotFound(dir.to_path_buf()).into());
@@ -350,6 +541,7 @@ impl Scanner {
             .map(|entry| entry.path().to_path_buf())
             .filter(|path| self.is_rom_file(path))
             .collect();
+        let file_paths = collapse_disc_tracks(file_paths);
 
         if file_paths.is_empty() {
             warn!("Nenhum arquivo de ROM encontrado em {}", dir.display());
@@ -366,7 +558,7 @@ impl Scanner {
             file_paths
                 .par_iter()
                 .enumerate()
-                .filter_map(|(index, path)| {
+                .flat_map_iter(|(index, path)| {
                     let thread_id = rayon::current_thread_index().unwrap_or(0);
                     
                     // Register thread if first time
@@ -376,18 +568,14 @@ impl Scanner {
                     monitor.set_main_message(&format!("Processando arquivo {} de {}", index + 1, file_paths.len()));
                     
                     // Process the file with detailed status updates
-                    match self.process_file_with_monitor(path, &monitor, thread_id) {
-                        Ok(Some(rom)) => {
-                            monitor.update_thread_status(thread_id, ThreadStatus::ProcessingComplete(path.display().to_string()));
-                            Some(rom)
-                        },
-                        Ok(None) => {
+                    match self.process_file_with_monitor(path, Some(dir), &monitor, thread_id) {
+                        Ok(roms) => {
                             monitor.update_thread_status(thread_id, ThreadStatus::ProcessingComplete(path.display().to_string()));
-                            None
+                            roms
                         },
                         Err(e) => {
                             monitor.update_thread_status(thread_id, ThreadStatus::Error(path.display().to_string(), e.to_string()));
-                            None
+                            Vec::new()
                         }
                     }
                 })
@@ -395,6 +583,7 @@ impl Scanner {
         });
 
         monitor.finish("✅ Escaneamento concluído");
+        self.save_crc_cache();
 
         info!("Processados {} arquivos de ROM", roms.len());
 
@@ -419,9 +608,22 @@ impl Scanner {
             .filter_map(|entry| entry.ok())
             .filter(|entry| entry.file_type().is_file())
             .map(|entry| entry.path().to_path_buf())
-            .filter(|path| self.is_rom_file(path))
             .collect();
 
+        self.scan_files(&[dir.to_path_buf()], file_paths)
+    }
+
+    /// Processa apenas os arquivos informados, como os alterados no modo watch.
+    /// Faixas de um CUE/GDI presente na lista são agrupadas na folha; `roots`
+    /// são os diretórios varridos, e o mais interno que contém cada arquivo
+    /// limita as regras de caminho.
+    pub fn scan_files(&self, roots: &[PathBuf], file_paths: Vec<PathBuf>) -> Result<Vec<RomFile>> {
+        let file_paths: Vec<PathBuf> = file_paths
+            .into_iter()
+            .filter(|path| path.is_file() && self.is_rom_file(path))
+            .collect();
+        let file_paths = collapse_disc_tracks(file_paths);
+
         if file_paths.is_empty() {
             return Ok(Vec::new());
         }
@@ -436,15 +638,16 @@ impl Scanner {
         let roms: Vec<RomFile> = pool.install(|| {
             file_paths
                 .par_iter()
-                .filter_map(|path| {
-                    match self.process_file_simple(path) {
-                        Ok(Some(rom)) => Some(rom),
-                        Ok(None) => None,
-                        Err(_) => None, // Ignora erros silenciosamente para performance
-                    }
+                .flat_map_iter(|path| {
+                    // Ignora erros silenciosamente para performance
+                    let root = roots.iter()
+                        .filter(|root| path.starts_with(root))
+                        .max_by_key(|root| root.components().count());
+                    self.process_file_simple(path, root.map(PathBuf::as_path)).unwrap_or_default()
                 })
                 .collect()
         });
+        self.save_crc_cache();
 
         Ok(roms)
     }
@@ -459,39 +662,189 @@ impl Scanner {
             return allowed_extensions.iter().any(|ext| ext.to_lowercase() == extension);
         }
 
-        // Default supported extensions
-        matches!(extension.as_str(),
-            // Nintendo
-            "nes" | "fds" | "unf" | "unif" |
-            "smc" | "sfc" | "swc" | "fig" |
-            "z64" | "n64" | "v64" |
-            "gcm" | "iso" | "gcz" | "rvz" |
-            "gb" | "gbc" | "gba" |
-            "nds" | "dsi" | "ids" |
-            "3ds" | "cci" | "cxi" |
-            
-            // Sega
-            "sms" | "sg" |
-            "md" | "smd" | "gen" | "bin" |
-            "32x" | "gg" |
-            "cdi" | "gdi" | "chd" |
-            
-            // Sony
-            "pbp" | "cue" | "img" | "mdf" | "nrg" | "cso" |
-            
-            // Atari
-            "a26" | "a78" |
-            
-            // Others
-            "pce" | "sgx" | "ws" | "wsc" | "neo" |
-            
-            // Archives
-            "zip" | "7z" | "rar"
-        )
+        // Default supported extensions: every extension claimed in configs/systems.toml,
+        // plus archives and M3U multi-disc lists
+        matches!(extension.as_str(), "zip" | "7z" | "rar" | "m3u")
+            || SystemRegistry::global().is_known_extension(&extension)
+    }
+
+    /// Hashes of the ROM from the CRC cache when its file is unchanged,
+    /// otherwise computed by `compute` and stored
+    fn cached_crc_variants(
+        &self,
+        rom: &RomFile,
+        compute: impl FnOnce(&Path) -> Result<CrcVariants>,
+    ) -> Result<CrcVariants> {
+        self.cached_file_variants(rom.hash_path(), &rom.cache_key(), compute)
     }
 
-    fn process_file(&self, path: &Path) -> Result<Option<RomFile>> {
+    fn cached_file_variants(
+        &self,
+        hash_path: &Path,
+        key: &Path,
+        compute: impl FnOnce(&Path) -> Result<CrcVariants>,
+    ) -> Result<CrcVariants> {
+        let stamp = FileStamp::read(hash_path)?;
+
+        let algorithms = self.hash_options.algorithms;
+        if let Some(cached) = self.crc_cache.lock().lookup(key, stamp).filter(|cached| cached.covers(algorithms)) {
+            return Ok(cached);
+        }
+
+        // Same content under another path: the file was renamed or moved.
+        // Fingerprint, size and modification time all survive a move, so the
+        // file is only read in full when one of them disagrees.
+        let stamp = stamp.with_fingerprint(content_fingerprint(hash_path).ok());
+        let moved = self.crc_cache.lock().moved_entry(key, stamp).filter(|cached| cached.covers(algorithms));
+        if let Some(cached) = moved
+            && self.crc_cache.lock().relink(key, stamp).is_some()
+        {
+            return Ok(cached);
+        }
+
+        let variants = compute(hash_path)?;
+        self.store_crc_variants(key, stamp, &variants);
+        Ok(variants)
+    }
+
+    /// CRCs of the tracks after the first one, which DATs list as separate
+    /// entries, through the same cache as the ROM itself
+    fn hash_remaining_tracks(&self, rom: &mut RomFile) {
+        rom.track_crc32s = rom.tracks
+            .iter()
+            .skip(1)
+            .filter_map(|track| {
+                self.cached_file_variants(track, track, |path| {
+                    calculate_crc32_variants_with(path, &self.header_skippers, &self.hash_options, None)
+                })
+                .map_err(|e| warn!("Falha ao calcular CRC32 para {}: {}", track.display(), e))
+                .ok()
+            })
+            .map(|variants| variants.raw)
+            .collect();
+    }
+
+    /// Store freshly computed hashes. The cache is saved every few hundred
+    /// entries so an interrupted scan keeps what it already hashed.
+    fn store_crc_variants(&self, key: &Path, stamp: FileStamp, variants: &CrcVariants) {
+        let mut cache = self.crc_cache.lock();
+        cache.insert(key, stamp, variants);
+        if let Err(e) = cache.checkpoint() {
+            warn!("Falha ao salvar cache de CRC: {}", e);
+        }
+    }
+
+    fn save_crc_cache(&self) {
+        if let Err(e) = self.crc_cache.lock().auto_save() {
+            warn!("Falha ao salvar cache de CRC: {}", e);
+        }
+    }
+
+    /// ROMs inside a ZIP/7z archive, or None when the archive is indexed as a
+    /// whole: MAME/arcade sets, archives without a known ROM inside, unreadable
+    /// archives and builds without `archive-support`
+    fn archive_entries(&self, path: &Path, root: Option<&Path>, progress: impl FnMut(usize, usize)) -> Option<Vec<RomFile>> {
+        match self.expand_archive(path, root, progress) {
+            Ok(roms) => roms,
+            Err(e) => {
+                warn!("Falha ao listar {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    #[cfg(feature = "archive-support")]
+    fn expand_archive(&self, path: &Path, root: Option<&Path>, mut progress: impl FnMut(usize, usize)) -> Result<Option<Vec<RomFile>>> {
+        if ArchiveFormat::detect_from_path(path) == ArchiveFormat::Unknown {
+            return Ok(None);
+        }
+
+        let mut reader = ArchiveReaderFactory::create_from_file(path)?;
+        let entries = reader.list_entries()?;
+        if RomFile::new(path.to_path_buf()).is_arcade_set(&entries) {
+            return Ok(None);
+        }
+
+        let entries: Vec<ArchiveEntry> = entries.into_iter().filter(|entry| entry.is_rom).collect();
+        if entries.is_empty() {
+            return Ok(None);
+        }
+
+        let stamp = FileStamp::read(path)?.with_fingerprint(content_fingerprint(path).ok());
+
+        let mut roms = Vec::with_capacity(entries.len());
+        for (index, entry) in entries.iter().enumerate() {
+            let mut rom = RomFile::from_archive_entry(path, entry);
+            rom.scan_root = root.map(Path::to_path_buf);
+            if self.calculate_crc {
+                self.hash_archive_entry(&mut rom, entry, reader.as_mut(), stamp);
+            }
+            rom.identify_system();
+            roms.push(rom);
+            progress(index + 1, entries.len());
+        }
+
+        debug!("{} ROMs encontradas em {}", roms.len(), path.display());
+        Ok(Some(roms))
+    }
+
+    #[cfg(not(feature = "archive-support"))]
+    fn expand_archive(&self, _path: &Path, _root: Option<&Path>, _progress: impl FnMut(usize, usize)) -> Result<Option<Vec<RomFile>>> {
+        Ok(None)
+    }
+
+    /// Use the CRC stored in the archive headers when it covers the bytes DATs
+    /// hash; headered and N64 images are streamed through the hashing engine
+    #[cfg(feature = "archive-support")]
+    fn hash_archive_entry(&self, rom: &mut RomFile, entry: &ArchiveEntry, reader: &mut dyn ArchiveReader, stamp: FileStamp) {
+        let key = rom.cache_key();
+        let stamp = stamp.with_fingerprint(stamp.fingerprint.map(|fingerprint| entry_fingerprint(fingerprint, &entry.name)));
+        let needs_stream = self.header_skippers.handles_extension(&entry.extension)
+            || is_n64_extension(&entry.extension);
+        let algorithms = self.hash_options.algorithms;
+        let cached = self.crc_cache.lock().lookup(&key, stamp).filter(|cached| cached.covers(algorithms));
+        let cached = cached.or_else(|| {
+            // Moved archive: the stamp matched, and the CRC32 in the entry
+            // header costs nothing to compare when the archive has one
+            let moved = self.crc_cache.lock().moved_entry(&key, stamp).filter(|cached| cached.covers(algorithms))?;
+            (entry.crc32.is_none_or(|crc32| crc32 == moved.raw) && self.crc_cache.lock().relink(&key, stamp).is_some())
+                .then_some(moved)
+        });
+        if let Some(cached) = cached {
+            rom.apply_crc_variants(&cached);
+            return;
+        }
+
+        let variants = match entry.crc32 {
+            Some(crc32) if !needs_stream => Ok(CrcVariants {
+                raw: crc32,
+                raw_digests: Some(Digests { size: entry.size, ..Default::default() }),
+                ..Default::default()
+            }),
+            _ => reader.get_entry_reader(&entry.name).and_then(|mut data| {
+                calculate_crc32_variants_from_reader(
+                    &mut data, &entry.extension, entry.size, &self.header_skippers, &self.hash_options,
+                )
+            }),
+        };
+
+        match variants {
+            Ok(variants) => {
+                rom.apply_crc_variants(&variants);
+                debug!("CRC32 de {}: {:08X}", key.display(), variants.raw);
+                self.store_crc_variants(&key, stamp, &variants);
+            }
+            Err(e) => warn!("Falha ao calcular CRC32 para {}: {}", key.display(), e),
+        }
+    }
+
+    fn process_file(&self, path: &Path, root: Option<&Path>) -> Result<Vec<RomFile>> {
+        if let Some(roms) = self.archive_entries(path, root, |_, _| {}) {
+            return Ok(roms);
+        }
+
         let mut rom = RomFile::new(path.to_path_buf());
+        rom.scan_root = root.map(Path::to_path_buf);
 
         // Get file metadata
         let metadata = std::fs::metadata(path)
@@ -500,53 +853,91 @@ impl Scanner {
 
         // Detect if it's an archive
         rom.is_archive = matches!(rom.extension.as_str(), "zip" | "7z" | "rar");
+        rom.attach_disc_tracks();
 
         // Calculate CRC32 if requested and not cached
         if self.calculate_crc {
-            if let Some(cached_crc) = self.crc_cache.get(path) {
-                rom.crc32 = Some(*cached_crc);
-                debug!("CRC32 do cache para {}: {:08X}", path.display(), *cached_crc);
-            } else {
-                match calculate_crc32(path) {
-                    Ok(crc) => {
-                        rom.crc32 = Some(crc);
-                        self.crc_cache.insert(path.to_path_buf(), crc);
-                        debug!("CRC32 calculado para {}: {:08X}", path.display(), crc);
-                    }
-                    Err(e) => {
-                        warn!("Falha ao calcular CRC32 para {}: {}", path.display(), e);
+            let computed = self.cached_crc_variants(&rom, |hash_path| {
+                calculate_crc32_variants_with(hash_path, &self.header_skippers, &self.hash_options, None)
+            });
+            match computed {
+                Ok(variants) => {
+                    rom.apply_crc_variants(&variants);
+                    self.hash_remaining_tracks(&mut rom);
+                    debug!("CRC32 para {}: {:08X}", path.display(), variants.raw);
+                    if let (Some(headerless), Some(skipper)) = (variants.headerless, &variants.skipper) {
+                        debug!("CRC32 sem header ({}) para {}: {:08X}", skipper, path.display(), headerless);
                     }
                 }
+                Err(e) => {
+                    warn!("Falha ao calcular CRC32 para {}: {}", path.display(), e);
+                }
             }
         }
 
         // Detect system
-        rom.system = rom.detect_system();
+        rom.identify_system();
 
         if self.verbose {
             debug!(
-                "Processado: {} | Sistema: {} | CRC32: {} | Tamanho: {} bytes",
+                "Processado: {} | Sistema: {} ({}) | CRC32: {} | Tamanho: {} bytes",
                 rom.filename,
                 rom.system.as_deref().unwrap_or("Desconhecido"),
+                rom.detection.as_ref().map(|d| d.describe()).unwrap_or_else(|| "-".to_string()),
                 rom.crc32.map(|c| format!("{:08X}", c)).unwrap_or_else(|| "N/A".to_string()),
                 rom.size
             );
         }
 
-        Ok(Some(rom))
+        Ok(vec![rom])
+    }
+
+    /// Calcula os hashes reportando o progresso de arquivos grandes ao monitor
+    fn hash_with_progress(&self, path: &Path, monitor: &ThreadMonitor, thread_id: usize) -> Result<CrcVariants> {
+        let file = path.display().to_string();
+        let mut last_percent = 0u64;
+        let mut report = |done: u64, total: u64| {
+            // Só atualiza a barra quando o percentual inteiro muda
+            let percent = done * 100 / total.max(1);
+            if percent > last_percent {
+                last_percent = percent;
+                monitor.update_thread_status(
+                    thread_id,
+                    ThreadStatus::HashingFile { file: file.clone(), progress: percent as f32 },
+                );
+            }
+        };
+
+        calculate_crc32_variants_with(path, &self.header_skippers, &self.hash_options, Some(&mut report))
     }
 
     /// Processa um arquivo com monitoramento detalhado de thread
     fn process_file_with_monitor(
         &self, 
         path: &Path, 
+        root: Option<&Path>,
         monitor: &ThreadMonitor, 
         thread_id: usize
-    ) -> Result<Option<RomFile>> {
+    ) -> Result<Vec<RomFile>> {
         // Update status: scanning file
         monitor.update_thread_status(thread_id, ThreadStatus::ScanningFile(path.display().to_string()));
 
+        // Archive entries are reported as they are listed and hashed
+        let report = |done: usize, total: usize| {
+            monitor.update_thread_status(
+                thread_id,
+                ThreadStatus::ExtractingArchive {
+                    file: path.display().to_string(),
+                    progress: done as f32 * 100.0 / total as f32,
+                },
+            );
+        };
+        if let Some(roms) = self.archive_entries(path, root, report) {
+            return Ok(roms);
+        }
+
         let mut rom = RomFile::new(path.to_path_buf());
+        rom.scan_root = root.map(Path::to_path_buf);
 
         // Get file metadata
         let metadata = std::fs::metadata(path)
@@ -555,62 +946,55 @@ impl Scanner {
 
         // Detect if it's an archive
         rom.is_archive = matches!(rom.extension.as_str(), "zip" | "7z" | "rar");
-
-        // If it's an archive, show extraction progress
-        if rom.is_archive {
-            // Simulate extraction progress for archives
-            for progress in (0..=100).step_by(20) {
-                monitor.update_thread_status(
-                    thread_id, 
-                    ThreadStatus::ExtractingArchive { 
-                        file: path.display().to_string(), 
-                        progress: progress as f32 
-                    }
-                );
-                std::thread::sleep(std::time::Duration::from_millis(10)); // Simulate work
-            }
-        }
+        rom.attach_disc_tracks();
 
         // Calculate CRC32 if requested and not cached
         if self.calculate_crc {
             monitor.update_thread_status(thread_id, ThreadStatus::CalculatingCrc(path.display().to_string()));
             
-            if let Some(cached_crc) = self.crc_cache.get(path) {
-                rom.crc32 = Some(*cached_crc);
-                debug!("CRC32 do cache para {}: {:08X}", path.display(), *cached_crc);
-            } else {
-                match calculate_crc32(path) {
-                    Ok(crc) => {
-                        rom.crc32 = Some(crc);
-                        self.crc_cache.insert(path.to_path_buf(), crc);
-                        debug!("CRC32 calculado para {}: {:08X}", path.display(), crc);
-                    }
-                    Err(e) => {
-                        warn!("Falha ao calcular CRC32 para {}: {}", path.display(), e);
+            let computed = self.cached_crc_variants(&rom, |hash_path| {
+                self.hash_with_progress(hash_path, monitor, thread_id)
+            });
+            match computed {
+                Ok(variants) => {
+                    rom.apply_crc_variants(&variants);
+                    self.hash_remaining_tracks(&mut rom);
+                    debug!("CRC32 para {}: {:08X}", path.display(), variants.raw);
+                    if let (Some(headerless), Some(skipper)) = (variants.headerless, &variants.skipper) {
+                        debug!("CRC32 sem header ({}) para {}: {:08X}", skipper, path.display(), headerless);
                     }
                 }
+                Err(e) => {
+                    warn!("Falha ao calcular CRC32 para {}: {}", path.display(), e);
+                }
             }
         }
 
         // Detect system
-        rom.system = rom.detect_system();
+        rom.identify_system();
 
         if self.verbose {
             debug!(
-                "Processado: {} | Sistema: {} | CRC32: {} | Tamanho: {} bytes",
+                "Processado: {} | Sistema: {} ({}) | CRC32: {} | Tamanho: {} bytes",
                 rom.filename,
                 rom.system.as_deref().unwrap_or("Desconhecido"),
+                rom.detection.as_ref().map(|d| d.describe()).unwrap_or_else(|| "-".to_string()),
                 rom.crc32.map(|c| format!("{:08X}", c)).unwrap_or_else(|| "N/A".to_string()),
                 rom.size
             );
         }
 
-        Ok(Some(rom))
+        Ok(vec![rom])
     }
 
     /// Processamento simplificado de arquivo sem monitoramento detalhado
-    fn process_file_simple(&self, path: &Path) -> Result<Option<RomFile>> {
+    fn process_file_simple(&self, path: &Path, root: Option<&Path>) -> Result<Vec<RomFile>> {
+        if let Some(roms) = self.archive_entries(path, root, |_, _| {}) {
+            return Ok(roms);
+        }
+
         let mut rom = RomFile::new(path.to_path_buf());
+        rom.scan_root = root.map(Path::to_path_buf);
 
         // Get file metadata
         let metadata = std::fs::metadata(path)
@@ -619,29 +1003,49 @@ impl Scanner {
 
         // Detect if it's an archive
         rom.is_archive = matches!(rom.extension.as_str(), "zip" | "7z" | "rar");
+        rom.attach_disc_tracks();
 
         // Calculate CRC32 if requested and not cached
         if self.calculate_crc {
-            if let Some(cached_crc) = self.crc_cache.get(path) {
-                rom.crc32 = Some(*cached_crc);
-            } else {
-                match calculate_crc32(path) {
-                    Ok(crc) => {
-                        rom.crc32 = Some(crc);
-                        self.crc_cache.insert(path.to_path_buf(), crc);
-                    }
-                    Err(_) => {
-                        // Ignora erro de CRC para performance em modo paralelo
-                    }
-                }
+            let computed = self.cached_crc_variants(&rom, |hash_path| {
+                calculate_crc32_variants_with(hash_path, &self.header_skippers, &self.hash_options, None)
+            });
+            // Ignora erro de CRC para performance em modo paralelo
+            if let Ok(variants) = computed {
+                rom.apply_crc_variants(&variants);
+                self.hash_remaining_tracks(&mut rom);
             }
         }
 
         // Detect system
-        rom.system = rom.detect_system();
+        rom.identify_system();
+
+        Ok(vec![rom])
+    }
+}
 
-        Ok(Some(rom))
+/// Drop track files referenced by a CUE/GDI sheet, and discs listed in an M3U,
+/// in the same scan so each disc or multi-disc game yields one entry
+fn collapse_disc_tracks(file_paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let track_files: HashSet<PathBuf> = file_paths
+        .iter()
+        .filter(|path| {
+            let extension = path.extension().unwrap_or_default().to_string_lossy();
+            SheetKind::from_extension(&extension).is_some()
+        })
+        .filter_map(|sheet| DiscSheet::load(sheet).ok())
+        .flat_map(|sheet| sheet.files())
+        .collect();
+
+    if track_files.is_empty() {
+        return file_paths;
     }
+
+    debug!("{} faixas agrupadas em arquivos CUE/GDI/M3U", track_files.len());
+    file_paths
+        .into_iter()
+        .filter(|path| !track_files.contains(path))
+        .collect()
 }
 
 impl Default for Scanner {
@@ -664,6 +1068,94 @@ mod tests {
         assert_eq!(rom.detect_system().unwrap(), "Nintendo - Game Boy Advance");
     }
 
+    #[test]
+    fn test_header_evidence_overrides_path() {
+        // Path rules stop at the scan root, so the random tempdir name above it
+        // can never outrank the "md" folder
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("roms");
+        let md_dir = root.join("md");
+        std::fs::create_dir_all(&md_dir).unwrap();
+
+        // A Game Boy image dumped as .bin inside a Mega Drive folder
+        let mut data = vec![0u8; 0x8000];
+        data[0x104..0x108].copy_from_slice(&[0xCE, 0xED, 0x66, 0x66]);
+        data[0x108..0x134].copy_from_slice(&[
+            0xCC, 0x0D, 0x00, 0x0B, 0x03, 0x73, 0x00, 0x83, 0x00, 0x0C, 0x00, 0x0D,
+            0x00, 0x08, 0x11, 0x1F, 0x88, 0x89, 0x00, 0x0E, 0xDC, 0xCC, 0x6E, 0xE6,
+            0xDD, 0xDD, 0xD9, 0x99, 0xBB, 0xBB, 0x67, 0x63, 0x6E, 0x0E, 0xEC, 0xCC,
+            0xDD, 0xDC, 0x99, 0x9F, 0xBB, 0xB9, 0x33, 0x3E,
+        ]);
+        let path = md_dir.join("game.bin");
+        std::fs::write(&path, &data).unwrap();
+
+        let mut rom = RomFile::new(path);
+        rom.identify_system();
+        assert_eq!(rom.system.as_deref(), Some("Nintendo - Game Boy"));
+        assert!(matches!(rom.detection, Some(DetectionEvidence::Header(_))));
+
+        // Without a recognizable header the path heuristic still applies
+        let path = md_dir.join("other.bin");
+        std::fs::write(&path, vec![0u8; 0x200]).unwrap();
+        let mut rom = RomFile::new(path);
+        rom.scan_root = Some(root);
+        let detection = rom.detect_system_with_evidence().unwrap();
+        assert_eq!(detection.system, "Sega - Mega Drive - Genesis");
+        assert_eq!(detection.evidence, DetectionEvidence::Path);
+
+        // Short abbreviations only match whole path words
+        let rom = RomFile::new(PathBuf::from("/roms/gamedata/logic.bin"));
+        assert!(rom.detect_system().is_none());
+    }
+
+    #[test]
+    fn test_cue_tracks_collapse_into_one_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("Game.cue"), concat!(
+            "FILE \"Game (Track 1).bin\" BINARY\n",
+            "  TRACK 01 MODE2/2352\n",
+            "FILE \"Game (Track 2).bin\" BINARY\n",
+            "  TRACK 02 AUDIO\n",
+            "FILE \"Game (Track 3).bin\" BINARY\n",
+            "  TRACK 03 AUDIO\n",
+        )).unwrap();
+        std::fs::write(dir.path().join("Game (Track 1).bin"), vec![1u8; 2352]).unwrap();
+        std::fs::write(dir.path().join("Game (Track 2).bin"), vec![2u8; 2352]).unwrap();
+
+        let roms = Scanner::new().with_threads(1).scan_directory_simple(dir.path()).unwrap();
+        assert_eq!(roms.len(), 1);
+
+        let disc = &roms[0];
+        assert_eq!(disc.filename, "Game.cue");
+        assert_eq!(disc.tracks.len(), 2);
+        assert_eq!(disc.missing_tracks, vec![dir.path().join("Game (Track 3).bin")]);
+        assert_eq!(disc.hash_path(), dir.path().join("Game (Track 1).bin"));
+        assert_eq!(disc.crc32, Some(crate::crc32::calculate_crc32(disc.hash_path()).unwrap()));
+        assert_eq!(disc.track_crc32s, vec![crate::crc32::calculate_crc32(&disc.tracks[1]).unwrap()]);
+    }
+
+    #[test]
+    fn test_m3u_collapses_its_discs() {
+        let dir = tempfile::tempdir().unwrap();
+        for disc in 1..=2 {
+            std::fs::write(
+                dir.path().join(format!("Game (Disc {}).cue", disc)),
+                format!("FILE \"Game (Disc {}).bin\" BINARY\n  TRACK 01 MODE2/2352\n", disc),
+            ).unwrap();
+            std::fs::write(dir.path().join(format!("Game (Disc {}).bin", disc)), vec![disc as u8; 2352]).unwrap();
+        }
+        std::fs::write(dir.path().join("Game.m3u"), "Game (Disc 1).cue\nGame (Disc 2).cue\n").unwrap();
+
+        let roms = Scanner::new().with_threads(1).scan_directory_simple(dir.path()).unwrap();
+        assert_eq!(roms.len(), 1);
+
+        let game = &roms[0];
+        assert_eq!(game.filename, "Game.m3u");
+        assert_eq!(game.tracks.len(), 4);
+        assert_eq!(game.hash_path(), dir.path().join("Game (Disc 1).bin"));
+        assert!(game.files().contains(&dir.path().join("Game (Disc 2).bin").as_path()));
+    }
+
     #[test]
     fn test_extension_filtering() {
         let scanner = Scanner::new();
@@ -682,4 +1174,209 @@ mod tests {
         assert!(scanner.is_rom_file(Path::new("game.bin")));
         assert!(!scanner.is_rom_file(Path::new("game.z64")));
     }
+
+    #[test]
+    fn test_crc_cache_persists_between_scans() {
+        let dir = tempfile::tempdir().unwrap();
+        let roms_dir = dir.path().join("roms");
+        std::fs::create_dir(&roms_dir).unwrap();
+        let cache_file = dir.path().join("crc32_cache.bin");
+        std::fs::write(roms_dir.join("a.gba"), b"first rom").unwrap();
+        std::fs::write(roms_dir.join("b.gba"), b"second rom").unwrap();
+
+        let scan = || {
+            let cache = CrcCache::load_from_file(&cache_file).unwrap().into_shared();
+            let roms = Scanner::new()
+                .with_crc_cache(cache.clone())
+                .with_threads(2)
+                .scan_directory_simple(&roms_dir)
+                .unwrap();
+            let stats = cache.lock().get_stats().unwrap();
+            (roms, stats.cache_hits, stats.cache_misses)
+        };
+
+        let (roms, hits, misses) = scan();
+        assert_eq!((roms.len(), hits, misses), (2, 0, 2));
+        assert!(cache_file.exists());
+
+        // Only the file whose size changed is hashed again
+        std::fs::write(roms_dir.join("b.gba"), b"second rom, patched").unwrap();
+        let (roms, hits, misses) = scan();
+        assert_eq!((hits, misses), (1, 1));
+        let patched = roms.iter().find(|rom| rom.filename == "b.gba").unwrap();
+        assert_eq!(patched.crc32, Some(crc32fast::hash(b"second rom, patched")));
+    }
+
+    #[test]
+    fn test_renamed_folder_reuses_cached_hashes() {
+        let dir = tempfile::tempdir().unwrap();
+        let roms_dir = dir.path().join("roms");
+        std::fs::create_dir(&roms_dir).unwrap();
+        std::fs::write(roms_dir.join("a.gba"), b"first rom").unwrap();
+        std::fs::write(roms_dir.join("b.gba"), b"second rom").unwrap();
+
+        let cache = CrcCache::new().into_shared();
+        let scan = |path: &Path| {
+            Scanner::new()
+                .with_crc_cache(cache.clone())
+                .with_threads(2)
+                .scan_directory_simple(path)
+                .unwrap()
+        };
+
+        scan(&roms_dir);
+        let renamed = dir.path().join("gba");
+        std::fs::rename(&roms_dir, &renamed).unwrap();
+        let roms = scan(&renamed);
+
+        let stats = cache.lock().get_stats().unwrap();
+        assert_eq!((stats.cache_hits, stats.cache_misses, stats.relinked), (2, 2, 2));
+        assert_eq!(stats.total_entries, 2);
+        let b = roms.iter().find(|rom| rom.filename == "b.gba").unwrap();
+        assert_eq!(b.crc32, Some(crc32fast::hash(b"second rom")));
+    }
+
+    #[test]
+    fn test_same_fingerprint_with_different_middle_is_rehashed() {
+        let dir = tempfile::tempdir().unwrap();
+        let original: Vec<u8> = (0..400_000u32).map(|i| (i % 251) as u8).collect();
+        let patch = |offset: usize| {
+            let mut data = original.clone();
+            data[offset] ^= 0xFF;
+            data
+        };
+        let (first_patch, second_patch) = (patch(200_000), patch(150_000));
+
+        let cache = CrcCache::new().into_shared();
+        let scan = || {
+            Scanner::new()
+                .with_crc_cache(cache.clone())
+                .with_threads(1)
+                .scan_directory_simple(dir.path())
+                .unwrap()
+        };
+        std::fs::write(dir.path().join("a.gba"), &original).unwrap();
+        scan();
+
+        // Same size and same first/last 64 KiB, so the fingerprints collide
+        std::fs::write(dir.path().join("b.gba"), &first_patch).unwrap();
+        assert_eq!(
+            content_fingerprint(&dir.path().join("a.gba")).unwrap(),
+            content_fingerprint(&dir.path().join("b.gba")).unwrap(),
+        );
+
+        // While the cached file still exists the new one is never relinked
+        let roms = scan();
+        let b = roms.iter().find(|rom| rom.filename == "b.gba").unwrap();
+        assert_eq!(b.crc32, Some(crc32fast::hash(&first_patch)));
+
+        // Nor once it is gone: a different modification time rejects the candidate
+        std::fs::remove_file(dir.path().join("a.gba")).unwrap();
+        std::fs::remove_file(dir.path().join("b.gba")).unwrap();
+        std::fs::write(dir.path().join("c.gba"), &second_patch).unwrap();
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(3600);
+        std::fs::File::options().write(true).open(dir.path().join("c.gba")).unwrap().set_modified(later).unwrap();
+        let roms = scan();
+        assert_eq!(roms[0].crc32, Some(crc32fast::hash(&second_patch)));
+        assert_eq!(cache.lock().get_stats().unwrap().relinked, 0);
+    }
+
+    #[test]
+    fn test_moved_file_is_relinked_without_reading_it() {
+        use std::io::{Seek, SeekFrom, Write};
+
+        let dir = tempfile::tempdir().unwrap();
+        let original: Vec<u8> = (0..400_000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(dir.path().join("a.gba"), &original).unwrap();
+
+        let cache = CrcCache::new().into_shared();
+        let scan = || {
+            Scanner::new()
+                .with_crc_cache(cache.clone())
+                .with_threads(1)
+                .scan_directory_simple(dir.path())
+                .unwrap()
+        };
+        scan();
+
+        // Move the file, then flip a byte between the fingerprinted ends and
+        // restore the timestamp: only a full read would notice the change
+        let moved = dir.path().join("b.gba");
+        std::fs::rename(dir.path().join("a.gba"), &moved).unwrap();
+        let modified = std::fs::metadata(&moved).unwrap().modified().unwrap();
+        let mut file = std::fs::File::options().write(true).open(&moved).unwrap();
+        file.seek(SeekFrom::Start(200_000)).unwrap();
+        file.write_all(&[original[200_000] ^ 0xFF]).unwrap();
+        file.set_modified(modified).unwrap();
+        drop(file);
+
+        let roms = scan();
+        assert_eq!(roms[0].crc32, Some(crc32fast::hash(&original)));
+        assert_eq!(cache.lock().get_stats().unwrap().relinked, 1);
+    }
+
+    #[cfg(feature = "archive-support")]
+    #[test]
+    fn test_archive_entries_identified_by_inner_crc() {
+        use std::io::Write;
+        use zip::write::SimpleFileOptions;
+
+        let dir = tempfile::tempdir().unwrap();
+        let snes: Vec<u8> = (0..40_000u32).map(|i| (i % 239) as u8).collect();
+        let mut nes = b"NES\x1A".to_vec();
+        nes.resize(16, 0);
+        nes.extend((0..24_576u32).map(|i| (i % 233) as u8));
+
+        let archive_path = dir.path().join("Collection.zip");
+        let mut writer = zip::ZipWriter::new(std::fs::File::create(&archive_path).unwrap());
+        let options = SimpleFileOptions::default();
+        for (name, data) in [("Super Mario World (USA).sfc", &snes), ("Zelda (USA).nes", &nes), ("readme.txt", &b"hi".to_vec())] {
+            writer.start_file(name, options).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let mut roms = Scanner::new().with_threads(1).scan_directory_simple(dir.path()).unwrap();
+        roms.sort_by(|a, b| a.filename.cmp(&b.filename));
+        assert_eq!(roms.len(), 2);
+
+        let smw = &roms[0];
+        assert_eq!(smw.crc32, Some(crc32fast::hash(&snes)));
+        assert_eq!(smw.system.as_deref(), Some("Nintendo - Super Nintendo Entertainment System"));
+        assert_eq!(smw.playlist_path(), VirtualRomFile::entry_path(&archive_path, "Super Mario World (USA).sfc"));
+        assert!(smw.playlist_path().to_string_lossy().ends_with("Collection.zip#Super Mario World (USA).sfc"));
+
+        // Headered entries are streamed so the headerless CRC is available
+        let zelda = &roms[1];
+        assert_eq!(zelda.crc32, Some(crc32fast::hash(&nes)));
+        assert_eq!(zelda.crc32_headerless, Some(crc32fast::hash(&nes[16..])));
+        assert_eq!(zelda.system.as_deref(), Some("Nintendo - Nintendo Entertainment System"));
+    }
+
+    #[cfg(feature = "archive-support")]
+    #[test]
+    fn test_arcade_sets_outside_arcade_dirs_are_not_expanded() {
+        use std::io::Write;
+        use zip::write::SimpleFileOptions;
+
+        let dir = tempfile::tempdir().unwrap();
+        let sets: [(&str, &[&str]); 2] = [
+            ("mslug.zip", &["201-p1.p1", "201-s1.s1", "201-m1.m1", "201-c1.bin"]),
+            ("sf2.zip", &["sf2e_30g.bin", "sf2e_37g.bin"]),
+        ];
+        for (archive, entries) in sets {
+            let mut writer = zip::ZipWriter::new(std::fs::File::create(dir.path().join(archive)).unwrap());
+            for (index, entry) in entries.iter().enumerate() {
+                writer.start_file(*entry, SimpleFileOptions::default()).unwrap();
+                writer.write_all(&[index as u8; 64]).unwrap();
+            }
+            writer.finish().unwrap();
+        }
+
+        let mut roms = Scanner::new().with_threads(1).scan_directory_simple(dir.path()).unwrap();
+        roms.sort_by(|a, b| a.filename.cmp(&b.filename));
+        let names: Vec<&str> = roms.iter().map(|rom| rom.filename.as_str()).collect();
+        assert_eq!(names, ["mslug.zip", "sf2.zip"]);
+        assert!(roms.iter().all(|rom| rom.archive_entry.is_none() && rom.system.as_deref() == Some("MAME")));
+    }
 }
@@ -0,0 +1,411 @@
+use anyhow::{Context, Result};
+use log::warn;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::error::ConfigError;
+use crate::platform::Platform;
+use crate::rom_header::SystemDetection;
+
+const SYSTEMS_FILE: &str = "systems.toml";
+const CORES_FILE: &str = "cores.toml";
+
+// Shipped definitions, used when `configs/` is not next to the working directory
+const BUILTIN_SYSTEMS: &str = include_str!("../configs/systems.toml");
+const BUILTIN_CORES: &str = include_str!("../configs/cores.toml");
+
+/// A system as declared under `[systems.<id>]`
+#[derive(Debug, Clone, Deserialize)]
+pub struct SystemDefinition {
+    /// RetroArch database name, e.g. "Nintendo - Nintendo 64"
+    pub name: String,
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    /// Id of the entry under `[cores]` used by default
+    pub default_core: Option<String>,
+    pub db_name: Option<String>,
+    #[serde(default)]
+    pub detection: DetectionRules,
+}
+
+impl SystemDefinition {
+    /// `db_name` written to playlist entries
+    pub fn db_name(&self) -> String {
+        self.db_name.clone().unwrap_or_else(|| format!("{}.lpl", self.name))
+    }
+}
+
+/// Rules that confirm a system beyond its file extension
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DetectionRules {
+    /// Substrings of a lowercased folder name, any of which confirms the system
+    #[serde(default)]
+    pub path_keywords: Vec<String>,
+    /// Whole words of a folder name, so "md" does not match "gamedata"
+    #[serde(default)]
+    pub path_tokens: Vec<String>,
+    /// Magic bytes at fixed offsets; any matching rule identifies the system
+    #[serde(default)]
+    pub magic: Vec<MagicRule>,
+}
+
+impl DetectionRules {
+    /// Extension alone is not enough when path rules are present
+    pub fn requires_path(&self) -> bool {
+        !self.path_keywords.is_empty() || !self.path_tokens.is_empty()
+    }
+
+    /// Length of the longest keyword or token found in one lowercased folder name
+    fn path_match_len(&self, folder: &str) -> Option<usize> {
+        let keyword = self.path_keywords.iter()
+            .filter(|keyword| folder.contains(keyword.as_str()))
+            .map(String::len);
+
+        let token = self.path_tokens.iter()
+            .filter(|token| folder
+                .split(|c: char| !c.is_ascii_alphanumeric())
+                .any(|word| word == token.as_str()))
+            .map(String::len);
+
+        keyword.chain(token).max()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MagicRule {
+    #[serde(default)]
+    pub offset: usize,
+    /// Expected bytes as a hex string
+    pub value: String,
+    pub description: Option<String>,
+}
+
+impl MagicRule {
+    fn matches(&self, data: &[u8]) -> bool {
+        let Some(expected) = decode_hex(&self.value) else {
+            return false;
+        };
+        data.get(self.offset..self.offset + expected.len()) == Some(expected.as_slice())
+    }
+
+    fn describe(&self) -> String {
+        self.description.clone()
+            .unwrap_or_else(|| format!("magic {} @ 0x{:X}", self.value, self.offset))
+    }
+}
+
+/// A libretro core as declared under `[cores.<id>]`
+#[derive(Debug, Clone, Deserialize)]
+pub struct CoreDefinition {
+    pub display_name: String,
+    /// Core library file name per platform
+    #[serde(default)]
+    pub platforms: HashMap<Platform, String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RegistryFile {
+    #[serde(default)]
+    systems: BTreeMap<String, SystemDefinition>,
+    #[serde(default)]
+    cores: BTreeMap<String, CoreDefinition>,
+}
+
+/// Systems and cores loaded from `configs/systems.toml` and `configs/cores.toml`
+#[derive(Debug, Clone, Default)]
+pub struct SystemRegistry {
+    systems: BTreeMap<String, SystemDefinition>,
+    cores: BTreeMap<String, CoreDefinition>,
+    // Extension -> ids of the systems that claim it
+    by_extension: HashMap<String, Vec<String>>,
+}
+
+impl SystemRegistry {
+    /// Definitions compiled into the binary
+    pub fn builtin() -> Self {
+        Self::from_sources(&[BUILTIN_SYSTEMS, BUILTIN_CORES])
+            .expect("built-in system definitions must parse")
+    }
+
+    /// Shipped definitions (from `configs/` when present) with overrides from
+    /// the user config directory merged on top
+    pub fn load_default() -> Result<Self> {
+        let mut dirs = vec![PathBuf::from("configs")];
+        dirs.push(crate::config::Config::get_default_config_dir());
+        Self::load_layers(&dirs)
+    }
+
+    /// Start from the built-in definitions and merge `systems.toml`/`cores.toml`
+    /// from each directory in order; later directories win key by key
+    pub fn load_layers(dirs: &[PathBuf]) -> Result<Self> {
+        let mut merged = toml::Table::new();
+        for source in [BUILTIN_SYSTEMS, BUILTIN_CORES] {
+            merge_tables(&mut merged, toml::from_str(source).map_err(ConfigError::InvalidFormat)?);
+        }
+
+        for dir in dirs {
+            for file in [SYSTEMS_FILE, CORES_FILE] {
+                let path = dir.join(file);
+                if path.exists() {
+                    merge_tables(&mut merged, read_table(&path)?);
+                }
+            }
+        }
+
+        Self::from_table(merged)
+    }
+
+    pub fn from_sources(sources: &[&str]) -> Result<Self> {
+        let mut merged = toml::Table::new();
+        for source in sources {
+            merge_tables(&mut merged, toml::from_str(source).map_err(ConfigError::InvalidFormat)?);
+        }
+        Self::from_table(merged)
+    }
+
+    fn from_table(table: toml::Table) -> Result<Self> {
+        let file: RegistryFile = table.try_into().map_err(ConfigError::InvalidFormat)?;
+
+        let mut by_extension: HashMap<String, Vec<String>> = HashMap::new();
+        for (id, system) in &file.systems {
+            for extension in &system.extensions {
+                by_extension.entry(extension.to_lowercase()).or_default().push(id.clone());
+            }
+            if let Some(core) = &system.default_core
+                && !file.cores.contains_key(core)
+            {
+                warn!("Core padrão '{}' do sistema '{}' não está definido em {}", core, id, CORES_FILE);
+            }
+        }
+
+        Ok(Self {
+            systems: file.systems,
+            cores: file.cores,
+            by_extension,
+        })
+    }
+
+    /// Registry shared by the scanner and playlist builder, loaded on first use
+    pub fn global() -> &'static SystemRegistry {
+        static REGISTRY: OnceLock<SystemRegistry> = OnceLock::new();
+        REGISTRY.get_or_init(|| {
+            Self::load_default().unwrap_or_else(|e| {
+                warn!("Falha ao carregar definições de sistemas, usando padrões embutidos: {}", e);
+                Self::builtin()
+            })
+        })
+    }
+
+    pub fn system(&self, id: &str) -> Option<&SystemDefinition> {
+        self.systems.get(id)
+    }
+
+    /// Look up a system by its RetroArch name
+    pub fn system_by_name(&self, name: &str) -> Option<&SystemDefinition> {
+        self.systems.values().find(|system| system.name == name)
+    }
+
+    pub fn systems(&self) -> impl Iterator<Item = (&String, &SystemDefinition)> {
+        self.systems.iter()
+    }
+
+    pub fn core(&self, id: &str) -> Option<&CoreDefinition> {
+        self.cores.get(id)
+    }
+
+    pub fn cores(&self) -> impl Iterator<Item = (&String, &CoreDefinition)> {
+        self.cores.iter()
+    }
+
+    pub fn is_known_extension(&self, extension: &str) -> bool {
+        self.by_extension.contains_key(extension)
+    }
+
+    /// Whether any system claiming this extension declares magic bytes
+    pub fn has_magic_rules(&self, extension: &str) -> bool {
+        self.candidates(extension).any(|system| !system.detection.magic.is_empty())
+    }
+
+    fn candidates<'a>(&'a self, extension: &str) -> impl Iterator<Item = &'a SystemDefinition> + 'a {
+        self.by_extension.get(extension)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.systems.get(id))
+    }
+
+    /// Match the configured magic bytes of systems claiming this extension
+    pub fn detect_from_magic(&self, extension: &str, data: &[u8]) -> Option<SystemDetection> {
+        self.candidates(extension).find_map(|system| {
+            system.detection.magic.iter()
+                .find(|rule| rule.matches(data))
+                .map(|rule| SystemDetection::from_header(&system.name, &rule.describe()))
+        })
+    }
+
+    /// Pick a system from the extension, confirmed by path rules where the
+    /// system declares them. Path rules only look at the folders from `root`
+    /// down to the ROM; the deepest folder with a match decides, the longest
+    /// match within it wins, and a path match beats a bare extension match.
+    pub fn detect_from_extension(&self, extension: &str, path: &Path, root: Option<&Path>) -> Option<SystemDetection> {
+        let mut by_extension = None;
+        let mut path_rules = Vec::new();
+        for system in self.candidates(extension) {
+            if system.detection.requires_path() {
+                path_rules.push(system);
+            } else {
+                by_extension.get_or_insert(system);
+            }
+        }
+
+        for folder in rom_folders(path, root) {
+            let mut best: Option<(usize, &SystemDefinition)> = None;
+            for &system in &path_rules {
+                if let Some(len) = system.detection.path_match_len(&folder)
+                    && best.is_none_or(|(best, _)| len > best)
+                {
+                    best = Some((len, system));
+                }
+            }
+            if let Some((_, system)) = best {
+                return Some(SystemDetection::from_path(&system.name));
+            }
+        }
+
+        by_extension.map(|system| SystemDetection::from_extension(&system.name))
+    }
+}
+
+/// Lowercased names of the folders holding `path`, deepest first. With a
+/// `root` that contains the ROM the walk stops at the root's own name, so
+/// ancestors such as `/home/atari-fan` never confirm a system.
+fn rom_folders(path: &Path, root: Option<&Path>) -> Vec<String> {
+    let stop = root.filter(|root| path.starts_with(root)).and_then(Path::parent);
+    path.ancestors()
+        .skip(1)
+        .take_while(|dir| Some(*dir) != stop)
+        .filter_map(Path::file_name)
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .collect()
+}
+
+fn read_table(path: &Path) -> Result<toml::Table> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| ConfigError::LoadFailed {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+    toml::from_str(&content)
+        .map_err(ConfigError::InvalidFormat)
+        .with_context(|| format!("Em {}", path.display()))
+}
+
+/// Recursively merge `overlay` into `base`; non-table values replace
+fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(table)) => merge_tables(existing, table),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    let value = value.trim();
+    if !value.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rom_header::DetectionEvidence;
+
+    #[test]
+    fn test_builtin_registry() {
+        let registry = SystemRegistry::builtin();
+        let n64 = registry.system("n64").unwrap();
+        assert_eq!(n64.name, "Nintendo - Nintendo 64");
+        assert_eq!(n64.db_name(), "Nintendo - Nintendo 64.lpl");
+        assert!(registry.core(n64.default_core.as_deref().unwrap()).is_some());
+        assert!(registry.is_known_extension("z64"));
+    }
+
+    #[test]
+    fn test_extension_and_path_detection() {
+        let registry = SystemRegistry::builtin();
+
+        let detection = registry.detect_from_extension("sfc", Path::new("/roms/game.sfc"), None).unwrap();
+        assert_eq!(detection.system, "Nintendo - Super Nintendo Entertainment System");
+        assert_eq!(detection.evidence, DetectionEvidence::Extension);
+
+        let detection = registry.detect_from_extension("bin", Path::new("/roms/megadrive/game.bin"), None).unwrap();
+        assert_eq!(detection.system, "Sega - Mega Drive - Genesis");
+        assert_eq!(detection.evidence, DetectionEvidence::Path);
+
+        // "playstation portable" is a longer match than "playstation"
+        let detection = registry.detect_from_extension("pbp", Path::new("/roms/PlayStation Portable/game.pbp"), None).unwrap();
+        assert_eq!(detection.system, "Sony - PlayStation Portable");
+
+        assert!(registry.detect_from_extension("iso", Path::new("/roms/misc/game.iso"), None).is_none());
+    }
+
+    #[test]
+    fn test_path_rules_ignore_ancestors_of_scan_root() {
+        let registry = SystemRegistry::builtin();
+
+        // The ROM folder is deeper than the misleading home directory
+        let detection = registry
+            .detect_from_extension("bin", Path::new("/home/atari-fan/roms/md/x.bin"), Some(Path::new("/home/atari-fan/roms")))
+            .unwrap();
+        assert_eq!(detection.system, "Sega - Mega Drive - Genesis");
+
+        // "ps2backup" is a longer keyword match, but only "psx" sits under the root
+        let detection = registry
+            .detect_from_extension("bin", Path::new("/mnt/ps2backup/roms/psx/game.bin"), Some(Path::new("/mnt/ps2backup/roms")))
+            .unwrap();
+        assert_eq!(detection.system, "Sony - PlayStation");
+
+        // Ancestors of the root are never consulted, but the root's own name is
+        let misc = Path::new("/home/atari-fan/roms/misc/game.bin");
+        assert!(registry.detect_from_extension("bin", misc, Some(Path::new("/home/atari-fan/roms"))).is_none());
+        let detection = registry
+            .detect_from_extension("bin", Path::new("/roms/genesis/game.bin"), Some(Path::new("/roms/genesis")))
+            .unwrap();
+        assert_eq!(detection.system, "Sega - Mega Drive - Genesis");
+    }
+
+    #[test]
+    fn test_user_overrides() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("systems.toml"), r#"
+[systems.n64]
+default_core = "parallel_n64"
+
+[systems.supervision]
+name = "Watara - Supervision"
+extensions = ["sv"]
+
+[systems.supervision.detection]
+magic = [{ offset = 2, value = "CAFE", description = "test magic" }]
+"#).unwrap();
+
+        let registry = SystemRegistry::load_layers(&[dir.path().to_path_buf()]).unwrap();
+        let n64 = registry.system("n64").unwrap();
+        assert_eq!(n64.default_core.as_deref(), Some("parallel_n64"));
+        assert_eq!(n64.extensions, vec!["z64", "n64", "v64"]);
+
+        assert!(registry.has_magic_rules("sv"));
+        let detection = registry.detect_from_magic("sv", &[0, 0, 0xCA, 0xFE]).unwrap();
+        assert_eq!(detection.system, "Watara - Supervision");
+        assert_eq!(detection.evidence, DetectionEvidence::Header("test magic".to_string()));
+    }
+}
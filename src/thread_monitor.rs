@@ -11,6 +11,7 @@ pub enum ThreadStatus {
     ScanningFile(String),
     ExtractingArchive { file: String, progress: f32 },
     CalculatingCrc(String),
+    HashingFile { file: String, progress: f32 },
     ProcessingComplete(String),
     Error(String, String), // filename, error
 }
@@ -108,6 +109,9 @@ impl ThreadMonitor {
                     ThreadStatus::CalculatingCrc(file) => {
                         format!("🔢 CRC32: {}", Self::truncate_filename(file, 30))
                     },
+                    ThreadStatus::HashingFile { file, progress } => {
+                        format!("🔢 Hash {} ({:.1}%)", Self::truncate_filename(file, 22), progress)
+                    },
                     ThreadStatus::ProcessingComplete(file) => {
                         format!("✅ Concluído: {}", Self::truncate_filename(file, 25))
                     },
@@ -117,8 +121,8 @@ impl ThreadMonitor {
                 };
                 pb.set_message(message);
                 
-                // Atualizar progresso para extração de arquivos
-                if let ThreadStatus::ExtractingArchive { progress, .. } = status {
+                // Atualizar progresso para extração e hash de arquivos grandes
+                if let ThreadStatus::ExtractingArchive { progress, .. } | ThreadStatus::HashingFile { progress, .. } = status {
                     pb.set_position(progress as u64);
                 }
             }
@@ -4,8 +4,9 @@ use std::path::{Path, PathBuf};
 use log::{info, debug};
 
 use crate::scanner::RomFile;
-use crate::dat_parser::{DatCollection, DatEntry};
-use crate::crc32::calculate_crc32;
+use crate::dat_parser::{DatCollection, DatEntry, MatchedBy};
+use crate::crc32::{calculate_crc32, calculate_crc32_variants, CrcVariant};
+use crate::header_skipper::SkipperSet;
 
 /// Validation result for a single ROM
 #[derive(Debug, Clone, PartialEq)]
@@ -51,6 +52,10 @@ pub struct ValidationReport {
     pub homebrew_roms: usize,
     pub corrupted_roms: usize,
     pub validation_results: HashMap<PathBuf, ValidationResult>,
+    /// Which CRC (raw or headerless) matched the DAT, per ROM
+    pub matched_variants: HashMap<PathBuf, CrcVariant>,
+    /// Strongest hash that confirmed the DAT match, per ROM
+    pub matched_by: HashMap<PathBuf, MatchedBy>,
     pub missing_from_collection: Vec<DatEntry>,
 }
 
@@ -113,6 +118,7 @@ impl ValidationReport {
 pub struct RomValidator {
     dat_collections: HashMap<String, DatCollection>,
     homebrew_patterns: Vec<String>,
+    header_skippers: SkipperSet,
 }
 
 impl RomValidator {
@@ -121,9 +127,27 @@ impl RomValidator {
         Self {
             dat_collections: HashMap::new(),
             homebrew_patterns: Self::load_homebrew_patterns(),
+            header_skippers: SkipperSet::builtin(),
         }
     }
 
+    /// Header skippers used for ROMs scanned without a CRC
+    pub fn with_header_skippers(mut self, skippers: SkipperSet) -> Self {
+        self.header_skippers = skippers;
+        self
+    }
+
+    /// Header skippers named by the loaded DATs
+    pub fn header_skipper_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.dat_collections
+            .values()
+            .flat_map(DatCollection::header_skippers)
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
     /// Load DAT collection for a system
     pub fn load_dat_collection<P: AsRef<Path>>(&mut self, system: &str, dat_path: P) -> Result<()> {
         let mut collection = DatCollection::new();
@@ -158,27 +182,46 @@ impl RomValidator {
 
     /// Validate a single ROM file
     pub fn validate_rom(&self, rom: &RomFile) -> Result<ValidationResult> {
+        self.validate_rom_with_variant(rom).map(|(result, _)| result)
+    }
+
+    /// Validate a ROM and report which CRC variant matched the DAT and which
+    /// hash confirmed it, if any
+    pub fn validate_rom_with_variant(&self, rom: &RomFile) -> Result<(ValidationResult, Option<(CrcVariant, MatchedBy)>)> {
         debug!("Validating ROM: {}", rom.path.display());
+
+        // A disc sheet with missing tracks cannot be a good dump
+        if !rom.missing_tracks.is_empty() {
+            let missing: Vec<String> = rom.missing_tracks
+                .iter()
+                .map(|track| track.display().to_string())
+                .collect();
+            return Ok((ValidationResult::Corrupted {
+                error: format!("Missing tracks: {}", missing.join(", ")),
+            }, None));
+        }
         
-        // Calculate CRC32 if not already available
-        let crc32 = match rom.crc32 {
-            Some(crc) => crc,
-            None => {
-                match calculate_crc32(&rom.path) {
-                    Ok(crc) => crc,
-                    Err(e) => {
-                        return Ok(ValidationResult::Corrupted {
-                            error: e.to_string(),
-                        });
-                    }
+        // Calculate CRC32 (raw and headerless) if not already available
+        let candidates = if rom.crc32.is_some() {
+            rom.hash_candidates()
+        } else {
+            match calculate_crc32_variants(rom.hash_path(), &self.header_skippers) {
+                Ok(variants) => variants.hash_candidates(),
+                Err(e) => {
+                    return Ok((ValidationResult::Corrupted {
+                        error: e.to_string(),
+                    }, None));
                 }
             }
         };
 
         // Check against DAT collections
         for (system, collection) in &self.dat_collections {
-            if let Some(entry) = collection.find_by_crc32(crc32) {
-                return Ok(self.analyze_dat_match(rom, entry, system));
+            if let Some(found) = collection.find_match(&candidates) {
+                debug!("DAT match for {} via {:?} CRC, confirmed by {}",
+                    rom.path.display(), found.variant, found.matched_by.describe());
+                let result = self.analyze_dat_match(rom, found.entry, system);
+                return Ok((result, Some((found.variant, found.matched_by))));
             }
         }
 
@@ -188,12 +231,12 @@ impl RomValidator {
             .unwrap_or("");
         
         if self.is_homebrew_pattern(filename) {
-            return Ok(ValidationResult::Homebrew {
+            return Ok((ValidationResult::Homebrew {
                 detected_name: filename.to_string(),
-            });
+            }, None));
         }
 
-        Ok(ValidationResult::Unknown)
+        Ok((ValidationResult::Unknown, None))
     }
 
     /// Analyze a DAT match
@@ -236,6 +279,10 @@ impl RomValidator {
 
     /// Check if DAT entry represents a bad dump
     fn is_bad_dump_entry(&self, entry: &DatEntry) -> bool {
+        if entry.status.as_deref() == Some("baddump") {
+            return true;
+        }
+
         let name = entry.name.to_lowercase();
         
         name.contains("[b]") || 
@@ -318,7 +365,11 @@ impl RomValidator {
         };
 
         for rom in roms {
-            let result = self.validate_rom(rom)?;
+            let (result, matched) = self.validate_rom_with_variant(rom)?;
+            if let Some((variant, matched_by)) = matched {
+                report.matched_variants.insert(rom.playlist_path(), variant);
+                report.matched_by.insert(rom.playlist_path(), matched_by);
+            }
             
             match &result {
                 ValidationResult::Valid { .. } => report.valid_roms += 1,
@@ -329,7 +380,7 @@ impl RomValidator {
                 ValidationResult::Corrupted { .. } => report.corrupted_roms += 1,
             }
 
-            report.validation_results.insert(rom.path.clone(), result);
+            report.validation_results.insert(rom.playlist_path(), result);
         }
 
         // Find missing ROMs from DAT collections
@@ -347,10 +398,18 @@ impl RomValidator {
     fn find_missing_roms(&self, roms: &[RomFile]) -> Vec<DatEntry> {
         let present_crc32s: std::collections::HashSet<u32> = roms
             .iter()
-            .filter_map(|rom| rom.crc32.or_else(|| {
-                // Try to calculate CRC32 for comparison
-                calculate_crc32(&rom.path).ok()
-            }))
+            .flat_map(|rom| {
+                let first = rom.crc32.or_else(|| {
+                    // Try to calculate CRC32 for comparison
+                    calculate_crc32(rom.hash_path()).ok()
+                });
+                let headerless = rom.crc32_headerless;
+                // Remaining tracks of a disc are listed as separate DAT entries;
+                // the scanner hashed them through the CRC cache
+                let other_tracks = rom.track_crc32s.iter().copied().map(Some);
+                [first, headerless].into_iter().chain(other_tracks)
+            })
+            .flatten()
             .collect();
 
         let mut missing = Vec::new();
@@ -387,6 +446,17 @@ impl RomValidator {
         content.push_str(&format!("- ❌ Bad Dumps: {}\n", report.bad_dumps));
         content.push_str(&format!("- 💥 Corrupted: {}\n\n", report.corrupted_roms));
 
+        if !report.matched_by.is_empty() {
+            content.push_str("## Match Confirmation\n\n");
+            for matched_by in [MatchedBy::Sha1, MatchedBy::Md5, MatchedBy::Size, MatchedBy::Crc32] {
+                let count = report.matched_by.values().filter(|by| **by == matched_by).count();
+                if count > 0 {
+                    content.push_str(&format!("- {}: {}\n", matched_by.describe(), count));
+                }
+            }
+            content.push('\n');
+        }
+
         // Unknown ROMs section
         if !report.unknown_roms().is_empty() {
             content.push_str("## Unknown ROMs\n\n");
@@ -1,16 +1,22 @@
 use anyhow::{Result, Context};
-use notify::{Watcher, RecursiveMode, Event, RecommendedWatcher};
+use notify::{Watcher, RecursiveMode, Event, PollWatcher, RecommendedWatcher};
 use std::path::{Path, PathBuf};
-use std::sync::mpsc::Receiver;
+use std::sync::mpsc::{Receiver, Sender};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use log::{info, debug, warn, error};
+use walkdir::WalkDir;
 
-use crate::scanner::Scanner;
-use crate::playlist::PlaylistBuilder;
+use crate::scanner::{RomFile, Scanner};
+use crate::playlist::{Playlist, PlaylistBuilder, PlaylistFormat};
 use crate::config::Config;
-use crate::platform::Platform;
+use crate::core_mapper::CoreMapper;
+use crate::error::HookError;
+use crate::hooks::{HookContext, HookEvent, HookRunner};
+use crate::platform::{Platform, PlatformPaths};
+use crate::system_registry::SystemRegistry;
+use crate::watch_control::{ControlRequest, ControlResponse, ControlServer, DaemonStatus};
 
 /// File system event types we care about
 #[derive(Debug, Clone)]
@@ -21,6 +27,19 @@ pub enum FileEvent {
     Renamed { from: PathBuf, to: PathBuf },
 }
 
+/// How file system changes are detected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum WatchBackend {
+    /// Native events, polling paths on network or FUSE mounts and paths the
+    /// native backend refuses
+    #[default]
+    Auto,
+    /// Native events only (inotify, FSEvents, ReadDirectoryChangesW)
+    Native,
+    /// Periodic directory scans comparing modification times
+    Poll,
+}
+
 /// Watch mode configuration
 #[derive(Debug, Clone)]
 pub struct WatchConfig {
@@ -28,6 +47,9 @@ pub struct WatchConfig {
     pub batch_size: usize,
     pub scan_delay: Duration,
     pub include_archives: bool,
+    pub backend: WatchBackend,
+    /// Time between scans of polled directories
+    pub poll_interval: Duration,
 }
 
 impl Default for WatchConfig {
@@ -37,41 +59,60 @@ impl Default for WatchConfig {
             batch_size: 100,
             scan_delay: Duration::from_secs(2),
             include_archives: true,
+            backend: WatchBackend::Auto,
+            poll_interval: Duration::from_secs(5),
         }
     }
 }
 
 /// File system watcher for ROM directories
 pub struct RomWatcher {
-    watcher: RecommendedWatcher,
+    /// None when the backend is `Poll` or native events are unavailable
+    native: Option<RecommendedWatcher>,
+    /// Created on the first directory that needs polling
+    poller: Option<PollWatcher>,
+    event_sender: Sender<notify::Result<Event>>,
     event_receiver: Receiver<notify::Result<Event>>,
     config: WatchConfig,
     watched_paths: HashSet<PathBuf>,
+    polled_paths: HashSet<PathBuf>,
     last_scan: Option<Instant>,
     pending_events: Vec<FileEvent>,
+    /// First half of renames waiting for their destination, by tracker cookie
+    pending_renames: HashMap<usize, (PathBuf, Instant)>,
+    /// Renames already reported, by tracker cookie, so the other notification
+    /// of the same rename (inotify sends From, To and then Both) is dropped
+    paired_renames: HashMap<usize, Instant>,
 }
 
 impl RomWatcher {
     /// Create a new ROM watcher
     pub fn new(config: WatchConfig) -> Result<Self> {
         let (tx, rx) = std::sync::mpsc::channel();
-        
-        let watcher = RecommendedWatcher::new(
-            move |result| {
-                if let Err(e) = tx.send(result) {
-                    error!("Failed to send watch event: {}", e);
-                }
-            },
-            notify::Config::default(),
-        ).with_context(|| "Failed to create file system watcher")?;
+
+        let native = match config.backend {
+            WatchBackend::Poll => None,
+            WatchBackend::Native => Some(
+                RecommendedWatcher::new(forward_events(tx.clone()), notify::Config::default())
+                    .with_context(|| "Failed to create file system watcher")?,
+            ),
+            WatchBackend::Auto => RecommendedWatcher::new(forward_events(tx.clone()), notify::Config::default())
+                .inspect_err(|e| warn!("Native file system events unavailable, polling instead: {}", e))
+                .ok(),
+        };
 
         Ok(Self {
-            watcher,
+            native,
+            poller: None,
+            event_sender: tx,
             event_receiver: rx,
             config,
             watched_paths: HashSet::new(),
+            polled_paths: HashSet::new(),
             last_scan: None,
             pending_events: Vec::new(),
+            pending_renames: HashMap::new(),
+            paired_renames: HashMap::new(),
         })
     }
 
@@ -89,8 +130,33 @@ impl RomWatcher {
             return Ok(());
         }
 
-        self.watcher.watch(&path, RecursiveMode::Recursive)
-            .with_context(|| format!("Failed to watch directory: {}", path.display()))?;
+        let remote = self.config.backend == WatchBackend::Auto && is_remote_mount(&path);
+        if remote {
+            info!("{} is on a network or FUSE mount, polling it", path.display());
+        }
+
+        let watched_natively = match &mut self.native {
+            Some(native) if !remote => match native.watch(&path, RecursiveMode::Recursive) {
+                Ok(()) => true,
+                Err(e) if self.config.backend == WatchBackend::Auto => {
+                    warn!("Native events unsupported for {} ({}), polling instead", path.display(), e);
+                    false
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to watch directory: {}", path.display()));
+                }
+            },
+            _ if self.config.backend == WatchBackend::Native => {
+                return Err(anyhow::anyhow!("Native file system events are unavailable"));
+            }
+            _ => false,
+        };
+
+        if !watched_natively {
+            self.poller()?.watch(&path, RecursiveMode::Recursive)
+                .with_context(|| format!("Failed to poll directory: {}", path.display()))?;
+            self.polled_paths.insert(path.clone());
+        }
         
         self.watched_paths.insert(path.clone());
         info!("Now watching directory: {}", path.display());
@@ -98,12 +164,34 @@ impl RomWatcher {
         Ok(())
     }
 
+    fn poller(&mut self) -> Result<&mut PollWatcher> {
+        if self.poller.is_none() {
+            let config = notify::Config::default().with_poll_interval(self.config.poll_interval);
+            let poller = PollWatcher::new(forward_events(self.event_sender.clone()), config)
+                .with_context(|| "Failed to create polling watcher")?;
+            self.poller = Some(poller);
+        }
+        Ok(self.poller.as_mut().unwrap())
+    }
+
+    /// Whether `path` is watched by periodic scans rather than native events
+    pub fn is_polled<P: AsRef<Path>>(&self, path: P) -> bool {
+        self.polled_paths.contains(path.as_ref())
+    }
+
     /// Remove a directory from watch
     pub fn unwatch_directory<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
         let path = path.as_ref().to_path_buf();
         
-        self.watcher.unwatch(&path)
-            .with_context(|| format!("Failed to unwatch directory: {}", path.display()))?;
+        let result = if self.polled_paths.remove(&path) {
+            self.poller()?.unwatch(&path)
+        } else {
+            match &mut self.native {
+                Some(native) => native.unwatch(&path),
+                None => Ok(()),
+            }
+        };
+        result.with_context(|| format!("Failed to unwatch directory: {}", path.display()))?;
         
         self.watched_paths.remove(&path);
         info!("Stopped watching directory: {}", path.display());
@@ -131,6 +219,8 @@ impl RomWatcher {
 
         // Add to pending events
         self.pending_events.extend(new_events);
+        let expired = self.expire_renames();
+        self.pending_events.extend(expired);
         
         // Check if we should process the batch
         let should_process = self.should_process_batch();
@@ -145,49 +235,38 @@ impl RomWatcher {
     }
 
     /// Process a single event
-    fn process_event(&self, event: Event) -> Result<Option<FileEvent>> {
+    fn process_event(&mut self, event: Event) -> Result<Option<FileEvent>> {
         self.convert_event(event)
     }
 
     /// Convert notify event to our event type
-    fn convert_event(&self, event: Event) -> Result<Option<FileEvent>> {
+    fn convert_event(&mut self, event: Event) -> Result<Option<FileEvent>> {
         use notify::EventKind;
-        
-        let paths = event.paths;
+        use notify::event::{CreateKind, ModifyKind, RemoveKind};
+
+        let tracker = event.tracker();
+        let mut paths = event.paths;
         if paths.is_empty() {
             return Ok(None);
         }
 
-        // Only process ROM files and archives
-        let first_path = &paths[0];
-        if !self.is_relevant_file(first_path) {
-            return Ok(None);
+        if let EventKind::Modify(ModifyKind::Name(mode)) = event.kind {
+            return Ok(self.convert_rename(mode, tracker, paths));
         }
 
+        let path = paths.swap_remove(0);
         let file_event = match event.kind {
-            EventKind::Create(_) => {
-                debug!("File created: {}", first_path.display());
-                Some(FileEvent::Created(first_path.clone()))
+            EventKind::Create(kind) if kind == CreateKind::Folder || self.is_relevant_file(&path) => {
+                debug!("File created: {}", path.display());
+                Some(FileEvent::Created(path))
             },
-            EventKind::Modify(_) => {
-                debug!("File modified: {}", first_path.display());
-                Some(FileEvent::Modified(first_path.clone()))
+            EventKind::Modify(_) if self.is_relevant_file(&path) => {
+                debug!("File modified: {}", path.display());
+                Some(FileEvent::Modified(path))
             },
-            EventKind::Remove(_) => {
-                debug!("File deleted: {}", first_path.display());
-                Some(FileEvent::Deleted(first_path.clone()))
-            },
-            EventKind::Other => {
-                // Check for rename events
-                if paths.len() == 2 {
-                    debug!("File renamed: {} -> {}", paths[0].display(), paths[1].display());
-                    Some(FileEvent::Renamed {
-                        from: paths[0].clone(),
-                        to: paths[1].clone(),
-                    })
-                } else {
-                    None
-                }
+            EventKind::Remove(kind) if kind == RemoveKind::Folder || self.is_relevant_file(&path) => {
+                debug!("File deleted: {}", path.display());
+                Some(FileEvent::Deleted(path))
             },
             _ => None,
         };
@@ -195,38 +274,113 @@ impl RomWatcher {
         Ok(file_event)
     }
 
-    /// Check if file is relevant for ROM scanning
+    /// Pair the two halves of a rename through the tracker cookie notify gives
+    /// both of them. A half without a partner is a move into or out of the
+    /// watched tree and becomes a create or a delete.
+    fn convert_rename(&mut self, mode: notify::event::RenameMode, tracker: Option<usize>, mut paths: Vec<PathBuf>) -> Option<FileEvent> {
+        use notify::event::RenameMode;
+
+        match mode {
+            RenameMode::Both if paths.len() == 2 => {
+                if let Some(cookie) = tracker {
+                    if self.paired_renames.remove(&cookie).is_some() {
+                        return None;
+                    }
+                    self.pending_renames.remove(&cookie);
+                    self.paired_renames.insert(cookie, Instant::now());
+                }
+                let to = paths.pop()?;
+                let from = paths.pop()?;
+                self.rename_event(from, to)
+            },
+            RenameMode::From => {
+                let from = paths.swap_remove(0);
+                match tracker {
+                    Some(cookie) => {
+                        self.pending_renames.insert(cookie, (from, Instant::now()));
+                        None
+                    },
+                    None => self.is_relevant_removal(&from).then_some(FileEvent::Deleted(from)),
+                }
+            },
+            RenameMode::To => {
+                let to = paths.swap_remove(0);
+                let Some(cookie) = tracker else {
+                    return (to.is_dir() || self.is_relevant_file(&to)).then_some(FileEvent::Created(to));
+                };
+                if self.paired_renames.remove(&cookie).is_some() {
+                    return None;
+                }
+                match self.pending_renames.remove(&cookie) {
+                    Some((from, _)) => {
+                        self.paired_renames.insert(cookie, Instant::now());
+                        self.rename_event(from, to)
+                    },
+                    None => (to.is_dir() || self.is_relevant_file(&to)).then_some(FileEvent::Created(to)),
+                }
+            },
+            // The backend can't tell which side this path is on
+            _ => {
+                let path = paths.swap_remove(0);
+                if path.exists() {
+                    (path.is_dir() || self.is_relevant_file(&path)).then_some(FileEvent::Created(path))
+                } else {
+                    self.is_relevant_removal(&path).then_some(FileEvent::Deleted(path))
+                }
+            },
+        }
+    }
+
+    fn rename_event(&self, from: PathBuf, to: PathBuf) -> Option<FileEvent> {
+        // Either side being a ROM matters, and so does any directory
+        if !(to.is_dir() || self.is_relevant_file(&from) || self.is_relevant_file(&to)) {
+            return None;
+        }
+
+        debug!("File renamed: {} -> {}", from.display(), to.display());
+        Some(FileEvent::Renamed { from, to })
+    }
+
+    /// Removed paths can't be inspected, so anything without an extension
+    /// is treated as a possible directory
+    fn is_relevant_removal(&self, path: &Path) -> bool {
+        self.is_relevant_file(path) || path.extension().is_none()
+    }
+
+    /// Renames whose destination never arrived moved the file out of the
+    /// watched directories
+    fn expire_renames(&mut self) -> Vec<FileEvent> {
+        let timeout = self.config.debounce_duration;
+        self.paired_renames.retain(|_, since| since.elapsed() < timeout);
+        let expired: Vec<usize> = self.pending_renames.iter()
+            .filter(|(_, (_, since))| since.elapsed() >= timeout)
+            .map(|(cookie, _)| *cookie)
+            .collect();
+
+        let sources: Vec<PathBuf> = expired.into_iter()
+            .filter_map(|cookie| self.pending_renames.remove(&cookie))
+            .map(|(from, _)| from)
+            .collect();
+
+        sources.into_iter()
+            .filter(|from| self.is_relevant_removal(from))
+            .map(FileEvent::Deleted)
+            .collect()
+    }
+
+    /// Check if file is relevant for ROM scanning: the extensions claimed in
+    /// configs/systems.toml, as `Scanner::is_rom_file` uses, plus M3U lists
     fn is_relevant_file(&self, path: &Path) -> bool {
-        if let Some(extension) = path.extension().and_then(|ext| ext.to_str()) {
-            let ext_lower = extension.to_lowercase();
-            
-            // ROM extensions
-            let is_rom = matches!(ext_lower.as_str(),
-                "nes" | "fds" | "unf" | "unif" |
-                "smc" | "sfc" | "swc" | "fig" |
-                "z64" | "n64" | "v64" |
-                "gcm" | "iso" | "gcz" | "rvz" |
-                "gb" | "gbc" | "gba" |
-                "nds" | "dsi" | "ids" |
-                "3ds" | "cci" | "cxi" |
-                "sms" | "sg" |
-                "md" | "smd" | "gen" | "bin" |
-                "32x" | "gg" |
-                "cdi" | "gdi" | "chd" |
-                "pbp" | "cue" | "img" | "mdf" | "nrg" | "cso" |
-                "a26" | "a78" |
-                "pce" | "sgx" | "ws" | "wsc" | "neo"
-            );
-            
-            // Archive extensions
-            let is_archive = self.config.include_archives && matches!(ext_lower.as_str(),
-                "zip" | "7z" | "rar"
-            );
-            
-            is_rom || is_archive
-        } else {
-            false
+        let Some(extension) = path.extension().and_then(|ext| ext.to_str()) else {
+            return false;
+        };
+        let extension = extension.to_lowercase();
+
+        // Archives are registered for arcade sets but only watched on request
+        if matches!(extension.as_str(), "zip" | "7z" | "rar") {
+            return self.config.include_archives;
         }
+        extension == "m3u" || SystemRegistry::global().is_known_extension(&extension)
     }
 
     /// Check if we should process the current batch of events
@@ -267,6 +421,11 @@ impl RomWatcher {
             if !processed_paths.contains(&path) {
                 processed_paths.insert(path);
                 unique_events.push(event);
+            } else if let FileEvent::Renamed { from, .. } = event {
+                // A later event replaces the rename, but its source is still gone
+                if processed_paths.insert(from.clone()) {
+                    unique_events.push(FileEvent::Deleted(from));
+                }
             }
         }
 
@@ -288,8 +447,8 @@ impl RomWatcher {
         }
     }
 
-    /// Run event loop
-    pub fn run_event_loop(&mut self) -> Result<()> {
+    /// Run event loop, passing each debounced batch of events to `handler`
+    pub fn run_event_loop(&mut self, mut handler: impl FnMut(Vec<FileEvent>) -> Result<()>) -> Result<()> {
         info!("Starting watch event loop...");
         
         loop {
@@ -301,7 +460,7 @@ impl RomWatcher {
                         
                         // Process batch if we have enough events
                         if self.pending_events.len() >= self.config.batch_size {
-                            self.process_pending_events()?;
+                            self.process_pending_events(&mut handler)?;
                         }
                     }
                 }
@@ -310,8 +469,10 @@ impl RomWatcher {
                 }
                 Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
                     // Timeout reached - process any pending events
+                    let expired = self.expire_renames();
+                    self.pending_events.extend(expired);
                     if !self.pending_events.is_empty() {
-                        self.process_pending_events()?;
+                        self.process_pending_events(&mut handler)?;
                     }
                 }
                 Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
@@ -325,7 +486,7 @@ impl RomWatcher {
     }
 
     /// Process accumulated pending events
-    fn process_pending_events(&mut self) -> Result<()> {
+    fn process_pending_events(&mut self, handler: &mut impl FnMut(Vec<FileEvent>) -> Result<()>) -> Result<()> {
         if self.pending_events.is_empty() {
             return Ok(());
         }
@@ -339,59 +500,474 @@ impl RomWatcher {
         }
 
         info!("Processing {} file events", self.pending_events.len());
-        
-        // Group events by directory to minimize scans
-        let mut affected_dirs = HashSet::new();
-        
-        for event in &self.pending_events {
+
+        let events = std::mem::take(&mut self.pending_events);
+        self.last_scan = Some(Instant::now());
+        handler(self.deduplicate_events(events))
+    }
+}
+
+fn forward_events(tx: Sender<notify::Result<Event>>) -> impl Fn(notify::Result<Event>) + Send + 'static {
+    move |result| {
+        if let Err(e) = tx.send(result) {
+            error!("Failed to send watch event: {}", e);
+        }
+    }
+}
+
+/// File systems whose changes made on other machines produce no native events
+fn is_remote_filesystem(fs_type: &str) -> bool {
+    matches!(fs_type,
+        "nfs" | "nfs4" | "cifs" | "smb3" | "smbfs" | "9p" | "afs" | "ceph" |
+        "glusterfs" | "davfs" | "fuse" | "fuseblk"
+    ) || fs_type.starts_with("fuse.")
+}
+
+/// Whether `path` lives on a network or FUSE mount, judged by the mount
+/// table entry with the longest mount point containing it
+#[cfg(target_os = "linux")]
+fn is_remote_mount(path: &Path) -> bool {
+    let Ok(path) = path.canonicalize() else {
+        return false;
+    };
+    let Ok(mounts) = std::fs::read_to_string("/proc/self/mounts") else {
+        return false;
+    };
+
+    mounts.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let mount_point = fields.nth(1)?.replace("\\040", " ");
+            let fs_type = fields.next()?;
+            Some((PathBuf::from(mount_point), fs_type))
+        })
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.as_os_str().len())
+        .is_some_and(|(_, fs_type)| is_remote_filesystem(fs_type))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_remote_mount(_path: &Path) -> bool {
+    false
+}
+
+/// Watch statistics
+#[derive(Debug)]
+pub struct WatchStats {
+    pub watched_directories: usize,
+    pub pending_events: usize,
+    pub last_scan: Option<Instant>,
+}
+
+/// Playlists written by one `IncrementalIndex::apply` call
+#[derive(Debug, Default)]
+pub struct IndexUpdate {
+    /// Files hashed again
+    pub rescanned: usize,
+    /// Files dropped from the index
+    pub removed: usize,
+    /// System playlists rewritten, or deleted when their last ROM went away
+    pub updated_playlists: Vec<String>,
+    pub master_updated: bool,
+}
+
+/// ROMs of the watched directories kept in memory, so file events re-hash
+/// only the files involved and rewrite only the playlists of their systems
+pub struct IncrementalIndex {
+    scanner: Scanner,
+    playlist_builder: PlaylistBuilder,
+    output_dir: PathBuf,
+    write_master: bool,
+    hooks: HookRunner,
+    /// Scanned file (plain ROM, archive or CUE/GDI sheet) -> ROMs found in it
+    files: HashMap<PathBuf, Vec<RomFile>>,
+    /// Directories scanned so far; path rules of rescanned files stop at them
+    roots: Vec<PathBuf>,
+}
+
+impl IncrementalIndex {
+    pub fn new(scanner: Scanner, playlist_builder: PlaylistBuilder, output_dir: PathBuf) -> Self {
+        Self {
+            scanner,
+            playlist_builder,
+            output_dir,
+            write_master: true,
+            hooks: HookRunner::default(),
+            files: HashMap::new(),
+            roots: Vec::new(),
+        }
+    }
+
+    /// Also maintain the master `roms.lpl`
+    pub fn with_master_playlist(mut self, enabled: bool) -> Self {
+        self.write_master = enabled;
+        self
+    }
+
+    /// Commands to run after playlists are written and after each update
+    pub fn with_hooks(mut self, hooks: HookRunner) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
+    /// Scan `directories` from scratch and write every playlist
+    pub fn rebuild(&mut self, directories: &[PathBuf]) -> Result<IndexUpdate> {
+        self.files.clear();
+        self.roots = directories.to_vec();
+        for dir in directories {
+            for rom in self.scanner.scan_directory_simple(dir)? {
+                self.files.entry(rom.path.clone()).or_default().push(rom);
+            }
+        }
+
+        let systems = self.roms().filter_map(|rom| rom.system.clone()).collect();
+        let mut update = self.write_playlists(systems, BTreeMap::new())?;
+        update.rescanned = self.files.len();
+        self.run_update_hooks(HookEvent::IndexComplete, &update)?;
+        Ok(update)
+    }
+
+    /// Scan one more directory and write the playlists of the systems found there
+    pub fn add_directory(&mut self, directory: &Path) -> Result<IndexUpdate> {
+        let roms = self.scanner.scan_directory_simple(directory)?;
+        if !self.roots.iter().any(|root| root == directory) {
+            self.roots.push(directory.to_path_buf());
+        }
+        let paths: BTreeSet<PathBuf> = roms.iter().map(|rom| rom.path.clone()).collect();
+
+        // Files already indexed through an enclosing directory are replaced
+        let mut systems = BTreeSet::new();
+        for path in &paths {
+            systems.extend(self.remove_file(path));
+        }
+        for rom in roms {
+            systems.extend(rom.system.clone());
+            self.files.entry(rom.path.clone()).or_default().push(rom);
+        }
+
+        let mut update = self.write_playlists(systems, BTreeMap::new())?;
+        update.rescanned = paths.len();
+        self.run_update_hooks(HookEvent::WatchBatch, &update)?;
+        Ok(update)
+    }
+
+    /// Apply a batch of file events
+    pub fn apply(&mut self, events: &[FileEvent]) -> Result<IndexUpdate> {
+        let mut removed = Vec::new();
+        let mut rescan = BTreeSet::new();
+        let mut moves = Vec::new();
+
+        for event in events {
             match event {
-                FileEvent::Created(path) | 
-                FileEvent::Modified(path) | 
-                FileEvent::Deleted(path) => {
-                    if let Some(parent) = path.parent() {
-                        affected_dirs.insert(parent.to_path_buf());
+                FileEvent::Created(path) | FileEvent::Modified(path) => self.queue_rescan(path, &mut rescan),
+                FileEvent::Deleted(path) => removed.push(path.clone()),
+                FileEvent::Renamed { from, to } => match self.move_files(from, to) {
+                    Some(moved) => moves.extend(moved),
+                    None => {
+                        removed.push(from.clone());
+                        self.queue_rescan(to, &mut rescan);
                     }
+                },
+            }
+        }
+
+        let mut update = IndexUpdate::default();
+        let mut systems = BTreeSet::new();
+
+        for path in removed {
+            // A deleted track invalidates its sheet; a deleted directory takes its files along
+            if let Some(sheet) = self.sheet_of(&path) {
+                rescan.insert(sheet);
+            }
+            let gone: Vec<PathBuf> = self.files.keys()
+                .filter(|file| file.starts_with(&path))
+                .cloned()
+                .collect();
+            for file in gone {
+                systems.extend(self.remove_file(&file));
+                update.removed += 1;
+            }
+        }
+
+        if !rescan.is_empty() {
+            let paths: Vec<PathBuf> = rescan.into_iter().collect();
+            for path in &paths {
+                systems.extend(self.remove_file(path));
+            }
+
+            let roms = self.scanner.scan_files(&self.roots, paths.clone())?;
+            update.rescanned = paths.len();
+            for rom in roms {
+                // Tracks indexed on their own before their sheet showed up
+                for track in &rom.tracks {
+                    systems.extend(self.remove_file(track));
+                }
+                systems.extend(rom.system.clone());
+                self.files.entry(rom.path.clone()).or_default().push(rom);
+            }
+        }
+
+        // Moved ROMs that stay in their system are patched into the playlist
+        // in place, which keeps entries the user edited
+        let mut patches: BTreeMap<String, Vec<(RomFile, RomFile)>> = BTreeMap::new();
+        for (old, new) in moves {
+            match (&old.system, &new.system) {
+                (Some(before), Some(after)) if before == after => {
+                    patches.entry(after.clone()).or_default().push((old, new));
+                }
+                _ => systems.extend(old.system.into_iter().chain(new.system)),
+            }
+        }
+
+        let written = self.write_playlists(systems, patches)?;
+        update.updated_playlists = written.updated_playlists;
+        update.master_updated = written.master_updated;
+        self.run_update_hooks(HookEvent::WatchBatch, &update)?;
+        Ok(update)
+    }
+
+    fn run_update_hooks(&self, event: HookEvent, update: &IndexUpdate) -> Result<()> {
+        let mut playlists: Vec<PathBuf> = update.updated_playlists.iter()
+            .map(|system| self.output_dir.join(format!("{}.lpl", system)))
+            .collect();
+        if update.master_updated {
+            playlists.push(self.output_dir.join("roms.lpl"));
+        }
+
+        self.hooks.run(&HookContext {
+            playlists,
+            roms: self.roms().count(),
+            rescanned: update.rescanned,
+            removed: update.removed,
+            ..HookContext::new(event, self.output_dir.clone())
+        })
+    }
+
+    /// Run the `after_playlist` hooks for a playlist just written
+    fn run_playlist_hook(&self, path: &Path, system: Option<&str>) -> Result<()> {
+        if !self.hooks.has_hooks(HookEvent::PlaylistWritten) {
+            return Ok(());
+        }
+
+        let roms = match system {
+            Some(system) => self.roms().filter(|rom| rom.system.as_deref() == Some(system)).count(),
+            None => self.roms().count(),
+        };
+        self.hooks.run(&HookContext {
+            playlists: vec![path.to_path_buf()],
+            system: system.map(str::to_string),
+            roms,
+            ..HookContext::new(HookEvent::PlaylistWritten, self.output_dir.clone())
+        })
+    }
+
+    /// Every indexed ROM
+    pub fn roms(&self) -> impl Iterator<Item = &RomFile> {
+        self.files.values().flatten()
+    }
+
+    fn queue_rescan(&self, path: &Path, rescan: &mut BTreeSet<PathBuf>) {
+        if path.is_dir() {
+            // A directory moved or copied into a watched tree
+            rescan.extend(
+                WalkDir::new(path)
+                    .into_iter()
+                    .filter_map(|entry| entry.ok())
+                    .filter(|entry| entry.file_type().is_file())
+                    .map(|entry| entry.into_path()),
+            );
+        } else {
+            rescan.insert(self.sheet_of(path).unwrap_or_else(|| path.to_path_buf()));
+        }
+    }
+
+    /// Re-key every indexed file at or below `from` to its place under `to`,
+    /// keeping its hashes. Returns the ROMs before and after the move, or None
+    /// when nothing was indexed there or a file changed extension and has to
+    /// be scanned again.
+    fn move_files(&mut self, from: &Path, to: &Path) -> Option<Vec<(RomFile, RomFile)>> {
+        let keys: Vec<PathBuf> = self.files.keys()
+            .filter(|key| key.starts_with(from))
+            .cloned()
+            .collect();
+        if keys.is_empty() {
+            return None;
+        }
+        let same_extension = from.extension().map(|ext| ext.to_ascii_lowercase())
+            == to.extension().map(|ext| ext.to_ascii_lowercase());
+        if keys.iter().any(|key| key == from) && !same_extension {
+            return None;
+        }
+
+        let mut moved = Vec::new();
+        for key in keys {
+            let new_key = relocate(&key, from, to);
+            let mut relocated = Vec::new();
+
+            for old in self.files.remove(&key).unwrap_or_default() {
+                let mut rom = old.clone();
+                rom.path = new_key.clone();
+                if rom.archive_entry.is_none() {
+                    rom.filename = new_key.file_name().unwrap_or_default().to_string_lossy().to_string();
                 }
-                FileEvent::Renamed { from, to } => {
-                    if let Some(parent) = from.parent() {
-                        affected_dirs.insert(parent.to_path_buf());
+                for track in rom.tracks.iter_mut().chain(rom.missing_tracks.iter_mut()) {
+                    *track = relocate(track, from, to);
+                }
+                // Folder names take part in system detection
+                rom.identify_system();
+
+                debug!("Movido sem recalcular hash: {} -> {}", old.playlist_path().display(), rom.playlist_path().display());
+                moved.push((old, rom.clone()));
+                relocated.push(rom);
+            }
+
+            self.files.insert(new_key, relocated);
+        }
+
+        Some(moved)
+    }
+
+    /// CUE/GDI sheet that lists `path` as one of its tracks
+    fn sheet_of(&self, path: &Path) -> Option<PathBuf> {
+        self.roms()
+            .find(|rom| rom.tracks.iter().chain(&rom.missing_tracks).any(|track| track == path))
+            .map(|rom| rom.path.clone())
+    }
+
+    /// Drop a file from the index, returning the systems its ROMs belonged to
+    fn remove_file(&mut self, path: &Path) -> Vec<String> {
+        self.files.remove(path)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|rom| rom.system)
+            .collect()
+    }
+
+    /// Rewrite the playlists of `systems`, patch moved entries into the
+    /// others and update the master playlist. Files whose content would not
+    /// change are left alone.
+    fn write_playlists(
+        &self,
+        mut systems: BTreeSet<String>,
+        patches: BTreeMap<String, Vec<(RomFile, RomFile)>>,
+    ) -> Result<IndexUpdate> {
+        let mut update = IndexUpdate::default();
+        let mut master_moves = Vec::new();
+
+        for (system, moves) in patches {
+            if systems.contains(&system) {
+                continue;
+            }
+            let playlist_path = self.output_dir.join(format!("{}.lpl", system));
+            match self.patch_playlist(&playlist_path, &moves)? {
+                Some(changed) => {
+                    if changed {
+                        info!("Playlist atualizada: {}", playlist_path.display());
+                        self.run_playlist_hook(&playlist_path, Some(&system))?;
+                        update.updated_playlists.push(system);
                     }
-                    if let Some(parent) = to.parent() {
-                        affected_dirs.insert(parent.to_path_buf());
+                    master_moves.extend(moves);
+                }
+                None => {
+                    systems.insert(system);
+                }
+            }
+        }
+
+        if systems.is_empty() && master_moves.is_empty() {
+            return Ok(update);
+        }
+
+        let affected: Vec<RomFile> = self.roms()
+            .filter(|rom| rom.system.as_ref().is_some_and(|system| systems.contains(system)))
+            .cloned()
+            .collect();
+        let mut playlists = self.playlist_builder.build_by_system(&affected)?;
+
+        for system in &systems {
+            let playlist_path = self.output_dir.join(format!("{}.lpl", system));
+            match playlists.remove(system) {
+                Some(playlist) => {
+                    if playlist.save_if_changed(&playlist_path)? {
+                        info!("Playlist atualizada: {}", playlist_path.display());
+                        self.run_playlist_hook(&playlist_path, Some(system))?;
+                        update.updated_playlists.push(system.clone());
                     }
                 }
+                None if playlist_path.exists() => {
+                    std::fs::remove_file(&playlist_path)
+                        .with_context(|| format!("Failed to remove playlist: {}", playlist_path.display()))?;
+                    info!("Playlist removida: {}", playlist_path.display());
+                    update.updated_playlists.push(system.clone());
+                }
+                None => {}
             }
         }
 
-        // Trigger rescan for affected directories
-        for dir in affected_dirs {
-            info!("Rescanning directory: {}", dir.display());
-            // Here you would integrate with your scanner and playlist builder
-            // For now, just log the action
-            debug!("Would rescan directory: {}", dir.display());
+        if self.write_master {
+            let master_path = self.output_dir.join("roms.lpl");
+            let patched = if systems.is_empty() {
+                self.patch_playlist(&master_path, &master_moves)?
+            } else {
+                None
+            };
+            update.master_updated = match patched {
+                Some(changed) => changed,
+                None => {
+                    let all_roms: Vec<RomFile> = self.roms().cloned().collect();
+                    self.playlist_builder.build_master(&all_roms)?.save_if_changed(&master_path)?
+                }
+            };
+            if update.master_updated {
+                self.run_playlist_hook(&master_path, None)?;
+            }
         }
 
-        // Clear processed events
-        self.pending_events.clear();
-        self.last_scan = Some(Instant::now());
-        
-        Ok(())
+        Ok(update)
+    }
+
+    /// Point the entries of moved ROMs at their new paths. Labels are renamed
+    /// along with the file unless the user changed them. Returns None when the
+    /// playlist lacks one of the entries and must be rebuilt instead.
+    fn patch_playlist(&self, path: &Path, moves: &[(RomFile, RomFile)]) -> Result<Option<bool>> {
+        let Ok(mut playlist) = Playlist::load(path) else {
+            return Ok(None);
+        };
+
+        for (old, new) in moves {
+            let (Some(old_system), Some(new_system)) = (&old.system, &new.system) else {
+                return Ok(None);
+            };
+            let old_item = self.playlist_builder.create_playlist_item(old, old_system)?;
+            let new_item = self.playlist_builder.create_playlist_item(new, new_system)?;
+
+            let Some(item) = playlist.items.iter_mut().find(|item| item.path == old_item.path) else {
+                return Ok(None);
+            };
+            item.path = new_item.path;
+            if item.label == old_item.label {
+                item.label = new_item.label;
+            }
+        }
+
+        playlist.sort_by_label();
+        playlist.save_if_changed(path).map(Some)
     }
 }
 
-/// Watch statistics
-#[derive(Debug)]
-pub struct WatchStats {
-    pub watched_directories: usize,
-    pub pending_events: usize,
-    pub last_scan: Option<Instant>,
+/// `path` with its `from` prefix replaced by `to`
+fn relocate(path: &Path, from: &Path, to: &Path) -> PathBuf {
+    match path.strip_prefix(from) {
+        Ok(rest) if rest.as_os_str().is_empty() => to.to_path_buf(),
+        Ok(rest) => to.join(rest),
+        Err(_) => path.to_path_buf(),
+    }
 }
 
 /// Watch mode runner
 pub struct WatchRunner {
     watcher: RomWatcher,
-    scanner: Scanner,
-    playlist_generator: PlaylistBuilder,
+    index: IncrementalIndex,
     config: Arc<Config>,
 }
 
@@ -404,11 +980,11 @@ impl WatchRunner {
         config: Arc<Config>,
     ) -> Result<Self> {
         let watcher = RomWatcher::new(watch_config)?;
+        let index = IncrementalIndex::new(scanner, playlist_generator, config.paths.output_directory.clone());
         
         Ok(Self {
             watcher,
-            scanner,
-            playlist_generator,
+            index,
             config,
         })
     }
@@ -427,6 +1003,7 @@ impl WatchRunner {
     pub fn run(&mut self) -> Result<()> {
         info!("Starting ROM directory watch mode...");
         self.start_watching()?;
+        self.index.rebuild(&self.config.paths.roms_directories)?;
 
         loop {
             // Process file system events
@@ -444,67 +1021,238 @@ impl WatchRunner {
 
     /// Handle file system events
     fn handle_events(&mut self, events: Vec<FileEvent>) -> Result<()> {
-        let mut should_rescan = false;
-        
-        for event in events {
+        for event in &events {
             match event {
-                FileEvent::Created(path) => {
-                    info!("New ROM detected: {}", path.display());
-                    should_rescan = true;
-                },
-                FileEvent::Modified(path) => {
-                    info!("ROM modified: {}", path.display());
-                    should_rescan = true;
-                },
-                FileEvent::Deleted(path) => {
-                    info!("ROM deleted: {}", path.display());
-                    should_rescan = true;
-                },
-                FileEvent::Renamed { from, to } => {
-                    info!("ROM renamed: {} -> {}", from.display(), to.display());
-                    should_rescan = true;
-                },
+                FileEvent::Created(path) => info!("New ROM detected: {}", path.display()),
+                FileEvent::Modified(path) => info!("ROM modified: {}", path.display()),
+                FileEvent::Deleted(path) => info!("ROM deleted: {}", path.display()),
+                FileEvent::Renamed { from, to } => info!("ROM renamed: {} -> {}", from.display(), to.display()),
             }
         }
 
-        if should_rescan {
-            self.perform_incremental_scan()?;
-        }
+        let start_time = Instant::now();
+        let update = self.index.apply(&events)?;
+        info!(
+            "Incremental update completed in {:?} - {} files rescanned, {} removed, {} playlists updated",
+            start_time.elapsed(), update.rescanned, update.removed, update.updated_playlists.len()
+        );
 
         Ok(())
     }
+}
 
-    /// Perform an incremental scan and update playlists
-    fn perform_incremental_scan(&mut self) -> Result<()> {
-        info!("Performing incremental ROM scan...");
-        
-        // TODO: Implement incremental scanning logic
-        // For now, do a full rescan
-        let start_time = Instant::now();
-        
-        // Scan all ROM directories
-        let mut all_roms = Vec::new();
-        for rom_dir in &self.config.paths.roms_directories {
-            let roms = self.scanner.scan_directory(rom_dir)?;
-            all_roms.extend(roms);
+/// Scan requested over the control socket, run from the daemon loop after
+/// the request was acknowledged
+#[derive(Debug, Clone, PartialEq)]
+enum ScheduledScan {
+    Rebuild,
+    Directory(PathBuf),
+}
+
+/// Watch loop that answers requests from the control socket between batches
+/// of file events
+pub struct WatchDaemon {
+    watcher: RomWatcher,
+    index: IncrementalIndex,
+    /// Events collected while paused
+    held: Vec<FileEvent>,
+    scheduled: Vec<ScheduledScan>,
+    status: DaemonStatus,
+}
+
+impl WatchDaemon {
+    pub fn new(watcher: RomWatcher, index: IncrementalIndex) -> Self {
+        Self {
+            watcher,
+            index,
+            held: Vec::new(),
+            scheduled: Vec::new(),
+            status: DaemonStatus {
+                started_at: Some(chrono::Utc::now()),
+                ..Default::default()
+            },
         }
+    }
 
-        // Generate playlists
-        let output_dir = &self.config.paths.output_directory;
-        let playlists = self.playlist_generator.build_by_system(&all_roms)?;
-        
-        // Save each playlist
-        for (system_name, playlist) in playlists {
-            let playlist_path = Path::new(output_dir).join(format!("{}.lpl", system_name));
-            playlist.save(&playlist_path)?;
-            info!("Saved playlist: {}", playlist_path.display());
+    /// Queue a full scan for the daemon loop; repeated requests collapse into one
+    pub fn schedule_rebuild(&mut self) {
+        if !self.scheduled.contains(&ScheduledScan::Rebuild) {
+            self.scheduled.push(ScheduledScan::Rebuild);
+        }
+    }
+
+    /// Index every watched directory from scratch
+    pub fn rebuild(&mut self) -> Result<IndexUpdate> {
+        // The scan sees the current state of the disk, so queued events are moot
+        self.held.clear();
+        let directories = self.directories();
+        let update = self.index.rebuild(&directories)?;
+        self.status.last_rebuild = Some(chrono::Utc::now());
+        info!("Indexed {} files, {} playlists written", update.rescanned, update.updated_playlists.len());
+        Ok(update)
+    }
+
+    /// Serve control requests and file events until a `Stop` request arrives
+    pub fn run(mut self, server: ControlServer) -> Result<()> {
+        info!("Watch daemon listening on {}", server.path().display());
+
+        loop {
+            while let Some(pending) = server.try_recv() {
+                let stop = pending.request == ControlRequest::Stop;
+                let response = self.handle(&pending.request);
+                pending.respond(response);
+                if stop {
+                    info!("Watch daemon stopped by control request");
+                    return Ok(());
+                }
+                server.publish_status(self.status());
+            }
+
+            // Status requests are answered from this snapshot while the scans run
+            server.publish_status(self.status());
+            if let Err(e) = self.run_scheduled().and_then(|_| self.tick()) {
+                // Only hooks under the `abort` policy fail hard enough to stop the daemon
+                if e.downcast_ref::<HookError>().is_some() {
+                    return Err(e);
+                }
+                error!("Failed to update the index: {:#}", e);
+                self.status.last_error = Some(format!("{:#}", e));
+            }
+
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    }
+
+    /// Run the scans requested through `Rescan` and `Watch`. They can take
+    /// minutes on large collections, longer than a control client waits.
+    pub fn run_scheduled(&mut self) -> Result<()> {
+        while !self.scheduled.is_empty() {
+            match self.scheduled.remove(0) {
+                ScheduledScan::Rebuild => {
+                    self.rebuild()?;
+                }
+                ScheduledScan::Directory(path) => {
+                    let update = self.index.add_directory(&path)?;
+                    info!(
+                        "Indexed {}: {} files, {} playlists written",
+                        path.display(), update.rescanned, update.updated_playlists.len()
+                    );
+                }
+            }
         }
-        
-        let elapsed = start_time.elapsed();
-        info!("Incremental scan completed in {:?} - found {} ROMs", elapsed, all_roms.len());
-        
         Ok(())
     }
+
+    /// Collect pending file events and apply them, unless paused
+    pub fn tick(&mut self) -> Result<Option<IndexUpdate>> {
+        let events = self.watcher.process_events()?;
+        self.held.extend(events);
+        if self.status.paused || self.held.is_empty() {
+            return Ok(None);
+        }
+
+        let events = self.watcher.deduplicate_events(std::mem::take(&mut self.held));
+        info!("Processing {} file system events", events.len());
+        let update = self.index.apply(&events)?;
+        info!(
+            "{} files rescanned, {} removed, {} playlists updated",
+            update.rescanned, update.removed, update.updated_playlists.len()
+        );
+
+        self.status.last_update = Some(chrono::Utc::now());
+        self.status.updates_applied += 1;
+        Ok(Some(update))
+    }
+
+    /// Carry out one control request
+    pub fn handle(&mut self, request: &ControlRequest) -> ControlResponse {
+        debug!("Control request: {:?}", request);
+        let result = match request {
+            ControlRequest::Status => return ControlResponse::Status(self.status()),
+            ControlRequest::Pause => {
+                self.status.paused = true;
+                Ok("Monitoramento pausado; eventos serão aplicados ao retomar".to_string())
+            }
+            ControlRequest::Resume => {
+                self.status.paused = false;
+                Ok(format!("Monitoramento retomado; {} eventos na fila", self.held.len()))
+            }
+            ControlRequest::Watch { path } => self.watch(path),
+            ControlRequest::Unwatch { path } => self.unwatch(path),
+            ControlRequest::Rescan => {
+                self.schedule_rebuild();
+                Ok("Varredura completa agendada; acompanhe com `watch-ctl status`".to_string())
+            }
+            ControlRequest::Stop => Ok("Daemon encerrado".to_string()),
+        };
+
+        match result {
+            Ok(message) => ControlResponse::ok(message),
+            Err(e) => {
+                let message = format!("{:#}", e);
+                self.status.last_error = Some(message.clone());
+                ControlResponse::error(message)
+            }
+        }
+    }
+
+    pub fn status(&self) -> DaemonStatus {
+        let directories = self.directories();
+        DaemonStatus {
+            polled_directories: directories.iter().filter(|dir| self.watcher.is_polled(dir)).cloned().collect(),
+            watched_directories: directories,
+            queued_events: self.watcher.stats().pending_events + self.held.len(),
+            scheduled_scans: self.scheduled.len(),
+            rebuilding: self.scheduled.contains(&ScheduledScan::Rebuild),
+            indexed_files: self.index.roms().count(),
+            ..self.status.clone()
+        }
+    }
+
+    fn directories(&self) -> Vec<PathBuf> {
+        let mut directories = self.watcher.watched_directories();
+        directories.sort();
+        directories
+    }
+
+    fn watch(&mut self, path: &Path) -> Result<String> {
+        if !path.is_absolute() {
+            anyhow::bail!("Use um caminho absoluto: {}", path.display());
+        }
+        if !path.is_dir() {
+            anyhow::bail!("Diretório não encontrado: {}", path.display());
+        }
+        if self.directories().iter().any(|dir| dir == path) {
+            anyhow::bail!("Diretório já monitorado: {}", path.display());
+        }
+
+        self.watcher.watch_directory(path)?;
+        self.scheduled.push(ScheduledScan::Directory(path.to_path_buf()));
+        Ok(format!("Monitorando {}; indexação agendada", path.display()))
+    }
+
+    fn unwatch(&mut self, path: &Path) -> Result<String> {
+        if !self.directories().iter().any(|dir| dir == path) {
+            anyhow::bail!("Diretório não monitorado: {}", path.display());
+        }
+
+        self.watcher.unwatch_directory(path)?;
+        self.held.retain(|event| !event_touches(event, path));
+        self.scheduled.retain(|scan| *scan != ScheduledScan::Directory(path.to_path_buf()));
+        let update = self.index.apply(&[FileEvent::Deleted(path.to_path_buf())])?;
+        Ok(format!(
+            "{} não é mais monitorado: {} arquivos removidos, {} playlists atualizadas",
+            path.display(), update.removed, update.updated_playlists.len()
+        ))
+    }
+}
+
+/// Whether `event` concerns a path at or below `directory`
+fn event_touches(event: &FileEvent, directory: &Path) -> bool {
+    match event {
+        FileEvent::Created(path) | FileEvent::Modified(path) | FileEvent::Deleted(path) => path.starts_with(directory),
+        FileEvent::Renamed { from, to } => from.starts_with(directory) || to.starts_with(directory),
+    }
 }
 
 /// Watch service for monitoring ROM directories and auto-updating playlists
@@ -514,6 +1262,13 @@ pub struct WatchService {
     source_platform: Platform,
     target_platform: Platform,
     output_dir: PathBuf,
+    scanner: Scanner,
+    write_master: bool,
+    hooks: HookRunner,
+    core_mapper: CoreMapper,
+    source_paths: PlatformPaths,
+    target_paths: PlatformPaths,
+    playlist_format: PlaylistFormat,
 }
 
 impl WatchService {
@@ -525,6 +1280,13 @@ impl WatchService {
             source_platform: Platform::Windows,
             target_platform: Platform::Windows,
             output_dir: PathBuf::from("./playlists"),
+            scanner: Scanner::new(),
+            write_master: true,
+            hooks: HookRunner::default(),
+            core_mapper: CoreMapper::new(),
+            source_paths: PlatformPaths::default(),
+            target_paths: PlatformPaths::default(),
+            playlist_format: PlaylistFormat::Json,
         }
     }
 
@@ -540,6 +1302,18 @@ impl WatchService {
         self
     }
 
+    /// Select how changes are detected
+    pub fn with_backend(mut self, backend: WatchBackend) -> Self {
+        self.config.backend = backend;
+        self
+    }
+
+    /// Time between scans of polled directories
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.config.poll_interval = interval;
+        self
+    }
+
     /// Set include archives flag
     pub fn with_include_archives(mut self, include: bool) -> Self {
         self.config.include_archives = include;
@@ -553,12 +1327,49 @@ impl WatchService {
         self
     }
 
+    /// ROM and core directories of the source and target devices
+    pub fn with_device_paths(mut self, source: PlatformPaths, target: PlatformPaths) -> Self {
+        self.source_paths = source;
+        self.target_paths = target;
+        self
+    }
+
+    /// Format of the playlists written
+    pub fn with_playlist_format(mut self, format: PlaylistFormat) -> Self {
+        self.playlist_format = format;
+        self
+    }
+
     /// Set output directory
     pub fn with_output_dir(mut self, dir: PathBuf) -> Self {
         self.output_dir = dir;
         self
     }
 
+    /// Scanner used to hash new and changed files
+    pub fn with_scanner(mut self, scanner: Scanner) -> Self {
+        self.scanner = scanner;
+        self
+    }
+
+    /// Keep the master `roms.lpl` up to date
+    pub fn with_master_playlist(mut self, enabled: bool) -> Self {
+        self.write_master = enabled;
+        self
+    }
+
+    /// Core mapping for the playlist entries
+    pub fn with_core_mapper(mut self, core_mapper: CoreMapper) -> Self {
+        self.core_mapper = core_mapper;
+        self
+    }
+
+    /// Commands to run when playlists change
+    pub fn with_hooks(mut self, hooks: HookRunner) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
     /// Add directory to watch
     pub fn add_watch_directory(&mut self, path: PathBuf) -> Result<()> {
         if !path.exists() {
@@ -569,8 +1380,37 @@ impl WatchService {
         Ok(())
     }
 
-    /// Start watching directories
-    pub fn start_watching(&self) -> Result<()> {
+    /// Index the watched directories, then keep their playlists up to date
+    pub fn start_watching(self) -> Result<()> {
+        let directories = self.watched_directories.clone();
+        let (mut watcher, mut index) = self.into_parts()?;
+        let initial = index.rebuild(&directories)?;
+        info!("Indexed {} files, {} playlists written", initial.rescanned, initial.updated_playlists.len());
+
+        // Start event loop
+        watcher.run_event_loop(|events| {
+            let update = index.apply(&events)?;
+            info!(
+                "{} files rescanned, {} removed, {} playlists updated",
+                update.rescanned, update.removed, update.updated_playlists.len()
+            );
+            Ok(())
+        })?;
+        
+        Ok(())
+    }
+
+    /// Like `start_watching`, but take commands from a control socket at `socket`
+    pub fn run_daemon(self, socket: &Path) -> Result<()> {
+        let server = ControlServer::bind(socket)?;
+        let (watcher, index) = self.into_parts()?;
+        let mut daemon = WatchDaemon::new(watcher, index);
+        // The first scan runs from the loop, with the socket already answering
+        daemon.schedule_rebuild();
+        daemon.run(server)
+    }
+
+    fn into_parts(self) -> Result<(RomWatcher, IncrementalIndex)> {
         let mut watcher = RomWatcher::new(self.config.clone())?;
         
         // Watch all directories
@@ -578,10 +1418,16 @@ impl WatchService {
             watcher.watch_directory(dir)?;
         }
 
-        // Start event loop
-        watcher.run_event_loop()?;
-        
-        Ok(())
+        let playlist_builder = PlaylistBuilder::new()
+            .with_platforms(self.source_platform, self.target_platform)
+            .with_device_paths(self.source_paths, self.target_paths)
+            .with_format(self.playlist_format)
+            .with_core_mapper(self.core_mapper);
+        let index = IncrementalIndex::new(self.scanner, playlist_builder, self.output_dir)
+            .with_master_playlist(self.write_master)
+            .with_hooks(self.hooks);
+
+        Ok((watcher, index))
     }
 }
 
@@ -613,6 +1459,14 @@ mod tests {
         assert!(watcher.is_relevant_file(Path::new("test.zip")));
         assert!(!watcher.is_relevant_file(Path::new("test.txt")));
         assert!(!watcher.is_relevant_file(Path::new("test.jpg")));
+
+        // Systems added to configs/systems.toml are picked up without code changes
+        assert!(watcher.is_relevant_file(Path::new("test.lnx")));
+        assert!(watcher.is_relevant_file(Path::new("test.VB")));
+        assert!(watcher.is_relevant_file(Path::new("game.m3u")));
+
+        let watcher = RomWatcher::new(WatchConfig { include_archives: false, ..WatchConfig::default() }).unwrap();
+        assert!(!watcher.is_relevant_file(Path::new("test.zip")));
     }
 
     #[test]
@@ -627,4 +1481,292 @@ mod tests {
         assert_eq!(watcher.watched_directories().len(), 1);
         assert!(watcher.watched_directories().contains(&temp_dir.path().to_path_buf()));
     }
+
+    #[test]
+    fn test_remote_filesystem_types() {
+        for fs_type in ["nfs4", "cifs", "fuse.sshfs", "fuseblk", "9p"] {
+            assert!(is_remote_filesystem(fs_type), "{}", fs_type);
+        }
+        for fs_type in ["ext4", "btrfs", "tmpfs", "overlay"] {
+            assert!(!is_remote_filesystem(fs_type), "{}", fs_type);
+        }
+    }
+
+    #[test]
+    fn test_forced_polling_reports_new_files() {
+        let temp_dir = tempdir().unwrap();
+        let config = WatchConfig {
+            backend: WatchBackend::Poll,
+            poll_interval: Duration::from_millis(100),
+            ..WatchConfig::default()
+        };
+        let mut watcher = RomWatcher::new(config).unwrap();
+        watcher.watch_directory(temp_dir.path()).unwrap();
+        assert!(watcher.is_polled(temp_dir.path()));
+
+        let rom = temp_dir.path().join("Game.nes");
+        fs::write(&rom, b"nes rom").unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut events = Vec::new();
+        while events.is_empty() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+            events = watcher.process_events().unwrap();
+        }
+        assert!(events.iter().any(|event| matches!(event, FileEvent::Created(path) if path == &rom)), "{:?}", events);
+    }
+
+    #[test]
+    fn test_incremental_index_updates_only_affected_playlists() {
+        let roms_dir = tempdir().unwrap();
+        let output_dir = tempdir().unwrap();
+        let roms = roms_dir.path();
+        fs::write(roms.join("Alpha.gba"), b"gba rom one").unwrap();
+        fs::write(roms.join("Beta.gb"), b"gb rom one").unwrap();
+
+        let mut index = IncrementalIndex::new(
+            Scanner::new().with_threads(1),
+            PlaylistBuilder::new(),
+            output_dir.path().to_path_buf(),
+        );
+        let initial = index.rebuild(&[roms.to_path_buf()]).unwrap();
+        assert_eq!(initial.updated_playlists.len(), 2);
+
+        let gba_playlist = output_dir.path().join("Nintendo - Game Boy Advance.lpl");
+        let gb_playlist = output_dir.path().join("Nintendo - Game Boy.lpl");
+        let gb_before = fs::read(&gb_playlist).unwrap();
+
+        fs::write(roms.join("Gamma.gba"), b"gba rom two").unwrap();
+        let update = index.apply(&[FileEvent::Created(roms.join("Gamma.gba"))]).unwrap();
+        assert_eq!(update.rescanned, 1);
+        assert_eq!(update.updated_playlists, vec!["Nintendo - Game Boy Advance".to_string()]);
+        assert!(update.master_updated);
+        assert_eq!(fs::read(&gb_playlist).unwrap(), gb_before);
+        assert_eq!(Playlist::load(&gba_playlist).unwrap().items.len(), 2);
+
+        fs::rename(roms.join("Gamma.gba"), roms.join("Delta.gba")).unwrap();
+        index.apply(&[FileEvent::Renamed { from: roms.join("Gamma.gba"), to: roms.join("Delta.gba") }]).unwrap();
+        let labels: Vec<String> = Playlist::load(&gba_playlist).unwrap().items.into_iter().map(|item| item.label).collect();
+        assert_eq!(labels, vec!["Alpha".to_string(), "Delta".to_string()]);
+
+        // A modification that changes nothing rewrites nothing
+        let update = index.apply(&[FileEvent::Modified(roms.join("Alpha.gba"))]).unwrap();
+        assert!(update.updated_playlists.is_empty() && !update.master_updated);
+
+        fs::remove_file(roms.join("Beta.gb")).unwrap();
+        let update = index.apply(&[FileEvent::Deleted(roms.join("Beta.gb"))]).unwrap();
+        assert_eq!(update.removed, 1);
+        assert!(!gb_playlist.exists());
+        assert_eq!(Playlist::load(&output_dir.path().join("roms.lpl")).unwrap().items.len(), 2);
+    }
+
+    #[test]
+    fn test_daemon_pause_resume_and_runtime_directories() {
+        let first_dir = tempdir().unwrap();
+        let second_dir = tempdir().unwrap();
+        let output_dir = tempdir().unwrap();
+        fs::write(first_dir.path().join("Alpha.gba"), b"gba rom one").unwrap();
+        fs::write(second_dir.path().join("Beta.gb"), b"gb rom one").unwrap();
+
+        let config = WatchConfig {
+            scan_delay: Duration::ZERO,
+            ..WatchConfig::default()
+        };
+        let mut watcher = RomWatcher::new(config).unwrap();
+        watcher.watch_directory(first_dir.path()).unwrap();
+        let index = IncrementalIndex::new(Scanner::new().with_threads(1), PlaylistBuilder::new(), output_dir.path().to_path_buf());
+        let mut daemon = WatchDaemon::new(watcher, index);
+        daemon.rebuild().unwrap();
+
+        let gba_playlist = output_dir.path().join("Nintendo - Game Boy Advance.lpl");
+        let gb_playlist = output_dir.path().join("Nintendo - Game Boy.lpl");
+        assert_eq!(Playlist::load(&gba_playlist).unwrap().items.len(), 1);
+
+        // Events arriving while paused are queued, not applied
+        assert!(matches!(daemon.handle(&ControlRequest::Pause), ControlResponse::Ok { .. }));
+        fs::write(first_dir.path().join("Gamma.gba"), b"gba rom two").unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while daemon.status().queued_events == 0 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+            assert!(daemon.tick().unwrap().is_none());
+        }
+        assert!(daemon.status().paused && daemon.status().queued_events > 0);
+        assert_eq!(Playlist::load(&gba_playlist).unwrap().items.len(), 1);
+
+        daemon.handle(&ControlRequest::Resume);
+        assert!(daemon.tick().unwrap().is_some());
+        assert_eq!(Playlist::load(&gba_playlist).unwrap().items.len(), 2);
+
+        // New directories are acknowledged at once and indexed from the loop
+        let second = second_dir.path().to_path_buf();
+        assert!(matches!(daemon.handle(&ControlRequest::Watch { path: second.clone() }), ControlResponse::Ok { .. }));
+        assert!(!gb_playlist.exists());
+        assert_eq!(daemon.status().scheduled_scans, 1);
+        daemon.run_scheduled().unwrap();
+        assert!(gb_playlist.exists());
+        assert_eq!(daemon.status().scheduled_scans, 0);
+        assert!(matches!(daemon.handle(&ControlRequest::Watch { path: second.clone() }), ControlResponse::Error { .. }));
+
+        match daemon.handle(&ControlRequest::Status) {
+            ControlResponse::Status(status) => {
+                assert_eq!(status.watched_directories.len(), 2);
+                assert_eq!(status.indexed_files, 3);
+                assert_eq!(status.updates_applied, 1);
+                assert!(status.last_rebuild.is_some());
+            }
+            other => panic!("unexpected response: {:?}", other),
+        }
+
+        // Repeated rescan requests collapse into one scheduled scan
+        daemon.handle(&ControlRequest::Rescan);
+        daemon.handle(&ControlRequest::Rescan);
+        assert_eq!(daemon.status().scheduled_scans, 1);
+        assert!(daemon.status().rebuilding);
+        daemon.run_scheduled().unwrap();
+        assert_eq!(daemon.status().indexed_files, 3);
+        assert!(!daemon.status().rebuilding);
+
+        assert!(matches!(daemon.handle(&ControlRequest::Unwatch { path: second }), ControlResponse::Ok { .. }));
+        assert!(!gb_playlist.exists());
+        assert_eq!(daemon.status().watched_directories, vec![first_dir.path().to_path_buf()]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hooks_run_after_playlist_writes_and_batches() {
+        let roms_dir = tempdir().unwrap();
+        let output_dir = tempdir().unwrap();
+        let log = output_dir.path().join("hooks.log");
+        let roms = roms_dir.path();
+        fs::write(roms.join("Alpha.gba"), b"gba rom one").unwrap();
+
+        let append = |line: &str| format!("echo \"{}\" >> '{}'", line, log.display());
+        let hooks = HookRunner::new(crate::config::HooksConfig {
+            after_playlist: vec![append("playlist $RETROARCH_INDEXER_SYSTEM $RETROARCH_INDEXER_ROMS")],
+            after_watch_batch: vec![append("batch $RETROARCH_INDEXER_RESCANNED")],
+            after_index: vec![append("index $RETROARCH_INDEXER_ROMS")],
+            ..Default::default()
+        });
+        let mut index = IncrementalIndex::new(Scanner::new().with_threads(1), PlaylistBuilder::new(), output_dir.path().to_path_buf())
+            .with_master_playlist(false)
+            .with_hooks(hooks);
+
+        index.rebuild(&[roms.to_path_buf()]).unwrap();
+        fs::write(roms.join("Gamma.gba"), b"gba rom two").unwrap();
+        index.apply(&[FileEvent::Created(roms.join("Gamma.gba"))]).unwrap();
+
+        let lines: Vec<String> = fs::read_to_string(&log).unwrap().lines().map(str::to_string).collect();
+        assert_eq!(lines, vec![
+            "playlist Nintendo - Game Boy Advance 1",
+            "index 1",
+            "playlist Nintendo - Game Boy Advance 2",
+            "batch 1",
+        ]);
+    }
+
+    #[test]
+    fn test_superseded_rename_keeps_source_deletion() {
+        let watcher = RomWatcher::new(WatchConfig::default()).unwrap();
+        let events = watcher.deduplicate_events(vec![
+            FileEvent::Renamed { from: PathBuf::from("old.nes"), to: PathBuf::from("new.nes") },
+            FileEvent::Modified(PathBuf::from("new.nes")),
+        ]);
+
+        assert_eq!(events.len(), 2);
+        assert!(matches!(&events[0], FileEvent::Deleted(path) if path == Path::new("old.nes")));
+        assert!(matches!(&events[1], FileEvent::Modified(path) if path == Path::new("new.nes")));
+    }
+
+    #[test]
+    fn test_rename_halves_paired_by_tracker() {
+        use notify::event::{ModifyKind, RenameMode};
+
+        let mut watcher = RomWatcher::new(WatchConfig {
+            debounce_duration: Duration::ZERO,
+            ..WatchConfig::default()
+        }).unwrap();
+        let rename = |mode, path: &str, cookie| {
+            Event::new(notify::EventKind::Modify(ModifyKind::Name(mode)))
+                .add_path(PathBuf::from(path))
+                .set_tracker(cookie)
+        };
+
+        assert!(watcher.convert_event(rename(RenameMode::From, "a/Game.sfc", 7)).unwrap().is_none());
+        let event = watcher.convert_event(rename(RenameMode::To, "b/Game.sfc", 7)).unwrap();
+        assert!(matches!(event, Some(FileEvent::Renamed { from, to })
+            if from == Path::new("a/Game.sfc") && to == Path::new("b/Game.sfc")));
+
+        // Moved out of the watched tree: the destination never shows up
+        assert!(watcher.convert_event(rename(RenameMode::From, "a/Other.sfc", 8)).unwrap().is_none());
+        let expired = watcher.expire_renames();
+        assert!(matches!(expired.as_slice(), [FileEvent::Deleted(path)] if path == Path::new("a/Other.sfc")));
+
+        // Moved in from outside
+        let event = watcher.convert_event(rename(RenameMode::To, "b/New.sfc", 9)).unwrap();
+        assert!(matches!(event, Some(FileEvent::Created(path)) if path == Path::new("b/New.sfc")));
+    }
+
+    #[test]
+    fn test_rename_both_after_paired_halves_is_dropped() {
+        use notify::event::{ModifyKind, RenameMode};
+
+        let mut watcher = RomWatcher::new(WatchConfig {
+            scan_delay: Duration::ZERO,
+            ..WatchConfig::default()
+        }).unwrap();
+        let rename = |mode, paths: &[&str]| {
+            let event = Event::new(notify::EventKind::Modify(ModifyKind::Name(mode))).set_tracker(3);
+            paths.iter().fold(event, |event, path| event.add_path(PathBuf::from(path)))
+        };
+
+        // inotify reports From and To, then Both once the pair is known; here
+        // Both lands in the next batch, where deduplication can't merge it
+        watcher.event_sender.send(Ok(rename(RenameMode::From, &["a/Game.sfc"]))).unwrap();
+        watcher.event_sender.send(Ok(rename(RenameMode::To, &["b/Game.sfc"]))).unwrap();
+        let events = watcher.process_events().unwrap();
+        assert!(matches!(events.as_slice(), [FileEvent::Renamed { from, to }]
+            if from == Path::new("a/Game.sfc") && to == Path::new("b/Game.sfc")));
+
+        watcher.event_sender.send(Ok(rename(RenameMode::Both, &["a/Game.sfc", "b/Game.sfc"]))).unwrap();
+        assert!(watcher.process_events().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_directory_rename_moves_entries_without_rehashing() {
+        let roms_dir = tempdir().unwrap();
+        let output_dir = tempdir().unwrap();
+        let old_dir = roms_dir.path().join("incoming");
+        fs::create_dir(&old_dir).unwrap();
+        fs::write(old_dir.join("Alpha.gba"), b"gba rom one").unwrap();
+        fs::write(old_dir.join("Beta.gba"), b"gba rom two").unwrap();
+
+        let cache = crate::cache::CrcCache::new().into_shared();
+        let mut index = IncrementalIndex::new(
+            Scanner::new().with_threads(1).with_crc_cache(cache.clone()),
+            PlaylistBuilder::new(),
+            output_dir.path().to_path_buf(),
+        );
+        index.rebuild(&[roms_dir.path().to_path_buf()]).unwrap();
+        let misses = cache.lock().get_stats().unwrap().cache_misses;
+
+        // The user renames one entry by hand
+        let playlist_path = output_dir.path().join("Nintendo - Game Boy Advance.lpl");
+        let mut playlist = Playlist::load(&playlist_path).unwrap();
+        playlist.items[0].label = "Alpha (My Favourite)".to_string();
+        playlist.save(&playlist_path).unwrap();
+
+        let new_dir = roms_dir.path().join("sorted");
+        fs::rename(&old_dir, &new_dir).unwrap();
+        let update = index.apply(&[FileEvent::Renamed { from: old_dir.clone(), to: new_dir.clone() }]).unwrap();
+
+        assert_eq!((update.rescanned, update.removed), (0, 0));
+        assert_eq!(cache.lock().get_stats().unwrap().cache_misses, misses);
+        let playlist = Playlist::load(&playlist_path).unwrap();
+        let entries: Vec<(String, String)> = playlist.items.into_iter().map(|item| (item.label, item.path)).collect();
+        assert_eq!(entries, vec![
+            ("Alpha (My Favourite)".to_string(), new_dir.join("Alpha.gba").to_string_lossy().to_string()),
+            ("Beta".to_string(), new_dir.join("Beta.gba").to_string_lossy().to_string()),
+        ]);
+        assert!(index.roms().all(|rom| rom.path.starts_with(&new_dir) && rom.crc32.is_some()));
+    }
 }
@@ -0,0 +1,300 @@
+//! Control socket of the watch daemon: one JSON request per line over a Unix
+//! domain socket, answered by one JSON response line
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use log::{debug, warn};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::mpsc::{Receiver, Sender};
+
+/// How long a connection may stay idle, and how long it waits for the daemon
+const CONNECTION_TIMEOUT_SECS: u64 = 30;
+
+/// Command sent to a running daemon
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum ControlRequest {
+    Status,
+    /// Keep collecting file events but stop applying them
+    Pause,
+    Resume,
+    /// Start watching and indexing another directory
+    Watch { path: PathBuf },
+    /// Stop watching a directory and drop its ROMs from the playlists
+    Unwatch { path: PathBuf },
+    /// Rescan every watched directory from scratch
+    Rescan,
+    Stop,
+}
+
+/// Reply to a `ControlRequest`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum ControlResponse {
+    Ok { message: String },
+    Status(DaemonStatus),
+    Error { message: String },
+}
+
+/// Snapshot of the daemon returned by `ControlRequest::Status`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    pub paused: bool,
+    pub watched_directories: Vec<PathBuf>,
+    /// Watched directories scanned periodically instead of through native events
+    pub polled_directories: Vec<PathBuf>,
+    /// Events received but not applied yet, including those held while paused
+    pub queued_events: usize,
+    /// Rescans and newly watched directories waiting to be indexed
+    #[serde(default)]
+    pub scheduled_scans: usize,
+    /// A full scan of every watched directory is queued or running
+    #[serde(default)]
+    pub rebuilding: bool,
+    pub indexed_files: usize,
+    pub started_at: Option<DateTime<Utc>>,
+    /// Last full scan of every watched directory
+    pub last_rebuild: Option<DateTime<Utc>>,
+    /// Last batch of file events applied to the playlists
+    pub last_update: Option<DateTime<Utc>>,
+    pub updates_applied: usize,
+    pub last_error: Option<String>,
+}
+
+/// Request received on the socket, waiting for the daemon's answer
+pub struct PendingRequest {
+    pub request: ControlRequest,
+    reply: Sender<ControlResponse>,
+}
+
+impl PendingRequest {
+    pub fn respond(self, response: ControlResponse) {
+        // The client may have hung up already
+        let _ = self.reply.send(response);
+    }
+}
+
+impl ControlResponse {
+    pub fn ok(message: impl Into<String>) -> Self {
+        Self::Ok { message: message.into() }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::Error { message: message.into() }
+    }
+}
+
+/// Socket used when none is given: the user's runtime directory, or the
+/// temporary directory where there is none
+pub fn default_socket_path() -> PathBuf {
+    dirs::runtime_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("retroarch-indexer.sock")
+}
+
+/// Listening end of the control socket. Connections are served on
+/// background threads and their requests queued for the daemon loop;
+/// `Status` is answered from the last published snapshot, so it stays
+/// available while the loop is busy with a long scan.
+pub struct ControlServer {
+    path: PathBuf,
+    requests: Receiver<PendingRequest>,
+    status: Arc<Mutex<Option<DaemonStatus>>>,
+}
+
+#[cfg(unix)]
+impl ControlServer {
+    /// Listen on `path`, replacing a socket left behind by a daemon that
+    /// is no longer running
+    pub fn bind(path: &Path) -> Result<Self> {
+        use std::os::unix::fs::PermissionsExt;
+        use std::os::unix::net::{UnixListener, UnixStream};
+
+        if path.exists() {
+            if UnixStream::connect(path).is_ok() {
+                anyhow::bail!("Já existe um daemon escutando em {}", path.display());
+            }
+            std::fs::remove_file(path)
+                .with_context(|| format!("Failed to remove stale socket: {}", path.display()))?;
+        }
+
+        let listener = UnixListener::bind(path)
+            .with_context(|| format!("Failed to bind control socket: {}", path.display()))?;
+        // Only the owner may steer the daemon
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let status = Arc::new(Mutex::new(None));
+        let snapshot = Arc::clone(&status);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        let tx = tx.clone();
+                        let snapshot = Arc::clone(&snapshot);
+                        std::thread::spawn(move || {
+                            if let Err(e) = serve_connection(stream, &tx, &snapshot) {
+                                debug!("Control connection closed: {:#}", e);
+                            }
+                        });
+                    }
+                    Err(e) => warn!("Failed to accept control connection: {}", e),
+                }
+            }
+        });
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            requests: rx,
+            status,
+        })
+    }
+}
+
+#[cfg(not(unix))]
+impl ControlServer {
+    pub fn bind(_path: &Path) -> Result<Self> {
+        anyhow::bail!("O socket de controle só está disponível em sistemas Unix")
+    }
+}
+
+impl ControlServer {
+    /// Next queued request, without blocking
+    pub fn try_recv(&self) -> Option<PendingRequest> {
+        self.requests.try_recv().ok()
+    }
+
+    /// Snapshot answered to `Status` requests from now on
+    pub fn publish_status(&self, status: DaemonStatus) {
+        *self.status.lock() = Some(status);
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(unix)]
+fn serve_connection(
+    stream: std::os::unix::net::UnixStream,
+    requests: &Sender<PendingRequest>,
+    status: &Mutex<Option<DaemonStatus>>,
+) -> Result<()> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::time::Duration;
+
+    let timeout = Duration::from_secs(CONNECTION_TIMEOUT_SECS);
+    stream.set_read_timeout(Some(timeout))?;
+    let mut writer = stream.try_clone()?;
+
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<ControlRequest>(&line) {
+            Ok(request) => {
+                let snapshot = (request == ControlRequest::Status).then(|| status.lock().clone()).flatten();
+                match snapshot {
+                    Some(snapshot) => ControlResponse::Status(snapshot),
+                    None => {
+                        let (reply, answer) = std::sync::mpsc::channel();
+                        requests.send(PendingRequest { request, reply })
+                            .map_err(|_| anyhow::anyhow!("Watch daemon stopped"))?;
+                        answer.recv_timeout(timeout)
+                            .unwrap_or_else(|_| ControlResponse::error("O daemon não respondeu a tempo"))
+                    }
+                }
+            }
+            Err(e) => ControlResponse::error(format!("Comando inválido: {}", e)),
+        };
+
+        serde_json::to_writer(&mut writer, &response)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+    }
+
+    Ok(())
+}
+
+/// Send one request to the daemon listening on `socket` and wait for its answer
+#[cfg(unix)]
+pub fn send(socket: &Path, request: &ControlRequest) -> Result<ControlResponse> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+    use std::time::Duration;
+
+    let mut stream = UnixStream::connect(socket)
+        .with_context(|| format!("Nenhum daemon escutando em {}", socket.display()))?;
+    // Scans are acknowledged before they run; leave room for the daemon's own timeout reply
+    stream.set_read_timeout(Some(Duration::from_secs(CONNECTION_TIMEOUT_SECS + 5)))?;
+
+    serde_json::to_writer(&mut stream, request)?;
+    stream.write_all(b"\n")?;
+    stream.flush()?;
+
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)
+        .with_context(|| "Failed to read daemon response")?;
+    serde_json::from_str(&line).with_context(|| format!("Invalid daemon response: {}", line.trim()))
+}
+
+#[cfg(not(unix))]
+pub fn send(_socket: &Path, _request: &ControlRequest) -> Result<ControlResponse> {
+    anyhow::bail!("O socket de controle só está disponível em sistemas Unix")
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_request_round_trip_over_socket() {
+        let dir = tempdir().unwrap();
+        let socket = dir.path().join("control.sock");
+        let server = ControlServer::bind(&socket).unwrap();
+
+        let client_socket = socket.clone();
+        let client = std::thread::spawn(move || {
+            send(&client_socket, &ControlRequest::Watch { path: PathBuf::from("/roms/snes") }).unwrap()
+        });
+
+        let pending = loop {
+            if let Some(pending) = server.try_recv() {
+                break pending;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        };
+        assert_eq!(pending.request, ControlRequest::Watch { path: PathBuf::from("/roms/snes") });
+        pending.respond(ControlResponse::ok("watching"));
+
+        match client.join().unwrap() {
+            ControlResponse::Ok { message } => assert_eq!(message, "watching"),
+            other => panic!("unexpected response: {:?}", other),
+        }
+
+        // Status comes from the published snapshot without waiting for the loop
+        server.publish_status(DaemonStatus { rebuilding: true, ..Default::default() });
+        match send(&socket, &ControlRequest::Status).unwrap() {
+            ControlResponse::Status(status) => assert!(status.rebuilding),
+            other => panic!("unexpected response: {:?}", other),
+        }
+        assert!(server.try_recv().is_none());
+
+        // A second daemon must not steal the socket of a live one
+        assert!(ControlServer::bind(&socket).is_err());
+        drop(server);
+        assert!(!socket.exists());
+    }
+}
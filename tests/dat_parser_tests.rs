@@ -100,3 +100,137 @@ game (
     // Should have no entries due to invalid CRC
     assert_eq!(collection.total_entries(), 0);
 }
+
+#[test]
+fn test_logiqx_xml_dat_parsing() {
+    let sample_dat = r#"<?xml version="1.0"?>
+<!DOCTYPE datafile PUBLIC "-//Logiqx//DTD ROM Management Datafile//EN" "http://www.logiqx.com/dats/datafile.dtd">
+<datafile>
+    <header>
+        <name>Nintendo - Super Nintendo Entertainment System</name>
+        <description>Nintendo - Super Nintendo Entertainment System</description>
+        <version>20240101-000000</version>
+        <clrmamepro header="No-Intro_SNES.xml"/>
+    </header>
+    <game name="Super Mario World (USA)">
+        <description>Super Mario World (USA)</description>
+        <rom name="Super Mario World (USA).sfc" size="524288" crc="B19ED489" md5="CDD3C8C37322978CA8669B34BC89C804" sha1="6b47bb75d16514b6a476aa0c73a683a2a4c18765"/>
+    </game>
+    <game name="Super Mario World (Japan)" cloneof="Super Mario World (USA)">
+        <description>Super Mario World - Super Mario Bros. 4 (Japan)</description>
+        <rom name="Super Mario World (Japan).sfc" size="524288" crc="a6ee4dd5" status="baddump"/>
+    </game>
+    <machine name="nodump">
+        <description>No Dump &amp; Friends</description>
+        <rom name="missing.bin" size="1024" status="nodump"/>
+    </machine>
+</datafile>
+"#;
+
+    let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+    temp_file.write_all(sample_dat.as_bytes()).expect("Failed to write to temp file");
+
+    let mut collection = DatCollection::new();
+    collection.load_dat_file(temp_file.path()).expect("Failed to parse DAT");
+
+    assert_eq!(collection.total_entries(), 2);
+
+    let smw = collection.find_by_crc32(0xb19ed489).unwrap();
+    assert_eq!(smw.name, "Super Mario World (USA)");
+    assert_eq!(smw.size, Some(524288));
+    assert_eq!(smw.rom_name.as_deref(), Some("Super Mario World (USA).sfc"));
+    assert_eq!(smw.md5.as_deref(), Some("cdd3c8c37322978ca8669b34bc89c804"));
+    assert_eq!(smw.sha1.as_deref(), Some("6b47bb75d16514b6a476aa0c73a683a2a4c18765"));
+
+    let japan = collection.find_by_crc32(0xa6ee4dd5).unwrap();
+    assert_eq!(japan.name, "Super Mario World - Super Mario Bros. 4 (Japan)");
+    assert_eq!(japan.clone_of.as_deref(), Some("Super Mario World (USA)"));
+    assert_eq!(japan.status.as_deref(), Some("baddump"));
+
+    let system = "Nintendo - Super Nintendo Entertainment System";
+    assert_eq!(collection.get_system_entries(system).unwrap().len(), 2);
+    let header = collection.get_header(system).unwrap();
+    assert_eq!(header.version.as_deref(), Some("20240101-000000"));
+    assert_eq!(header.header_skipper.as_deref(), Some("No-Intro_SNES.xml"));
+}
+
+#[test]
+fn test_crc_collision_narrowed_by_size_and_sha1() {
+    use retroarch_fast_indexer::crc32::{CrcVariant, Digests, HashCandidate};
+    use retroarch_fast_indexer::dat_parser::MatchedBy;
+
+    let sample_dat = r#"<?xml version="1.0"?>
+<datafile>
+    <header><name>Collision Test</name></header>
+    <game name="Game A">
+        <rom name="a.bin" size="1024" crc="deadbeef" sha1="aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"/>
+    </game>
+    <game name="Game B">
+        <rom name="b.bin" size="2048" crc="deadbeef" sha1="bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"/>
+    </game>
+    <game name="Game C">
+        <rom name="c.bin" size="2048" crc="deadbeef" sha1="cccccccccccccccccccccccccccccccccccccccc"/>
+    </game>
+</datafile>
+"#;
+
+    let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+    temp_file.write_all(sample_dat.as_bytes()).expect("Failed to write to temp file");
+
+    let mut collection = DatCollection::new();
+    collection.load_dat_file(temp_file.path()).expect("Failed to parse DAT");
+    assert_eq!(collection.find_all_by_crc32(0xdeadbeef).len(), 3);
+
+    let candidate = |size: u64, sha1: Option<&str>| vec![HashCandidate {
+        variant: CrcVariant::Raw,
+        crc32: 0xdeadbeef,
+        digests: Some(Digests { size, sha1: sha1.map(str::to_string), ..Default::default() }),
+    }];
+
+    // Size alone singles out Game A
+    let found = collection.find_match(&candidate(1024, None)).unwrap();
+    assert_eq!(found.entry.name, "Game A");
+    assert_eq!(found.matched_by, MatchedBy::Size);
+    assert!(!found.ambiguous);
+
+    // B and C share a size; SHA1 decides
+    let found = collection.find_match(&candidate(2048, Some("CCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCC"))).unwrap();
+    assert_eq!(found.entry.name, "Game C");
+    assert_eq!(found.matched_by, MatchedBy::Sha1);
+
+    let found = collection.find_match(&candidate(2048, None)).unwrap();
+    assert!(found.ambiguous);
+
+    // A size no entry has is not a match
+    assert!(collection.find_match(&candidate(4096, None)).is_none());
+}
+
+#[test]
+fn test_logiqx_xml_dat_with_utf8_bom() {
+    // Saved by a Windows editor: the BOM comes before the XML declaration, and
+    // the header mentions clrmamepro, which the ClrMamePro parser keys on
+    let sample_dat = "\u{feff}<?xml version=\"1.0\"?>
+<datafile>
+    <header>
+        <name>Sega - Mega Drive - Genesis</name>
+        <clrmamepro/>
+    </header>
+    <game name=\"Sonic The Hedgehog (USA, Europe)\">
+        <description>Sonic The Hedgehog (USA, Europe)</description>
+        <rom name=\"Sonic The Hedgehog (USA, Europe).md\" size=\"524288\" crc=\"f9394e97\"/>
+    </game>
+</datafile>
+";
+
+    let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+    temp_file.write_all(sample_dat.as_bytes()).expect("Failed to write to temp file");
+
+    let mut collection = DatCollection::new();
+    collection.load_dat_file(temp_file.path()).expect("Failed to parse DAT");
+
+    assert_eq!(collection.total_entries(), 1);
+    let sonic = collection.find_by_crc32(0xf9394e97).unwrap();
+    assert_eq!(sonic.name, "Sonic The Hedgehog (USA, Europe)");
+    assert_eq!(sonic.size, Some(524288));
+    assert!(collection.get_header("Sega - Mega Drive - Genesis").is_some());
+}